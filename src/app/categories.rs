@@ -0,0 +1,347 @@
+use leptos::{
+    component, create_action, create_resource, create_rw_signal, event_target_value, server, view,
+    Action, CollectView, IntoView, ServerFnError, SignalGet, SignalGetUntracked, SignalSet,
+    SignalWith, Suspense, Transition,
+};
+use leptos_router::{use_params_map, A};
+use serde::{Deserialize, Serialize};
+
+use crate::app::recipes::CategoryRecipes;
+
+#[component]
+pub fn CategoriesView() -> impl IntoView {
+    let categories = create_resource(|| (), |()| get_categories());
+
+    view! {
+        <h2> "Categories" </h2>
+        <Suspense fallback=|| view! { <p> "Loading..." </p> }>
+            {move || categories.get().map(|categories| match categories {
+                Ok(categories) => categories.into_iter().map(|category| view! {
+                    <p>
+                        <A href=format!("/categories/{}", category.id)> {category.name} </A>
+                    </p>
+                }).collect_view(),
+                Err(_) => view! { <p> "Failed to query categories" </p> }.into_view(),
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+pub fn CategoryView() -> impl IntoView {
+    let params = use_params_map();
+    let category = create_resource(
+        move || params.with(|params| params.get("id").cloned().unwrap_or_default()),
+        get_category,
+    );
+
+    view! {
+        <Suspense fallback=|| view! { <p> "Loading..." </p> }>
+            {move || category.get().map(|category| match category {
+                Ok(Some(category)) => view! {
+                    <h2> {category.name} </h2>
+                    <CategoryRecipes category=category.id/>
+                }.into_view(),
+                Ok(None) => view! { <h2> "Category not found" </h2> }.into_view(),
+                Err(_) => view! { <p> "Failed to query category" </p> }.into_view(),
+            })}
+        </Suspense>
+    }
+}
+
+/// Admin page to create, rename and delete categories
+#[component]
+pub fn CategoriesAdminView() -> impl IntoView {
+    let categories = create_resource(|| (), |()| get_categories());
+    let error = create_rw_signal(None);
+    let name = create_rw_signal(String::new());
+
+    let new_action = create_action(move |name_value: &String| {
+        error.set(None);
+
+        let name_value = name_value.clone();
+
+        async move {
+            let result = new_category(name_value).await;
+            if result == Ok(CategoryChange::Done) {
+                name.set(String::new());
+            }
+            error.set(change_error(result));
+            categories.refetch();
+        }
+    });
+
+    let rename_action = create_action(move |(id, name): &(String, String)| {
+        error.set(None);
+
+        let id = id.clone();
+        let name = name.clone();
+
+        async move {
+            error.set(change_error(rename_category(id, name).await));
+            categories.refetch();
+        }
+    });
+
+    let delete_action = create_action(move |id: &String| {
+        error.set(None);
+
+        let id = id.clone();
+
+        async move {
+            error.set(change_error(delete_category(id).await));
+            categories.refetch();
+        }
+    });
+
+    view! {
+        <h2> "Manage categories" </h2>
+        <Transition fallback=|| view! { <p> "Loading..." </p> }>
+            {move || categories.get().map(|categories| match categories {
+                Ok(categories) => categories.into_iter().map(|category| view! {
+                    <CategoryAdminRow
+                        category=category
+                        rename_action=rename_action
+                        delete_action=delete_action
+                    />
+                }).collect_view(),
+                Err(_) => view! { <p> "Failed to query categories" </p> }.into_view(),
+            })}
+        </Transition>
+        <div>
+            <input
+                prop:value=move || name.get()
+                on:input=move |ev| name.set(event_target_value(&ev))
+            />
+            <button on:click=move |_| new_action.dispatch(name.get_untracked())> "Create" </button>
+        </div>
+        {move || error.get()}
+    }
+}
+
+#[component]
+fn CategoryAdminRow(
+    category: CategoryInfo,
+    rename_action: Action<(String, String), ()>,
+    delete_action: Action<String, ()>,
+) -> impl IntoView {
+    let name = create_rw_signal(category.name);
+
+    let id = category.id.clone();
+    let rename = move |_| rename_action.dispatch((id.clone(), name.get_untracked()));
+
+    let id = category.id.clone();
+    let delete = move |_| delete_action.dispatch(id.clone());
+
+    view! {
+        <div style="display:flex;flex-direction:row;">
+            <input
+                prop:value=name.get_untracked()
+                on:input=move |ev| name.set(event_target_value(&ev))
+            />
+            <button on:click=rename> "Rename" </button>
+            <button on:click=delete> "Delete" </button>
+            <A href=format!("/categories/{}", category.id)> "Recipes" </A>
+        </div>
+    }
+}
+
+/// Message telling why a change of a category failed, `None` if it succeeded
+fn change_error(result: Result<CategoryChange, ServerFnError>) -> Option<&'static str> {
+    match result {
+        Ok(CategoryChange::Done) => None,
+        Ok(CategoryChange::InvalidCredentials) => {
+            Some("You must be signed in to manage categories")
+        }
+        Ok(CategoryChange::Forbidden) => Some("Only admins can manage categories"),
+        Ok(CategoryChange::NotFound) => Some("This category no longer exists"),
+        Ok(CategoryChange::NameAlreadyTaken) => Some("A category already has this name"),
+        Ok(CategoryChange::InvalidName) => Some("The name must not be empty nor too long"),
+        Err(ServerFnError::Request(_)) => Some("Network error"),
+        Err(_) => Some("Internal error, retry later"),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CategoryInfo {
+    pub id: String,
+    pub name: String,
+}
+
+/// Outcome of a change requested on the categories
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CategoryChange {
+    Done,
+    InvalidCredentials,
+    Forbidden,
+    NotFound,
+    NameAlreadyTaken,
+    InvalidName,
+}
+
+#[server(GetCategories, encoding = "GetCbor")]
+pub async fn get_categories() -> Result<Vec<CategoryInfo>, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::database::{categories::GetCategoriesError, Database};
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.get_categories().await {
+        Ok(categories) => Ok(categories
+            .into_iter()
+            .map(|category| CategoryInfo {
+                id: category.id.to_hex(),
+                name: category.name,
+            })
+            .collect()),
+        Err(GetCategoriesError::Database(err)) => {
+            error!("Database error while getting categories, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+    }
+}
+
+#[server(GetCategory, encoding = "GetCbor")]
+async fn get_category(id: String) -> Result<Option<CategoryInfo>, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::database::{categories::GetCategoryError, Database};
+
+    let Ok(id) = ObjectId::parse_str(id) else {
+        return Ok(None);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.get_category(id).await {
+        Ok(category) => Ok(category.map(|category| CategoryInfo {
+            id: category.id.to_hex(),
+            name: category.name,
+        })),
+        Err(GetCategoryError::Database(err)) => {
+            error!("Database error while getting category, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+    }
+}
+
+#[server(NewCategory, encoding = "Cbor")]
+async fn new_category(name: String) -> Result<CategoryChange, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{categories::NewCategoryError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(CategoryChange::InvalidCredentials);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.new_category(user_name, user_token, name).await {
+        Ok(()) => Ok(CategoryChange::Done),
+        Err(NewCategoryError::Database(err)) => {
+            error!("Database error while creating category, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(NewCategoryError::InvalidCredentials) => Ok(CategoryChange::InvalidCredentials),
+        Err(NewCategoryError::Forbidden) => Ok(CategoryChange::Forbidden),
+        Err(NewCategoryError::NameAlreadyTaken) => Ok(CategoryChange::NameAlreadyTaken),
+        Err(NewCategoryError::InvalidName) => Ok(CategoryChange::InvalidName),
+    }
+}
+
+#[server(RenameCategory, encoding = "Cbor")]
+async fn rename_category(id: String, name: String) -> Result<CategoryChange, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{categories::RenameCategoryError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(CategoryChange::InvalidCredentials);
+    };
+
+    let Ok(id) = ObjectId::parse_str(id) else {
+        return Ok(CategoryChange::NotFound);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .rename_category(user_name, user_token, id, name)
+        .await
+    {
+        Ok(()) => Ok(CategoryChange::Done),
+        Err(RenameCategoryError::Database(err)) => {
+            error!("Database error while renaming category, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(RenameCategoryError::InvalidCredentials) => Ok(CategoryChange::InvalidCredentials),
+        Err(RenameCategoryError::Forbidden) => Ok(CategoryChange::Forbidden),
+        Err(RenameCategoryError::NotFound) => Ok(CategoryChange::NotFound),
+        Err(RenameCategoryError::NameAlreadyTaken) => Ok(CategoryChange::NameAlreadyTaken),
+        Err(RenameCategoryError::InvalidName) => Ok(CategoryChange::InvalidName),
+    }
+}
+
+#[server(DeleteCategory, encoding = "Cbor")]
+async fn delete_category(id: String) -> Result<CategoryChange, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{categories::DeleteCategoryError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(CategoryChange::InvalidCredentials);
+    };
+
+    let Ok(id) = ObjectId::parse_str(id) else {
+        return Ok(CategoryChange::NotFound);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.delete_category(user_name, user_token, id).await {
+        Ok(()) => Ok(CategoryChange::Done),
+        Err(DeleteCategoryError::Database(err)) => {
+            error!("Database error while deleting category, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(DeleteCategoryError::InvalidCredentials) => Ok(CategoryChange::InvalidCredentials),
+        Err(DeleteCategoryError::Forbidden) => Ok(CategoryChange::Forbidden),
+        Err(DeleteCategoryError::NotFound) => Ok(CategoryChange::NotFound),
+    }
+}
@@ -0,0 +1,439 @@
+use leptos::{
+    component, create_action, create_resource, create_rw_signal, event_target_value, server, view,
+    Action, CollectView, IntoView, ServerFnError, SignalGet, SignalGetUntracked, SignalSet,
+    Transition,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{recipe::RecipeChange, users::AuthorLink};
+
+/// Paginated comments of the recipe `recipe`, with a form to add one
+#[component]
+pub fn CommentThread(recipe: String) -> impl IntoView {
+    // `None` for the last page
+    let page = create_rw_signal(Some(0));
+    let error = create_rw_signal(None);
+    let content = create_rw_signal(String::new());
+
+    let comments = create_resource(
+        {
+            let recipe = recipe.clone();
+            move || (recipe.clone(), page.get())
+        },
+        |(recipe, page)| get_comments(recipe, page.unwrap_or(0), page.is_none()),
+    );
+
+    let new_action = create_action({
+        let recipe = recipe.clone();
+        move |comment_content: &String| {
+            error.set(None);
+
+            let recipe = recipe.clone();
+            let comment_content = comment_content.clone();
+
+            async move {
+                let result = new_comment(recipe, comment_content).await;
+                if result == Ok(RecipeChange::Done) {
+                    content.set(String::new());
+                    // The new comment is the last one
+                    if page.get_untracked().is_none() {
+                        comments.refetch();
+                    } else {
+                        page.set(None);
+                    }
+                }
+                error.set(change_error(result, "Banned users can't comment"));
+            }
+        }
+    });
+
+    let update_action = create_action({
+        let recipe = recipe.clone();
+        move |(comment, comment_content): &(String, String)| {
+            error.set(None);
+
+            let recipe = recipe.clone();
+            let comment = comment.clone();
+            let comment_content = comment_content.clone();
+
+            async move {
+                let result = update_comment(recipe, comment, comment_content).await;
+                error.set(change_error(
+                    result,
+                    "Only the author or an admin can edit this comment",
+                ));
+                comments.refetch();
+            }
+        }
+    });
+
+    let delete_action = create_action(move |comment: &String| {
+        error.set(None);
+
+        let recipe = recipe.clone();
+        let comment = comment.clone();
+
+        async move {
+            let result = delete_comment(recipe, comment).await;
+            error.set(change_error(
+                result,
+                "Only the author or an admin can delete this comment",
+            ));
+            comments.refetch();
+        }
+    });
+
+    let submit = move |_| {
+        let content = content.get_untracked();
+        if !content.trim().is_empty() {
+            new_action.dispatch(content);
+        }
+    };
+
+    view! {
+        <div class="comments">
+            <Transition fallback=|| view! { <p> "Loading comments..." </p> }>
+                {move || comments.get().map(|comments| match comments {
+                    Ok(Some(comments)) => {
+                        let current = comments.page;
+                        let page_count = comments.page_count;
+
+                        view! {
+                            {comments.comments.into_iter().map(|comment| view! {
+                                <CommentView
+                                    comment=comment
+                                    update_action=update_action
+                                    delete_action=delete_action
+                                />
+                            }).collect_view()}
+
+                            <div class="comments_pages">
+                                <button
+                                    disabled=current == 0
+                                    on:click=move |_| page.set(Some(current.saturating_sub(1)))
+                                > "Previous" </button>
+                                " Page " {current + 1} "/" {page_count} " "
+                                <button
+                                    disabled=current + 1 >= page_count
+                                    on:click=move |_| page.set(Some(current + 1))
+                                > "Next" </button>
+                            </div>
+                        }.into_view()
+                    }
+                    Ok(None) => view! {}.into_view(),
+                    Err(_) => view! { <p> "Failed to query comments" </p> }.into_view(),
+                })}
+            </Transition>
+
+            <textarea
+                prop:value=move || content.get()
+                on:input=move |ev| content.set(event_target_value(&ev))
+            />
+            <button on:click=submit> "Comment" </button>
+            {move || error.get()}
+        </div>
+    }
+}
+
+#[component]
+fn CommentView(
+    comment: CommentInfo,
+    update_action: Action<(String, String), ()>,
+    delete_action: Action<String, ()>,
+) -> impl IntoView {
+    let editing = create_rw_signal(false);
+    let draft = create_rw_signal(comment.content.clone());
+
+    let id = comment.id.clone();
+    let save = move |_| {
+        editing.set(false);
+        update_action.dispatch((id.clone(), draft.get_untracked()));
+    };
+
+    let id = comment.id;
+    let delete = move |_| delete_action.dispatch(id.clone());
+
+    let content = comment.content;
+    let editable = comment.editable;
+
+    view! {
+        <div class="comment">
+            <p>
+                <b> <AuthorLink author=comment.author/> </b>
+                " " {relative_date(comment.age)}
+            </p>
+            {move || if editing.get() {
+                view! {
+                    <textarea
+                        prop:value=draft.get_untracked()
+                        on:input=move |ev| draft.set(event_target_value(&ev))
+                    />
+                    <button on:click=save.clone()> "Save" </button>
+                    <button on:click=move |_| editing.set(false)> "Cancel" </button>
+                }.into_view()
+            } else {
+                view! {
+                    <p> {content.clone()} </p>
+                    {editable.then(|| view! {
+                        <button on:click=move |_| editing.set(true)> "Edit" </button>
+                    })}
+                }.into_view()
+            }}
+            {editable.then(|| view! {
+                <button on:click=delete> "Delete" </button>
+            })}
+        </div>
+    }
+}
+
+/// Message telling why a change of a comment failed, `None` if it succeeded
+fn change_error(
+    result: Result<RecipeChange, ServerFnError>,
+    forbidden: &'static str,
+) -> Option<String> {
+    match result {
+        Ok(RecipeChange::Done) => None,
+        Ok(RecipeChange::InvalidCredentials) => Some("You must be signed in to comment".into()),
+        Ok(RecipeChange::NotFound) => Some("This comment no longer exists".into()),
+        Ok(RecipeChange::Forbidden) => Some(forbidden.into()),
+        Ok(RecipeChange::Invalid(errors)) if !errors.is_empty() => {
+            Some(format!("Comment: {}", errors[0]))
+        }
+        Err(ServerFnError::Request(_)) => Some("Network error".into()),
+        Ok(RecipeChange::Invalid(_)) | Err(_) => Some("Internal error, retry later".into()),
+    }
+}
+
+/// Human readable time elapsed since `seconds` ago
+fn relative_date(seconds: u64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    const MONTH: u64 = 30 * DAY;
+    const YEAR: u64 = 365 * DAY;
+
+    let (count, unit) = match seconds {
+        0..MINUTE => return "just now".to_string(),
+        MINUTE..HOUR => (seconds / MINUTE, "minute"),
+        HOUR..DAY => (seconds / HOUR, "hour"),
+        DAY..MONTH => (seconds / DAY, "day"),
+        MONTH..YEAR => (seconds / MONTH, "month"),
+        _ => (seconds / YEAR, "year"),
+    };
+
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CommentInfo {
+    pub id: String,
+    /// `None` if the author no longer exists
+    pub author: Option<String>,
+    pub content: String,
+    /// Seconds elapsed since the comment was posted
+    pub age: u64,
+    /// Whether the viewer is the author or an admin, and can edit and delete the comment
+    pub editable: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CommentPageInfo {
+    pub comments: Vec<CommentInfo>,
+    pub page: usize,
+    pub page_count: usize,
+}
+
+#[server(GetComments, encoding = "GetCbor")]
+async fn get_comments(
+    recipe: String,
+    page: usize,
+    last: bool,
+) -> Result<Option<CommentPageInfo>, ServerFnError> {
+    use bson::{oid::ObjectId, DateTime};
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::Cookies,
+        database::{comments::GetCommentsError, Database},
+    };
+
+    const COMMENTS_PER_PAGE: usize = 20;
+
+    let Ok(recipe) = ObjectId::parse_str(recipe) else {
+        return Ok(None);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    let now = DateTime::now().timestamp_millis();
+
+    let page = (!last).then_some(page);
+
+    let credentials = Cookies::credentials().ok();
+    let viewer = credentials
+        .as_ref()
+        .map(|credentials| (credentials.name.as_str(), credentials.token.as_str()));
+
+    match database
+        .get_comments(viewer, recipe, page, COMMENTS_PER_PAGE)
+        .await
+    {
+        Ok(comments) => Ok(comments.map(|comments| CommentPageInfo {
+            comments: comments
+                .comments
+                .into_iter()
+                .map(|entry| CommentInfo {
+                    id: entry.comment.id.to_hex(),
+                    author: entry.author,
+                    content: entry.comment.content,
+                    age: u64::try_from((now - entry.comment.date.timestamp_millis()) / 1000)
+                        .unwrap_or(0),
+                    editable: entry.editable,
+                })
+                .collect(),
+            page: comments.page,
+            page_count: comments.page_count,
+        })),
+        Err(GetCommentsError::Database(err)) => {
+            error!("Database error while getting comments, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+    }
+}
+
+#[server(NewComment, encoding = "Cbor")]
+async fn new_comment(recipe: String, content: String) -> Result<RecipeChange, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{comments::NewCommentError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(RecipeChange::InvalidCredentials);
+    };
+
+    let Ok(recipe) = ObjectId::parse_str(recipe) else {
+        return Ok(RecipeChange::NotFound);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .new_comment(user_name, user_token, recipe, content)
+        .await
+    {
+        Ok(()) => Ok(RecipeChange::Done),
+        Err(NewCommentError::Database(err)) => {
+            error!("Database error while creating comment, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(NewCommentError::InvalidCredentials) => Ok(RecipeChange::InvalidCredentials),
+        Err(NewCommentError::NotFound) => Ok(RecipeChange::NotFound),
+        Err(NewCommentError::Forbidden) => Ok(RecipeChange::Forbidden),
+        Err(NewCommentError::InvalidContent(err)) => Ok(RecipeChange::Invalid(vec![err])),
+    }
+}
+
+#[server(UpdateComment, encoding = "Cbor")]
+async fn update_comment(
+    recipe: String,
+    comment: String,
+    content: String,
+) -> Result<RecipeChange, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{comments::UpdateCommentError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(RecipeChange::InvalidCredentials);
+    };
+
+    let (Ok(recipe), Ok(comment)) = (ObjectId::parse_str(recipe), ObjectId::parse_str(comment))
+    else {
+        return Ok(RecipeChange::NotFound);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .update_comment(user_name, user_token, recipe, comment, content)
+        .await
+    {
+        Ok(()) => Ok(RecipeChange::Done),
+        Err(UpdateCommentError::Database(err)) => {
+            error!("Database error while updating comment, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(UpdateCommentError::InvalidCredentials) => Ok(RecipeChange::InvalidCredentials),
+        Err(UpdateCommentError::NotFound) => Ok(RecipeChange::NotFound),
+        Err(UpdateCommentError::Forbidden) => Ok(RecipeChange::Forbidden),
+        Err(UpdateCommentError::InvalidContent(err)) => Ok(RecipeChange::Invalid(vec![err])),
+    }
+}
+
+#[server(DeleteComment, encoding = "Cbor")]
+async fn delete_comment(recipe: String, comment: String) -> Result<RecipeChange, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{comments::DeleteCommentError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(RecipeChange::InvalidCredentials);
+    };
+
+    let (Ok(recipe), Ok(comment)) = (ObjectId::parse_str(recipe), ObjectId::parse_str(comment))
+    else {
+        return Ok(RecipeChange::NotFound);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .delete_comment(user_name, user_token, recipe, comment)
+        .await
+    {
+        Ok(()) => Ok(RecipeChange::Done),
+        Err(DeleteCommentError::Database(err)) => {
+            error!("Database error while deleting comment, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(DeleteCommentError::InvalidCredentials) => Ok(RecipeChange::InvalidCredentials),
+        Err(DeleteCommentError::NotFound) => Ok(RecipeChange::NotFound),
+        Err(DeleteCommentError::Forbidden) => Ok(RecipeChange::Forbidden),
+    }
+}
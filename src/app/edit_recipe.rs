@@ -0,0 +1,166 @@
+use std::rc::Rc;
+
+use leptos::{
+    component, create_action, create_resource, server, view, IntoView, ServerFnError, SignalGet,
+    SignalSet, SignalWith, SignalWithUntracked, Suspense,
+};
+use leptos_router::{use_navigate, use_params_map};
+
+use crate::{
+    app::{
+        new_recipe::{RecipeFields, RecipeForm, RecipeFormArgs, INVALID_FIELDS},
+        recipe::{get_recipe, RecipeChange},
+    },
+    model::{Ingredient, Tool},
+};
+
+#[component]
+pub fn EditRecipeView() -> impl IntoView {
+    let params = use_params_map();
+    let id = move || params.with(|params| params.get("id").cloned().unwrap_or_default());
+    let recipe = create_resource(id, get_recipe);
+    let navigate = Rc::new(use_navigate());
+
+    let action = create_action(move |args: &RecipeFormArgs| {
+        let RecipeFormArgs {
+            fields,
+            error,
+            errors,
+        } = args.clone();
+
+        error.set(None);
+
+        let id = params.with_untracked(|params| params.get("id").cloned().unwrap_or_default());
+        let navigate = Rc::clone(&navigate);
+
+        async move {
+            match update_recipe(
+                id.clone(),
+                fields.name,
+                fields.icon_url,
+                fields.price_level,
+                fields.healthy_level,
+                fields.servings,
+                fields.instructions,
+                fields.ingredients,
+                fields.tools,
+                fields.categories,
+            )
+            .await
+            {
+                Ok(RecipeChange::Done) => navigate(&format!("/recipes/{id}"), Default::default()),
+                Ok(RecipeChange::InvalidCredentials) => {
+                    error.set(Some("You must be signed in to edit a recipe"))
+                }
+                Ok(RecipeChange::NotFound) => error.set(Some("This recipe no longer exists")),
+                Ok(RecipeChange::Forbidden) => {
+                    error.set(Some("Only the author or an admin can edit this recipe"))
+                }
+                Ok(RecipeChange::Invalid(invalid)) => {
+                    errors.set(invalid);
+                    error.set(Some(INVALID_FIELDS));
+                }
+                Err(ServerFnError::Request(_)) => error.set(Some("Network error")),
+                Err(_) => error.set(Some("Internal error, retry later")),
+            };
+        }
+    });
+
+    view! {
+        <Suspense fallback=|| view! { <p> "Loading..." </p> }>
+            {move || recipe.get().map(|recipe| match recipe {
+                Ok(Some(recipe)) => {
+                    let fields = RecipeFields {
+                        name: recipe.name,
+                        icon_url: recipe.icon_url,
+                        price_level: recipe.price_level,
+                        healthy_level: recipe.healthy_level,
+                        servings: recipe.servings,
+                        instructions: recipe.instructions,
+                        ingredients: recipe.ingredients,
+                        tools: recipe.tools,
+                        categories: recipe
+                            .categories
+                            .into_iter()
+                            .map(|category| category.id)
+                            .collect(),
+                    };
+                    view! { <RecipeForm action=action fields=fields submit="Save"/> }.into_view()
+                }
+                Ok(None) => view! { <h2> "Recipe not found" </h2> }.into_view(),
+                Err(_) => view! { <p> "Failed to query recipe" </p> }.into_view(),
+            })}
+        </Suspense>
+    }
+}
+
+#[server(UpdateRecipe, encoding = "Cbor")]
+async fn update_recipe(
+    id: String,
+    name: String,
+    icon_url: String,
+    price_level: u8,
+    healthy_level: u8,
+    servings: u8,
+    instructions: Vec<String>,
+    ingredients: Vec<Ingredient>,
+    tools: Vec<Tool>,
+    categories: Vec<String>,
+) -> Result<RecipeChange, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{recipes::UpdateRecipeError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(RecipeChange::InvalidCredentials);
+    };
+
+    let Ok(id) = ObjectId::parse_str(id) else {
+        return Ok(RecipeChange::NotFound);
+    };
+
+    let categories = categories
+        .iter()
+        .filter_map(|id| ObjectId::parse_str(id).ok())
+        .collect();
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .update_recipe(
+            user_name,
+            user_token,
+            id,
+            name,
+            icon_url,
+            price_level,
+            healthy_level,
+            servings,
+            instructions,
+            ingredients,
+            tools,
+            categories,
+        )
+        .await
+    {
+        Ok(()) => Ok(RecipeChange::Done),
+        Err(UpdateRecipeError::Database(err)) => {
+            error!("Database error while updating recipe, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(UpdateRecipeError::InvalidCredentials) => Ok(RecipeChange::InvalidCredentials),
+        Err(UpdateRecipeError::NotFound) => Ok(RecipeChange::NotFound),
+        Err(UpdateRecipeError::Forbidden) => Ok(RecipeChange::Forbidden),
+        Err(UpdateRecipeError::Invalid(errors)) => Ok(RecipeChange::Invalid(errors)),
+    }
+}
@@ -1,10 +1,8 @@
 use leptos::{
-    component, create_action, create_rw_signal, event_target_value, server, view, Action, IntoView,
-    RwSignal, ServerFnError, SignalGet, SignalGetUntracked, SignalSet,
+    component, create_action, create_resource, create_rw_signal, event_target_value, server, view,
+    Action, IntoView, RwSignal, ServerFnError, SignalGet, SignalGetUntracked, SignalSet,
 };
 
-use crate::{cookies::Cookies, model::Token};
-
 #[component]
 pub fn SignInView() -> impl IntoView {
     let action = create_action(move |args: &LoginActionArgs| {
@@ -18,18 +16,8 @@ pub fn SignInView() -> impl IntoView {
 
         async move {
             match signin(name.get_untracked(), password.get_untracked()).await {
-                Ok(Some(token)) => {
-                    if Cookies::set_credentials(
-                        &name.get_untracked(),
-                        &token.token,
-                        &token.expiration,
-                    )
-                    .is_err()
-                    {
-                        error.set(Some("Internal error, retry later"));
-                    }
-                }
-                Ok(None) => error.set(Some("Wrong name or password")),
+                Ok(true) => {}
+                Ok(false) => error.set(Some("Wrong name or password")),
                 Err(ServerFnError::Request(_)) => error.set(Some("Network error")),
                 Err(_) => error.set(Some("Internal error, retry later")),
             };
@@ -54,14 +42,11 @@ pub fn SignUpView() -> impl IntoView {
 
         async move {
             match signup(name.get(), password.get()).await {
-                Ok(Some(token)) => {
-                    if Cookies::set_credentials(&name.get(), &token.token, &token.expiration)
-                        .is_err()
-                    {
-                        error.set(Some("Internal error, retry later"));
-                    }
+                Ok(true) => {}
+                Ok(false) => error.set(Some("Name already taken")),
+                Err(ServerFnError::Args(_)) => {
+                    error.set(Some("The name must not be empty nor contain % or /"))
                 }
-                Ok(None) => error.set(Some("Name already taken")),
                 Err(ServerFnError::Request(_)) => error.set(Some("Network error")),
                 Err(_) => error.set(Some("Internal error, retry later")),
             }
@@ -69,7 +54,7 @@ pub fn SignUpView() -> impl IntoView {
     });
 
     view! {
-        <LoginView action=action title="Sign in"/>
+        <LoginView action=action title="Sign up" check_name=true/>
     }
 }
 
@@ -81,11 +66,28 @@ struct LoginActionArgs {
 }
 
 #[component]
-fn LoginView(action: Action<LoginActionArgs, ()>, #[prop(into)] title: String) -> impl IntoView {
+fn LoginView(
+    action: Action<LoginActionArgs, ()>,
+    #[prop(into)] title: String,
+    /// Tell the user whether the name is still available while typing it
+    #[prop(optional)]
+    check_name: bool,
+) -> impl IntoView {
     let name = create_rw_signal(String::new());
     let password = create_rw_signal(String::new());
     let error = create_rw_signal(None);
 
+    let name_availability = create_resource(
+        move || name.get(),
+        move |name| async move {
+            if !check_name || name.is_empty() {
+                return None;
+            }
+
+            is_name_available(name).await.ok()
+        },
+    );
+
     let submit = move |_| {
         action.dispatch(LoginActionArgs {
             name,
@@ -106,6 +108,13 @@ fn LoginView(action: Action<LoginActionArgs, ()>, #[prop(into)] title: String) -
                         id="login_name_input" class="login_input"
                         type="text" maxLength=256 required
                     />
+                    <span class="login_name_availability">
+                        {move || match name_availability.get().flatten() {
+                            Some(true) => Some("Name available"),
+                            Some(false) => Some("Name already taken"),
+                            None => None,
+                        }}
+                    </span>
                 </div>
 
                 <div class="login_input_container">
@@ -128,39 +137,54 @@ fn LoginView(action: Action<LoginActionArgs, ()>, #[prop(into)] title: String) -
 }
 
 #[server(Signin, encoding = "Cbor")]
-async fn signin(name: String, password: String) -> Result<Option<Token>, ServerFnError> {
+async fn signin(name: String, password: String) -> Result<bool, ServerFnError> {
     use leptos::logging::error;
 
-    use crate::database::{signin::SigninError, Database};
+    use crate::{
+        cookies::Cookies,
+        database::{signin::SigninError, Database},
+    };
 
     let Ok(database) = Database::new().await else {
         return Err(ServerFnError::ServerError("".into()));
     };
 
-    match database.signin(name, password).await {
-        Ok(token) => Ok(Some(token)),
+    match database.signin(name.clone(), password).await {
+        Ok(token) => {
+            Cookies::set_credentials(&name, &token.token, &token.expiration)
+                .map_err(|()| ServerFnError::ServerError("".into()))?;
+            Ok(true)
+        }
         Err(SigninError::Database(err)) => {
             error!("Database error while signin: {:?}", err);
             Err(ServerFnError::ServerError("".into()))
         }
-        Err(SigninError::WrongNameOrPassword) => Ok(None),
+        Err(SigninError::WrongNameOrPassword) => Ok(false),
         Err(SigninError::Internal) => Err(ServerFnError::ServerError("".into())),
     }
 }
 
 #[server(Signup, encoding = "Cbor")]
-async fn signup(name: String, password: String) -> Result<Option<Token>, ServerFnError> {
+async fn signup(name: String, password: String) -> Result<bool, ServerFnError> {
     use leptos::logging::error;
 
-    use crate::database::{signup::SignupError, Database};
+    use crate::{
+        cookies::Cookies,
+        database::{signup::SignupError, Database},
+    };
 
     let Ok(database) = Database::new().await else {
         return Err(ServerFnError::ServerError("".into()));
     };
 
-    match database.signup(name, password).await {
-        Ok(token) => Ok(Some(token)),
-        Err(SignupError::NameAlreadyTaken) => Ok(None),
+    match database.signup(name.clone(), password).await {
+        Ok(token) => {
+            Cookies::set_credentials(&name, &token.token, &token.expiration)
+                .map_err(|()| ServerFnError::ServerError("".into()))?;
+            Ok(true)
+        }
+        Err(SignupError::NameAlreadyTaken) => Ok(false),
+        Err(SignupError::InvalidName) => Err(ServerFnError::Args("Invalid name".into())),
         Err(SignupError::Database(err)) => {
             error!("Database error while signup: {:?}", err);
             Err(ServerFnError::ServerError("".into()))
@@ -168,3 +192,19 @@ async fn signup(name: String, password: String) -> Result<Option<Token>, ServerF
         Err(SignupError::Internal) => Err(ServerFnError::ServerError("".into())),
     }
 }
+
+#[server(IsNameAvailable, encoding = "GetCbor")]
+async fn is_name_available(name: String) -> Result<bool, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::database::Database;
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    database.is_name_available(&name).await.map_err(|err| {
+        error!("Database error while checking name availability: {:?}", err);
+        ServerFnError::ServerError("".into())
+    })
+}
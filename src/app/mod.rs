@@ -1,17 +1,30 @@
-mod login;
-mod nav_bar;
-mod new_recipe;
-mod recipes;
+pub mod categories;
+pub mod comments;
+pub mod edit_recipe;
+pub mod login;
+pub mod nav_bar;
+pub mod new_recipe;
+pub mod planner;
+pub mod recipe;
+pub mod recipes;
+pub mod shopping;
+pub mod users;
 
 use leptos::{component, view, IntoView};
 use leptos_meta::{provide_meta_context, Stylesheet};
 use leptos_router::{Route, Router, Routes};
 
 use crate::app::{
+    categories::{CategoriesAdminView, CategoriesView, CategoryView},
+    edit_recipe::EditRecipeView,
     login::{SignInView, SignUpView},
     nav_bar::NavBar,
     new_recipe::NewRecipeView,
+    planner::PlannerView,
+    recipe::RecipeView,
     recipes::RecipesView,
+    shopping::ShoppingListView,
+    users::{UserView, UsersAdminView},
 };
 
 #[component]
@@ -31,7 +44,16 @@ pub fn App() -> impl IntoView {
             <Route path="/signin" view=SignInView/>
             <Route path="/signup" view=SignUpView/>
             <Route path="/recipes" view=RecipesView/>
+            <Route path="/recipes/:id" view=RecipeView/>
+            <Route path="/recipes/:id/edit" view=EditRecipeView/>
             <Route path="/new_recipe" view=NewRecipeView/>
+            <Route path="/categories" view=CategoriesView/>
+            <Route path="/categories/:id" view=CategoryView/>
+            <Route path="/admin/categories" view=CategoriesAdminView/>
+            <Route path="/shopping" view=ShoppingListView/>
+            <Route path="/planner" view=PlannerView/>
+            <Route path="/users/:name" view=UserView/>
+            <Route path="/admin/users" view=UsersAdminView/>
             <Route path="/*any" view=|| view!{ <h1> "Not found" </h1>}/>
         </Routes>
 
@@ -1,8 +1,37 @@
-use leptos::{component, view, IntoView};
-use leptos_router::A;
+use std::rc::Rc;
+
+use leptos::{
+    component, create_action, create_rw_signal, server, view, IntoView, ServerFnError, SignalGet,
+    SignalSet,
+};
+use leptos_router::{use_navigate, A};
 
 #[component]
 pub fn NavBar() -> impl IntoView {
+    let error = create_rw_signal(None);
+    let navigate = Rc::new(use_navigate());
+
+    let signout_action = create_action(move |everywhere: &bool| {
+        let everywhere = *everywhere;
+        let navigate = Rc::clone(&navigate);
+
+        error.set(None);
+
+        async move {
+            let result = if everywhere {
+                signout_all().await
+            } else {
+                signout().await
+            };
+
+            match result {
+                Ok(_) => navigate("/signin", Default::default()),
+                Err(ServerFnError::Request(_)) => error.set(Some("Network error")),
+                Err(_) => error.set(Some("Internal error, retry later")),
+            }
+        }
+    });
+
     view! {
         <header>
             <A href="/"> "Home" </A>
@@ -10,6 +39,74 @@ pub fn NavBar() -> impl IntoView {
             <A href="/signup"> "Signup" </A>
             <A href="/recipes"> "Recipes" </A>
             <A href="/new_recipe"> "New Recipe" </A>
+            <A href="/categories"> "Categories" </A>
+            <A href="/shopping"> "Shopping list" </A>
+            <A href="/planner"> "Planner" </A>
+            <A href="/admin/categories"> "Manage categories" </A>
+            <A href="/admin/users"> "Manage users" </A>
+            <button on:click=move |_| signout_action.dispatch(false)> "Sign out" </button>
+            <button on:click=move |_| signout_action.dispatch(true)> "Sign out everywhere" </button>
+            {move || error.get()}
         </header>
     }
 }
+
+#[server(Signout, encoding = "Cbor")]
+async fn signout() -> Result<bool, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{signout::SignoutError, Database},
+    };
+
+    let Ok(Credentials { name, token }) = Cookies::credentials() else {
+        return Ok(false);
+    };
+
+    // The cookies are useless whether or not the token was still valid
+    Cookies::clear_credentials().map_err(|()| ServerFnError::ServerError("".into()))?;
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.signout(name, token).await {
+        Ok(()) => Ok(true),
+        Err(SignoutError::Database(err)) => {
+            error!("Database error while signout: {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(SignoutError::InvalidCredentials) => Ok(false),
+    }
+}
+
+#[server(SignoutAll, encoding = "Cbor")]
+async fn signout_all() -> Result<bool, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{signout::SignoutError, Database},
+    };
+
+    let Ok(Credentials { name, token }) = Cookies::credentials() else {
+        return Ok(false);
+    };
+
+    // The cookies are useless whether or not the token was still valid
+    Cookies::clear_credentials().map_err(|()| ServerFnError::ServerError("".into()))?;
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.signout_all(name, token).await {
+        Ok(()) => Ok(true),
+        Err(SignoutError::Database(err)) => {
+            error!("Database error while signout everywhere: {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(SignoutError::InvalidCredentials) => Ok(false),
+    }
+}
@@ -1,99 +1,212 @@
-use futures::future::OptionFuture;
 use leptos::{
-    component, create_action, create_rw_signal, event_target_value, server, view, For, IntoView,
-    ServerFnError, SignalGet, SignalGetUntracked, SignalSet, SignalUpdate,
+    component, create_action, create_resource, create_rw_signal, event_target_checked,
+    event_target_value, server, view, Action, CollectView, For, IntoView, RwSignal, ServerFnError,
+    SignalGet, SignalGetUntracked, SignalSet, SignalUpdate, SignalWith, SignalWithUntracked,
+    Suspense,
 };
 
 use crate::{
-    cookies::{Cookies, Credentials},
-    model::{Ingredient, Tool},
+    app::{categories::get_categories, recipe::RecipeChange},
+    model::{Ingredient, Tool, DEFAULT_SERVINGS, LEVEL_RANGE, SERVINGS_RANGE},
+    quantity::Quantity,
     reactive_vec::ReactiveVec,
+    validation::{validate_recipe, RecipeDraft, RecipeField, ValidationError},
 };
 
 #[component]
 pub fn NewRecipeView() -> impl IntoView {
-    let error = create_rw_signal(None);
-
-    let name = create_rw_signal(String::new());
-    let icon_url = create_rw_signal(String::new());
-    let price_level = create_rw_signal(0);
-    let healthy_level = create_rw_signal(0);
-
-    let instructions = ReactiveVec::<String>::new();
-    let ingredients = ReactiveVec::<Ingredient>::new();
-    let tools = ReactiveVec::<Tool>::new();
+    let action = create_action(move |args: &RecipeFormArgs| {
+        let RecipeFormArgs {
+            fields,
+            error,
+            errors,
+        } = args.clone();
 
-    let create_action = create_action(move |()| {
         error.set(None);
 
-        let Ok(Credentials {
-            name: user_name,
-            token: user_token,
-        }) = Cookies::credentials()
-        else {
-            error.set(Some("You must be signed in to create a recipe"));
-            return OptionFuture::from(None);
-        };
-
-        let name = name.get_untracked();
-        let icon_url = icon_url.get_untracked();
-        let price_level = price_level.get_untracked();
-        let healthy_level = healthy_level.get_untracked();
-        let instructions = instructions.get_values_untracked();
-        let ingredients = ingredients.get_values_untracked();
-        let tools = tools.get_values_untracked();
-
-        OptionFuture::from(Some(async move {
+        async move {
             match new_recipe(
-                user_name,
-                user_token,
-                name,
-                icon_url,
-                price_level,
-                healthy_level,
-                instructions,
-                ingredients,
-                tools,
+                fields.name,
+                fields.icon_url,
+                fields.price_level,
+                fields.healthy_level,
+                fields.servings,
+                fields.instructions,
+                fields.ingredients,
+                fields.tools,
+                fields.categories,
             )
             .await
             {
-                Ok(true) => {}
-                Ok(false) => error.set(Some("Session expired, please sign in")),
+                Ok(RecipeChange::Done) => {}
+                Ok(RecipeChange::InvalidCredentials) => {
+                    error.set(Some("You must be signed in to create a recipe"))
+                }
+                Ok(RecipeChange::Forbidden) => error.set(Some("Banned users can't create recipes")),
+                Ok(RecipeChange::Invalid(invalid)) => {
+                    errors.set(invalid);
+                    error.set(Some(INVALID_FIELDS));
+                }
                 Err(ServerFnError::Request(_)) => error.set(Some("Network error")),
-                Err(_) => error.set(Some("Internal error, retry later")),
+                Ok(RecipeChange::NotFound) | Err(_) => {
+                    error.set(Some("Internal error, retry later"))
+                }
             };
-        }))
+        }
     });
 
+    view! {
+        <RecipeForm
+            action=action
+            fields=RecipeFields {
+                servings: DEFAULT_SERVINGS,
+                ..RecipeFields::default()
+            }
+            submit="Create"
+        />
+    }
+}
+
+/// Values of the fields of a [`RecipeForm`]
+#[derive(Clone, Default)]
+pub struct RecipeFields {
+    pub name: String,
+    pub icon_url: String,
+    pub price_level: u8,
+    pub healthy_level: u8,
+    pub servings: u8,
+    pub instructions: Vec<String>,
+    pub ingredients: Vec<Ingredient>,
+    pub tools: Vec<Tool>,
+    /// Ids of the categories of the recipe
+    pub categories: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct RecipeFormArgs {
+    pub fields: RecipeFields,
+    pub error: RwSignal<Option<&'static str>>,
+    /// Errors shown next to the fields, to set if the server rejects them
+    pub errors: RwSignal<Vec<ValidationError>>,
+}
+
+/// Message shown when some fields of a [`RecipeForm`] have errors
+pub const INVALID_FIELDS: &str = "Some fields are invalid";
+
+/// Form used to both create and edit a recipe, prefilled with `fields`
+#[component]
+pub fn RecipeForm(
+    action: Action<RecipeFormArgs, ()>,
+    fields: RecipeFields,
+    #[prop(into)] submit: String,
+) -> impl IntoView {
+    let error = create_rw_signal(None);
+    let errors = create_rw_signal(Vec::new());
+
+    let name = create_rw_signal(fields.name);
+    let icon_url = create_rw_signal(fields.icon_url);
+    let price_level = create_rw_signal(fields.price_level);
+    let healthy_level = create_rw_signal(fields.healthy_level);
+    let servings = create_rw_signal(fields.servings);
+
+    let instructions = ReactiveVec::from_values(fields.instructions);
+    let ingredients = ReactiveVec::from_values(fields.ingredients);
+    let tools = ReactiveVec::from_values(fields.tools);
+    let categories = create_rw_signal(fields.categories);
+
+    let all_categories = create_resource(|| (), |()| get_categories());
+
+    let submit_action = move |_| {
+        let fields = RecipeFields {
+            name: name.get_untracked(),
+            icon_url: icon_url.get_untracked(),
+            price_level: price_level.get_untracked(),
+            healthy_level: healthy_level.get_untracked(),
+            servings: servings.get_untracked(),
+            instructions: instructions.get_values_untracked(),
+            ingredients: ingredients.get_values_untracked(),
+            tools: tools.get_values_untracked(),
+            categories: categories.get_untracked(),
+        };
+
+        // Same checks as the server, to not send a request that would be rejected
+        let invalid = validate_recipe(&RecipeDraft {
+            name: &fields.name,
+            icon_url: &fields.icon_url,
+            price_level: fields.price_level,
+            healthy_level: fields.healthy_level,
+            servings: fields.servings,
+            instructions: &fields.instructions,
+            ingredients: &fields.ingredients,
+            tools: &fields.tools,
+        });
+        let valid = invalid.is_empty();
+        errors.set(invalid);
+
+        if valid {
+            action.dispatch(RecipeFormArgs {
+                fields,
+                error,
+                errors,
+            });
+        } else {
+            error.set(Some(INVALID_FIELDS));
+        }
+    };
+
     view! {
         <div style="display:flex;flex-direction:row;">
             <p> "Name" </p>
-            <input on:input=move |ev| name.set(event_target_value(&ev))/>
+            <input prop:value=name.get_untracked() on:input=move |ev| name.set(event_target_value(&ev))/>
+            <FieldErrors errors=errors field=move || Some(RecipeField::Name)/>
         </div>
         <div style="display:flex;flex-direction:row;">
             <p> "Icon url" </p>
-            <input on:input=move |ev| icon_url.set(event_target_value(&ev))/>
+            <input prop:value=icon_url.get_untracked() on:input=move |ev| icon_url.set(event_target_value(&ev))/>
+            <FieldErrors errors=errors field=move || Some(RecipeField::IconUrl)/>
         </div>
         <div style="display:flex;flex-direction:row;">
             <p> "Price level" </p>
-            <input type="range" min=0 max=4 on:input=move |ev| price_level.set(event_target_value(&ev).parse::<u8>().unwrap())/>
+            <input
+                type="range" min=*LEVEL_RANGE.start() max=*LEVEL_RANGE.end()
+                prop:value=price_level.get_untracked()
+                on:input=move |ev| if let Ok(level) = event_target_value(&ev).parse() { price_level.set(level) }
+            />
+            <FieldErrors errors=errors field=move || Some(RecipeField::PriceLevel)/>
         </div>
         <div style="display:flex;flex-direction:row;">
             <p> "Healthy level" </p>
-            <input type="range" min=0 max=4 on:input=move |ev| healthy_level.set(event_target_value(&ev).parse::<u8>().unwrap())/>
+            <input
+                type="range" min=*LEVEL_RANGE.start() max=*LEVEL_RANGE.end()
+                prop:value=healthy_level.get_untracked()
+                on:input=move |ev| if let Ok(level) = event_target_value(&ev).parse() { healthy_level.set(level) }
+            />
+            <FieldErrors errors=errors field=move || Some(RecipeField::HealthyLevel)/>
+        </div>
+        <div style="display:flex;flex-direction:row;">
+            <p> "Servings" </p>
+            <input
+                type="number" min=*SERVINGS_RANGE.start() max=*SERVINGS_RANGE.end()
+                prop:value=servings.get_untracked()
+                on:input=move |ev| servings.set(event_target_value(&ev).parse().unwrap_or(0))
+            />
+            <FieldErrors errors=errors field=move || Some(RecipeField::Servings)/>
         </div>
         <h3> "Instructions" </h3>
+        <FieldErrors errors=errors field=move || Some(RecipeField::Instructions)/>
         <div>
             <For
                 each=move || instructions.get()
                 key=|instruction|instruction.0
                 let:instruction
             >
-                <textarea on:input=move |ev| instruction.1.set(event_target_value(&ev))/>
+                <textarea prop:value=instruction.1.get_untracked() on:input=move |ev| instruction.1.set(event_target_value(&ev))/>
+                <FieldErrors errors=errors field=move || instructions.index_of(instruction.0).map(RecipeField::Instruction)/>
             </For>
             <button on:click=move |_| instructions.push(String::new())> "+" </button>
         </div>
         <h3> "Ingredients" </h3>
+        <FieldErrors errors=errors field=move || Some(RecipeField::Ingredients)/>
         <div>
             <For
                 each=move || ingredients.get()
@@ -103,15 +216,18 @@ pub fn NewRecipeView() -> impl IntoView {
 
                 <div style="display:flex;flex-direction:row;">
                     <p> "Name" </p>
-                    <input on:input=move |ev| ingredient.1.update(|ingredient| ingredient.name = event_target_value(&ev))/>
+                    <input prop:value=ingredient.1.get_untracked().name on:input=move |ev| ingredient.1.update(|ingredient| ingredient.name = event_target_value(&ev))/>
+                    <FieldErrors errors=errors field=move || ingredients.index_of(ingredient.0).map(RecipeField::IngredientName)/>
                 </div>
                 <div style="display:flex;flex-direction:row;">
                     <p> "Icon url" </p>
-                    <input on:input=move |ev| ingredient.1.update(|ingredient| ingredient.icon_url = event_target_value(&ev))/>
+                    <input prop:value=ingredient.1.get_untracked().icon_url on:input=move |ev| ingredient.1.update(|ingredient| ingredient.icon_url = event_target_value(&ev))/>
+                    <FieldErrors errors=errors field=move || ingredients.index_of(ingredient.0).map(RecipeField::IngredientIconUrl)/>
                 </div>
                 <div style="display:flex;flex-direction:row;">
                     <p> "Quantity" </p>
-                    <input on:input=move |ev| ingredient.1.update(|ingredient| ingredient.quantity = event_target_value(&ev))/>
+                    <input prop:value=ingredient.1.get_untracked().quantity.to_string() on:input=move |ev| ingredient.1.update(|ingredient| ingredient.quantity = Quantity::parse(&event_target_value(&ev)))/>
+                    <FieldErrors errors=errors field=move || ingredients.index_of(ingredient.0).map(RecipeField::IngredientQuantity)/>
                 </div>
             </For>
             <button on:click=move |_| ingredients.push(Ingredient::default())> "+" </button>
@@ -119,6 +235,7 @@ pub fn NewRecipeView() -> impl IntoView {
 
 
         <h3> "Tools" </h3>
+        <FieldErrors errors=errors field=move || Some(RecipeField::Tools)/>
         <div>
             <For
                 each=move || tools.get()
@@ -128,37 +245,103 @@ pub fn NewRecipeView() -> impl IntoView {
 
                 <div style="display:flex;flex-direction:row;">
                     <p> "Name" </p>
-                    <input on:input=move |ev| tool.1.update(|tool| tool.name = event_target_value(&ev))/>
+                    <input prop:value=tool.1.get_untracked().name on:input=move |ev| tool.1.update(|tool| tool.name = event_target_value(&ev))/>
+                    <FieldErrors errors=errors field=move || tools.index_of(tool.0).map(RecipeField::ToolName)/>
                 </div>
                 <div style="display:flex;flex-direction:row;">
                     <p> "Icon url" </p>
-                    <input on:input=move |ev| tool.1.update(|tool| tool.icon_url = event_target_value(&ev))/>
+                    <input prop:value=tool.1.get_untracked().icon_url on:input=move |ev| tool.1.update(|tool| tool.icon_url = event_target_value(&ev))/>
+                    <FieldErrors errors=errors field=move || tools.index_of(tool.0).map(RecipeField::ToolIconUrl)/>
                 </div>
             </For>
             <button on:click=move |_| tools.push(Tool::default())> "+" </button>
         </div>
 
+        <h3> "Categories" </h3>
+        <Suspense fallback=|| view! { <p> "Loading..." </p> }>
+            {move || all_categories.get().map(|all_categories| match all_categories {
+                Ok(all_categories) => all_categories.into_iter().map(|category| {
+                    let id = category.id.clone();
+                    let checked = categories.with_untracked(|categories| categories.contains(&id));
+
+                    view! {
+                        <label>
+                            <input
+                                type="checkbox"
+                                prop:checked=checked
+                                on:change=move |ev| {
+                                    let id = id.clone();
+                                    if event_target_checked(&ev) {
+                                        categories.update(|categories| categories.push(id));
+                                    } else {
+                                        categories.update(|categories| categories.retain(|other| *other != id));
+                                    }
+                                }
+                            />
+                            {category.name}
+                        </label>
+                    }
+                }).collect_view(),
+                Err(_) => view! { <p> "Failed to query categories" </p> }.into_view(),
+            })}
+        </Suspense>
+
         {move || error.get()}
 
-        <button on:click=move |_| create_action.dispatch(())> "Create" </button>
+        <button on:click=submit_action> {submit} </button>
+    }
+}
+
+/// Messages of the `errors` concerning `field`, which is `None` if it no longer exists
+#[component]
+fn FieldErrors<F>(errors: RwSignal<Vec<ValidationError>>, field: F) -> impl IntoView
+where
+    F: Fn() -> Option<RecipeField> + 'static,
+{
+    move || {
+        let field = field();
+        errors.with(|errors| {
+            errors
+                .iter()
+                .filter(|error| Some(error.field()) == field)
+                .map(|error| view! { <span class="field_error"> {error.to_string()} </span> })
+                .collect_view()
+        })
     }
 }
 
 #[server(NewRecipe, encoding = "Cbor")]
 async fn new_recipe(
-    user_name: String,
-    user_token: String,
     name: String,
     icon_url: String,
     price_level: u8,
     healthy_level: u8,
+    servings: u8,
     instructions: Vec<String>,
     ingredients: Vec<Ingredient>,
     tools: Vec<Tool>,
-) -> Result<bool, ServerFnError> {
+    categories: Vec<String>,
+) -> Result<RecipeChange, ServerFnError> {
+    use bson::oid::ObjectId;
     use leptos::logging::error;
 
-    use crate::database::{recipes::NewRecipeError, Database};
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{recipes::NewRecipeError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(RecipeChange::InvalidCredentials);
+    };
+
+    let categories = categories
+        .iter()
+        .filter_map(|id| ObjectId::parse_str(id).ok())
+        .collect();
 
     let Ok(database) = Database::new().await else {
         return Err(ServerFnError::ServerError("".into()));
@@ -172,17 +355,21 @@ async fn new_recipe(
             icon_url,
             price_level,
             healthy_level,
+            servings,
             instructions,
             ingredients,
             tools,
+            categories,
         )
         .await
     {
-        Ok(()) => Ok(true),
+        Ok(()) => Ok(RecipeChange::Done),
         Err(NewRecipeError::Database(err)) => {
             error!("Database error while creating recipe, {:?}", err);
             return Err(ServerFnError::ServerError("".into()));
         }
-        Err(NewRecipeError::InvalidCredentials) => Ok(false),
+        Err(NewRecipeError::InvalidCredentials) => Ok(RecipeChange::InvalidCredentials),
+        Err(NewRecipeError::Forbidden) => Ok(RecipeChange::Forbidden),
+        Err(NewRecipeError::Invalid(errors)) => Ok(RecipeChange::Invalid(errors)),
     }
 }
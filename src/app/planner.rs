@@ -0,0 +1,557 @@
+use leptos::{
+    component, create_action, create_resource, create_rw_signal, event_target_value, server, view,
+    Action, CollectView, IntoView, RwSignal, ServerFnError, SignalGet, SignalGetUntracked,
+    SignalSet, Transition,
+};
+use leptos_router::A;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::recipes::{get_recipes, search_recipes, RecipeFilter, RecipeSort},
+    model::{Meal, SERVINGS_RANGE},
+};
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// What is being dragged onto the planner
+#[derive(Clone)]
+enum Dragged {
+    /// Recipe with this id, from the recipe list
+    Recipe(String),
+    /// Meal already planned on a date
+    Meal(String, Meal),
+}
+
+/// Weekly meal planner of the signed in user, at `/planner`
+#[component]
+pub fn PlannerView() -> impl IntoView {
+    let week = create_rw_signal(String::new());
+    let message = create_rw_signal(None);
+    let dragged = create_rw_signal(None);
+
+    let plan = create_resource(move || week.get(), get_week_plan);
+
+    let plan_action = create_action(
+        move |(date, meal, recipe, servings): &(String, Meal, String, Option<u8>)| {
+            message.set(None);
+
+            let (date, meal, recipe, servings) = (date.clone(), *meal, recipe.clone(), *servings);
+
+            async move {
+                message.set(change_error(plan_meal(date, meal, recipe, servings).await));
+                plan.refetch();
+            }
+        },
+    );
+
+    let move_action = create_action(move |(from, to): &((String, Meal), (String, Meal))| {
+        message.set(None);
+
+        let ((from, from_meal), (to, to_meal)) = (from.clone(), to.clone());
+
+        async move {
+            message.set(change_error(move_meal(from, from_meal, to, to_meal).await));
+            plan.refetch();
+        }
+    });
+
+    let unplan_action = create_action(move |(date, meal): &(String, Meal)| {
+        message.set(None);
+
+        let (date, meal) = (date.clone(), *meal);
+
+        async move {
+            message.set(change_error(unplan_meal(date, meal).await));
+            plan.refetch();
+        }
+    });
+
+    let shopping_action = create_action(move |week: &String| {
+        message.set(None);
+
+        let week = week.clone();
+
+        async move {
+            let result = build_week_shopping_list(week).await;
+            message.set(if result == Ok(PlannerChange::Done) {
+                Some("The ingredients of the week were added to your shopping list")
+            } else {
+                change_error(result)
+            });
+        }
+    });
+
+    let drop_on = move |date: String, meal: Meal| match dragged.get_untracked() {
+        Some(Dragged::Recipe(recipe)) => plan_action.dispatch((date, meal, recipe, None)),
+        Some(Dragged::Meal(from, from_meal)) if (&from, from_meal) != (&date, meal) => {
+            move_action.dispatch(((from, from_meal), (date, meal)))
+        }
+        _ => {}
+    };
+
+    view! {
+        <h2> "Meal planner" </h2>
+        <div class="planner">
+            <Transition fallback=|| view! { <p> "Loading..." </p> }>
+                {move || plan.get().map(|plan| match plan {
+                    Ok(Some(plan)) => {
+                        let start = plan.start.clone();
+                        view! {
+                            <div>
+                                <p>
+                                    <button on:click=move |_| week.set(plan.previous.clone())> "Previous week" </button>
+                                    " Week of " {plan.start.clone()} " "
+                                    <button on:click=move |_| week.set(plan.next.clone())> "Next week" </button>
+                                    <button on:click=move |_| week.set(String::new())> "This week" </button>
+                                </p>
+                                <table class="planner_week">
+                                    <tr>
+                                        <th/>
+                                        {plan.days.iter().zip(WEEKDAYS).map(|(day, weekday)| view! {
+                                            <th> {weekday} <br/> {day.date.clone()} </th>
+                                        }).collect_view()}
+                                    </tr>
+                                    {Meal::ALL.into_iter().map(|meal| view! {
+                                        <tr>
+                                            <th> {meal.name()} </th>
+                                            {plan.days.iter().map(|day| {
+                                                let date = day.date.clone();
+                                                let planned = day.meals.iter().find(|planned| planned.meal == meal).cloned();
+                                                view! {
+                                                    <td
+                                                        class="planner_slot"
+                                                        on:dragover=|ev| ev.prevent_default()
+                                                        on:drop={
+                                                            let date = date.clone();
+                                                            move |ev| {
+                                                                ev.prevent_default();
+                                                                drop_on(date.clone(), meal);
+                                                            }
+                                                        }
+                                                    >
+                                                        {planned.map(|planned| view! {
+                                                            <PlannedMealView
+                                                                date=date
+                                                                planned=planned
+                                                                dragged=dragged
+                                                                plan_action=plan_action
+                                                                unplan_action=unplan_action
+                                                            />
+                                                        })}
+                                                    </td>
+                                                }
+                                            }).collect_view()}
+                                        </tr>
+                                    }).collect_view()}
+                                </table>
+                                <button on:click=move |_| shopping_action.dispatch(start.clone())>
+                                    "Build shopping list for this week"
+                                </button>
+                                " " <A href="/shopping"> "Open shopping list" </A>
+                            </div>
+                        }.into_view()
+                    }
+                    Ok(None) => view! {
+                        <p> <A href="/signin"> "Sign in" </A> " to plan your meals" </p>
+                    }.into_view(),
+                    Err(_) => view! { <p> "Failed to query meal plan" </p> }.into_view(),
+                })}
+            </Transition>
+            <PlannerRecipes dragged=dragged/>
+        </div>
+        {move || message.get()}
+    }
+}
+
+#[component]
+fn PlannedMealView(
+    date: String,
+    planned: PlannedMealInfo,
+    dragged: RwSignal<Option<Dragged>>,
+    plan_action: Action<(String, Meal, String, Option<u8>), ()>,
+    unplan_action: Action<(String, Meal), ()>,
+) -> impl IntoView {
+    let meal = planned.meal;
+    let recipe = planned.recipe.clone();
+
+    let drag_date = date.clone();
+    let servings_date = date.clone();
+
+    view! {
+        <div
+            class="planned_meal"
+            draggable="true"
+            on:dragstart=move |_| dragged.set(Some(Dragged::Meal(drag_date.clone(), meal)))
+            on:dragend=move |_| dragged.set(None)
+        >
+            <A href=format!("/recipes/{}", planned.recipe)>
+                {planned.name.unwrap_or_else(|| "Deleted recipe".to_string())}
+            </A>
+            <br/>
+            <input
+                type="number" min=*SERVINGS_RANGE.start() max=*SERVINGS_RANGE.end()
+                prop:value=planned.servings
+                on:change=move |ev| {
+                    if let Some(servings) = event_target_value(&ev).parse().ok().filter(|servings| SERVINGS_RANGE.contains(servings)) {
+                        plan_action.dispatch((servings_date.clone(), meal, recipe.clone(), Some(servings)));
+                    }
+                }
+            />
+            " servings "
+            <button on:click=move |_| unplan_action.dispatch((date.clone(), meal))> "Remove" </button>
+        </div>
+    }
+}
+
+/// Recipes to drag onto the planner, the best rated ones or the ones matching a search
+#[component]
+fn PlannerRecipes(dragged: RwSignal<Option<Dragged>>) -> impl IntoView {
+    const RECIPE_COUNT: usize = 20;
+
+    let query = create_rw_signal(String::new());
+
+    let recipes = create_resource(
+        move || query.get(),
+        |query| async move {
+            if query.trim().is_empty() {
+                get_recipes(
+                    RecipeSort::Rating,
+                    RecipeFilter::default(),
+                    None,
+                    RECIPE_COUNT,
+                )
+                .await
+                .map(|list| list.recipes)
+            } else {
+                search_recipes(query, RecipeFilter::default(), 0)
+                    .await
+                    .map(|page| page.recipes)
+            }
+        },
+    );
+
+    view! {
+        <aside class="planner_recipes">
+            <p> "Drag a recipe to a meal" </p>
+            <input
+                placeholder="Search recipes"
+                prop:value=move || query.get()
+                on:input=move |ev| query.set(event_target_value(&ev))
+            />
+            <Transition fallback=|| view! { <p> "Loading..." </p> }>
+                {move || recipes.get().map(|recipes| match recipes {
+                    Ok(recipes) => recipes.into_iter().map(|recipe| {
+                        let id = recipe.id;
+                        view! {
+                            <div
+                                class="planner_recipe"
+                                draggable="true"
+                                on:dragstart=move |_| dragged.set(Some(Dragged::Recipe(id.clone())))
+                                on:dragend=move |_| dragged.set(None)
+                            >
+                                <img src=recipe.icon_url/>
+                                {recipe.name}
+                            </div>
+                        }
+                    }).collect_view(),
+                    Err(_) => view! { <p> "Failed to query recipes" </p> }.into_view(),
+                })}
+            </Transition>
+        </aside>
+    }
+}
+
+/// Message telling why a change of the planner failed, `None` if it succeeded
+fn change_error(result: Result<PlannerChange, ServerFnError>) -> Option<&'static str> {
+    match result {
+        Ok(PlannerChange::Done) => None,
+        Ok(PlannerChange::InvalidCredentials) => Some("You must be signed in to plan meals"),
+        Ok(PlannerChange::NotFound) => Some("This no longer exists"),
+        Ok(PlannerChange::InvalidServings) => Some("Invalid number of servings"),
+        Ok(PlannerChange::InvalidDate) => Some("Invalid date"),
+        Err(ServerFnError::Request(_)) => Some("Network error"),
+        Err(_) => Some("Internal error, retry later"),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WeekPlanInfo {
+    /// Monday of the week, formatted as `YYYY-MM-DD` like the other dates
+    pub start: String,
+    pub previous: String,
+    pub next: String,
+    /// Days from monday to sunday
+    pub days: Vec<DayPlanInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DayPlanInfo {
+    pub date: String,
+    pub meals: Vec<PlannedMealInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlannedMealInfo {
+    pub meal: Meal,
+    pub recipe: String,
+    /// Name of the recipe, `None` if the recipe doesn't exist anymore
+    pub name: Option<String>,
+    pub servings: u8,
+}
+
+/// Outcome of a change requested on the meal planner
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlannerChange {
+    Done,
+    InvalidCredentials,
+    /// The recipe, or the meal to move, doesn't exist
+    NotFound,
+    InvalidServings,
+    InvalidDate,
+}
+
+#[server(GetWeekPlan, encoding = "GetCbor")]
+pub async fn get_week_plan(week: String) -> Result<Option<WeekPlanInfo>, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{planner::PlannerError, Database},
+        date::Date,
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(None);
+    };
+
+    // Any day of the week, the current week if there is none
+    let monday = Date::parse(&week).unwrap_or_else(Date::today).monday();
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    let meals = match database.get_week_plan(user_name, user_token, monday).await {
+        Ok(meals) => meals,
+        Err(PlannerError::Database(err)) => {
+            error!("Database error while getting week plan, {:?}", err);
+            return Err(ServerFnError::ServerError("".into()));
+        }
+        Err(PlannerError::InvalidCredentials) => return Ok(None),
+    };
+
+    let days = (0..7)
+        .map(|offset| {
+            let date = monday.add_days(offset);
+            DayPlanInfo {
+                date: date.to_string(),
+                meals: meals
+                    .iter()
+                    .filter(|(meal_date, _, _)| *meal_date == date)
+                    .map(|(_, meal, name)| PlannedMealInfo {
+                        meal: meal.meal,
+                        recipe: meal.recipe.to_hex(),
+                        name: name.clone(),
+                        servings: meal.servings,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(Some(WeekPlanInfo {
+        start: monday.to_string(),
+        previous: monday.add_days(-7).to_string(),
+        next: monday.add_days(7).to_string(),
+        days,
+    }))
+}
+
+#[server(PlanMeal, encoding = "Cbor")]
+pub async fn plan_meal(
+    date: String,
+    meal: Meal,
+    recipe: String,
+    servings: Option<u8>,
+) -> Result<PlannerChange, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{planner::PlanMealError, Database},
+        date::Date,
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(PlannerChange::InvalidCredentials);
+    };
+
+    let Some(date) = Date::parse(&date) else {
+        return Ok(PlannerChange::InvalidDate);
+    };
+
+    let Ok(recipe) = ObjectId::parse_str(recipe) else {
+        return Ok(PlannerChange::NotFound);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .plan_meal(user_name, user_token, date, meal, recipe, servings)
+        .await
+    {
+        Ok(()) => Ok(PlannerChange::Done),
+        Err(PlanMealError::Database(err)) => {
+            error!("Database error while planning meal, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(PlanMealError::InvalidCredentials) => Ok(PlannerChange::InvalidCredentials),
+        Err(PlanMealError::NotFound) => Ok(PlannerChange::NotFound),
+        Err(PlanMealError::InvalidServings) => Ok(PlannerChange::InvalidServings),
+    }
+}
+
+#[server(MoveMeal, encoding = "Cbor")]
+pub async fn move_meal(
+    from: String,
+    from_meal: Meal,
+    to: String,
+    to_meal: Meal,
+) -> Result<PlannerChange, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{planner::MoveMealError, Database},
+        date::Date,
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(PlannerChange::InvalidCredentials);
+    };
+
+    let (Some(from), Some(to)) = (Date::parse(&from), Date::parse(&to)) else {
+        return Ok(PlannerChange::InvalidDate);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .move_meal(user_name, user_token, (from, from_meal), (to, to_meal))
+        .await
+    {
+        Ok(()) => Ok(PlannerChange::Done),
+        Err(MoveMealError::Database(err)) => {
+            error!("Database error while moving meal, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(MoveMealError::InvalidCredentials) => Ok(PlannerChange::InvalidCredentials),
+        Err(MoveMealError::NotFound) => Ok(PlannerChange::NotFound),
+    }
+}
+
+#[server(UnplanMeal, encoding = "Cbor")]
+pub async fn unplan_meal(date: String, meal: Meal) -> Result<PlannerChange, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{planner::PlannerError, Database},
+        date::Date,
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(PlannerChange::InvalidCredentials);
+    };
+
+    let Some(date) = Date::parse(&date) else {
+        return Ok(PlannerChange::InvalidDate);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .unplan_meal(user_name, user_token, date, meal)
+        .await
+    {
+        Ok(()) => Ok(PlannerChange::Done),
+        Err(PlannerError::Database(err)) => {
+            error!("Database error while unplanning meal, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(PlannerError::InvalidCredentials) => Ok(PlannerChange::InvalidCredentials),
+    }
+}
+
+#[server(BuildWeekShoppingList, encoding = "Cbor")]
+pub async fn build_week_shopping_list(week: String) -> Result<PlannerChange, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{planner::PlannerError, Database},
+        date::Date,
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(PlannerChange::InvalidCredentials);
+    };
+
+    let Some(week) = Date::parse(&week) else {
+        return Ok(PlannerChange::InvalidDate);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .add_week_to_shopping_list(user_name, user_token, week)
+        .await
+    {
+        Ok(()) => Ok(PlannerChange::Done),
+        Err(PlannerError::Database(err)) => {
+            error!(
+                "Database error while building week shopping list, {:?}",
+                err
+            );
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(PlannerError::InvalidCredentials) => Ok(PlannerChange::InvalidCredentials),
+    }
+}
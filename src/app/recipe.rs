@@ -0,0 +1,433 @@
+use std::rc::Rc;
+
+use leptos::{
+    component, create_action, create_memo, create_resource, create_rw_signal, event_target_checked,
+    event_target_value, server, view, CollectView, IntoView, ServerFnError, SignalGet,
+    SignalGetUntracked, SignalSet, SignalWith, Suspense,
+};
+use leptos_router::{use_navigate, use_params_map, A};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::{
+        categories::CategoryInfo,
+        comments::CommentThread,
+        recipes::format_rating,
+        shopping::{self, add_to_shopping_list, ShoppingChange},
+        users::{get_unit_preference, set_unit_preference, AuthorLink, UserChange},
+    },
+    model::{Ingredient, Tool, LEVEL_RANGE, NOTE_RANGE, SERVINGS_RANGE},
+    quantity::{density, Amount, UnitSystem},
+    validation::ValidationError,
+};
+
+#[component]
+pub fn RecipeView() -> impl IntoView {
+    let params = use_params_map();
+    let recipe = create_resource(
+        move || params.with(|params| params.get("id").cloned().unwrap_or_default()),
+        get_recipe,
+    );
+
+    view! {
+        <Suspense fallback=|| view! { <p> "Loading..." </p> }>
+            {move || recipe.get().map(|recipe| match recipe {
+                Ok(Some(recipe)) => view! { <RecipeDetailsView recipe=recipe/> }.into_view(),
+                Ok(None) => view! { <h2> "Recipe not found" </h2> }.into_view(),
+                Err(_) => view! { <p> "Failed to query recipe" </p> }.into_view(),
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn RecipeDetailsView(recipe: RecipeDetails) -> impl IntoView {
+    let error = create_rw_signal(None);
+    let navigate = Rc::new(use_navigate());
+
+    let id = recipe.id.clone();
+    let delete_action = create_action(move |()| {
+        error.set(None);
+
+        let id = id.clone();
+        let navigate = Rc::clone(&navigate);
+
+        async move {
+            match delete_recipe(id).await {
+                Ok(RecipeChange::Done) => navigate("/recipes", Default::default()),
+                Ok(RecipeChange::InvalidCredentials) => {
+                    error.set(Some("You must be signed in to delete a recipe"))
+                }
+                Ok(RecipeChange::NotFound) => error.set(Some("This recipe no longer exists")),
+                Ok(RecipeChange::Forbidden) => {
+                    error.set(Some("Only the author or an admin can delete this recipe"))
+                }
+                Err(ServerFnError::Request(_)) => error.set(Some("Network error")),
+                Ok(RecipeChange::Invalid(_)) | Err(_) => {
+                    error.set(Some("Internal error, retry later"))
+                }
+            };
+        }
+    });
+
+    let servings = create_rw_signal(recipe.servings);
+    let base_servings = recipe.servings;
+    let scale = create_memo(move |_| {
+        Amount::new(u64::from(servings.get()), u64::from(base_servings)).unwrap_or(Amount::ONE)
+    });
+
+    let preference = create_resource(|| (), |()| get_unit_preference());
+    let units = move || preference.get().and_then(Result::ok).flatten();
+    let show_original = create_rw_signal(false);
+    let units_error = create_rw_signal(None);
+
+    let units_action = create_action(move |units: &Option<UnitSystem>| {
+        units_error.set(None);
+        // Applied right away, even if it can't be saved
+        preference.set(Ok(*units));
+
+        let units = *units;
+
+        async move {
+            match set_unit_preference(units).await {
+                Ok(UserChange::Done) => {}
+                Ok(UserChange::InvalidCredentials) => {
+                    units_error.set(Some("Sign in to remember this choice"))
+                }
+                Err(ServerFnError::Request(_)) => units_error.set(Some("Network error")),
+                Ok(_) | Err(_) => units_error.set(Some("Internal error, retry later")),
+            };
+        }
+    });
+
+    let shopping_message = create_rw_signal(None);
+
+    let id = recipe.id.clone();
+    let shopping_action = create_action(move |servings: &u8| {
+        shopping_message.set(None);
+
+        let id = id.clone();
+        let servings = *servings;
+
+        async move {
+            let result = add_to_shopping_list(id, Some(servings)).await;
+            shopping_message.set(if result == Ok(ShoppingChange::Done) {
+                Some("Added to your shopping list")
+            } else {
+                shopping::change_error(result)
+            });
+        }
+    });
+
+    let rating = create_rw_signal((recipe.rating, recipe.rating_count));
+    let rating_error = create_rw_signal(None);
+
+    let id = recipe.id.clone();
+    let rate_action = create_action(move |note: &u8| {
+        rating_error.set(None);
+
+        let id = id.clone();
+        let note = *note;
+
+        async move {
+            match rate_recipe(id.clone(), note).await {
+                Ok(RecipeChange::Done) => {
+                    if let Ok(Some(recipe)) = get_recipe(id).await {
+                        rating.set((recipe.rating, recipe.rating_count));
+                    }
+                }
+                Ok(RecipeChange::InvalidCredentials) => {
+                    rating_error.set(Some("You must be signed in to rate a recipe"))
+                }
+                Ok(RecipeChange::NotFound) => {
+                    rating_error.set(Some("This recipe no longer exists"))
+                }
+                Ok(RecipeChange::Forbidden) => {
+                    rating_error.set(Some("Banned users can't rate recipes"))
+                }
+                Err(ServerFnError::Request(_)) => rating_error.set(Some("Network error")),
+                Ok(RecipeChange::Invalid(_)) | Err(_) => {
+                    rating_error.set(Some("Internal error, retry later"))
+                }
+            };
+        }
+    });
+
+    view! {
+        <h2> {recipe.name} </h2>
+        <p> "By " <AuthorLink author=recipe.author/> </p>
+        <div>
+            <A href=format!("/recipes/{}/edit", recipe.id)> "Edit" </A>
+            <button on:click=move |_| delete_action.dispatch(())> "Delete" </button>
+            {move || error.get()}
+        </div>
+        <img src=recipe.icon_url/>
+        <p>
+            {recipe.categories.into_iter().map(|category| view! {
+                <A href=format!("/categories/{}", category.id)> {category.name} </A> " "
+            }).collect_view()}
+        </p>
+        <p> "Price level: " {recipe.price_level} "/" {*LEVEL_RANGE.end()} </p>
+        <p> "Healthy level: " {recipe.healthy_level} "/" {*LEVEL_RANGE.end()} </p>
+        <p>
+            {move || rating.with(|(rating, count)| format_rating(*rating, *count))}
+            " Rate: "
+            {NOTE_RANGE.map(|note| view! {
+                <button on:click=move |_| rate_action.dispatch(note)> {note} "★" </button>
+            }).collect_view()}
+            {move || rating_error.get()}
+        </p>
+
+        <h3> "Ingredients" </h3>
+        <p>
+            "Servings "
+            <input
+                type="number" min=*SERVINGS_RANGE.start() max=*SERVINGS_RANGE.end()
+                prop:value=move || servings.get()
+                on:input=move |ev| {
+                    if let Some(count) = event_target_value(&ev).parse().ok().filter(|count| SERVINGS_RANGE.contains(count)) {
+                        servings.set(count);
+                    }
+                }
+            />
+            {move || (servings.get() != base_servings).then(|| view! {
+                <button on:click=move |_| servings.set(base_servings)> "Reset" </button>
+            })}
+        </p>
+        <p>
+            "Units "
+            <select on:change=move |ev| units_action.dispatch(match event_target_value(&ev).as_str() {
+                "metric" => Some(UnitSystem::Metric),
+                "imperial" => Some(UnitSystem::Imperial),
+                _ => None,
+            })>
+                <option value="" selected=move || units().is_none()> "As written" </option>
+                <option value="metric" selected=move || units() == Some(UnitSystem::Metric)> "Metric" </option>
+                <option value="imperial" selected=move || units() == Some(UnitSystem::Imperial)> "Imperial" </option>
+            </select>
+            {move || units().is_some().then(|| view! {
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=move || show_original.get()
+                        on:change=move |ev| show_original.set(event_target_checked(&ev))
+                    />
+                    "Show original units"
+                </label>
+            })}
+            {move || units_error.get()}
+        </p>
+        <ul>
+            {recipe.ingredients.into_iter().map(|ingredient| {
+                let quantity = ingredient.quantity;
+                let density = density(&ingredient.name);
+                let shown = move || {
+                    let quantity = quantity.scaled(scale.get());
+                    match units() {
+                        Some(units) if !show_original.get() => quantity.converted(units, density),
+                        _ => quantity,
+                    }
+                };
+                view! {
+                    <li>
+                        <img src=ingredient.icon_url/>
+                        {ingredient.name} " " {move || shown().to_string()}
+                    </li>
+                }
+            }).collect_view()}
+        </ul>
+        <p>
+            <button on:click=move |_| shopping_action.dispatch(servings.get_untracked())>
+                "Add to shopping list"
+            </button>
+            " " {move || shopping_message.get()}
+        </p>
+
+        <h3> "Tools" </h3>
+        <ul>
+            {recipe.tools.into_iter().map(|tool| view! {
+                <li>
+                    <img src=tool.icon_url/>
+                    {tool.name}
+                </li>
+            }).collect_view()}
+        </ul>
+
+        <h3> "Instructions" </h3>
+        <ol>
+            {recipe.instructions.into_iter().map(|instruction| view! {
+                <li> {instruction} </li>
+            }).collect_view()}
+        </ol>
+
+        <h3> "Comments" </h3>
+        <CommentThread recipe=recipe.id.clone()/>
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecipeDetails {
+    pub id: String,
+    /// Name of the author, `None` if the author doesn't exist anymore
+    pub author: Option<String>,
+    pub name: String,
+    pub icon_url: String,
+    pub price_level: u8,
+    pub healthy_level: u8,
+    pub servings: u8,
+    pub instructions: Vec<String>,
+    pub ingredients: Vec<Ingredient>,
+    pub tools: Vec<Tool>,
+    /// Average note, `None` if nobody rated the recipe
+    pub rating: Option<f32>,
+    pub rating_count: u32,
+    pub categories: Vec<CategoryInfo>,
+}
+
+/// Outcome of a change requested on a recipe or its comments
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RecipeChange {
+    Done,
+    InvalidCredentials,
+    NotFound,
+    Forbidden,
+    /// The fields of the recipe were rejected for these reasons
+    Invalid(Vec<ValidationError>),
+}
+
+#[server(GetRecipe, encoding = "GetCbor")]
+pub async fn get_recipe(id: String) -> Result<Option<RecipeDetails>, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::database::{categories::GetCategoriesError, recipes::GetRecipeError, Database};
+
+    let Ok(id) = ObjectId::parse_str(id) else {
+        return Ok(None);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    let (recipe, author) = match database.get_recipe(id).await {
+        Ok(Some(recipe)) => recipe,
+        Ok(None) => return Ok(None),
+        Err(GetRecipeError::Database(err)) => {
+            error!("Database error while getting recipe, {:?}", err);
+            return Err(ServerFnError::ServerError("".into()));
+        }
+    };
+
+    let categories = match database.get_categories().await {
+        Ok(categories) => categories,
+        Err(GetCategoriesError::Database(err)) => {
+            error!("Database error while getting recipe categories, {:?}", err);
+            return Err(ServerFnError::ServerError("".into()));
+        }
+    };
+
+    Ok(Some(RecipeDetails {
+        id: recipe.id.to_hex(),
+        author,
+        rating: recipe.rating(),
+        rating_count: u32::try_from(recipe.notes.len()).unwrap_or(u32::MAX),
+        name: recipe.name,
+        icon_url: recipe.icon_url,
+        price_level: recipe.price_level,
+        healthy_level: recipe.healthy_level,
+        servings: recipe.servings,
+        instructions: recipe.instructions,
+        ingredients: recipe.ingredients,
+        tools: recipe.tools,
+        categories: categories
+            .into_iter()
+            .filter(|category| recipe.categories.contains(&category.id))
+            .map(|category| CategoryInfo {
+                id: category.id.to_hex(),
+                name: category.name,
+            })
+            .collect(),
+    }))
+}
+
+#[server(DeleteRecipe, encoding = "Cbor")]
+async fn delete_recipe(id: String) -> Result<RecipeChange, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{recipes::DeleteRecipeError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(RecipeChange::InvalidCredentials);
+    };
+
+    let Ok(id) = ObjectId::parse_str(id) else {
+        return Ok(RecipeChange::NotFound);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.delete_recipe(user_name, user_token, id).await {
+        Ok(()) => Ok(RecipeChange::Done),
+        Err(DeleteRecipeError::Database(err)) => {
+            error!("Database error while deleting recipe, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(DeleteRecipeError::InvalidCredentials) => Ok(RecipeChange::InvalidCredentials),
+        Err(DeleteRecipeError::NotFound) => Ok(RecipeChange::NotFound),
+        Err(DeleteRecipeError::Forbidden) => Ok(RecipeChange::Forbidden),
+    }
+}
+
+#[server(RateRecipe, encoding = "Cbor")]
+async fn rate_recipe(id: String, note: u8) -> Result<RecipeChange, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{recipes::RateRecipeError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(RecipeChange::InvalidCredentials);
+    };
+
+    let Ok(id) = ObjectId::parse_str(id) else {
+        return Ok(RecipeChange::NotFound);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.rate_recipe(user_name, user_token, id, note).await {
+        Ok(()) => Ok(RecipeChange::Done),
+        Err(RateRecipeError::Database(err)) => {
+            error!("Database error while rating recipe, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(RateRecipeError::InvalidCredentials) => Ok(RecipeChange::InvalidCredentials),
+        Err(RateRecipeError::NotFound) => Ok(RecipeChange::NotFound),
+        Err(RateRecipeError::Forbidden) => Ok(RecipeChange::Forbidden),
+        Err(RateRecipeError::InvalidNote) => Err(ServerFnError::Args(format!(
+            "The note must be between {} and {}",
+            NOTE_RANGE.start(),
+            NOTE_RANGE.end()
+        ))),
+    }
+}
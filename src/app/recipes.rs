@@ -1,57 +1,664 @@
-use leptos::{component, server, view, Await, CollectView, IntoView, ServerFnError};
+use leptos::{
+    component, create_action, create_effect, create_memo, create_resource, create_rw_signal,
+    event_target_checked, event_target_value, server, store_value, view, Callable, Callback,
+    CollectView, IntoView, Memo, RwSignal, ServerFnError, SignalGet, SignalGetUntracked, SignalSet,
+    SignalUpdate, SignalWith, SignalWithUntracked, Suspense, Transition,
+};
+use leptos_router::{use_navigate, use_query_map, ParamsMap, A};
 use serde::{Deserialize, Serialize};
 
+use crate::app::{categories::get_categories, users::AuthorLink};
+
 #[component]
 pub fn RecipesView() -> impl IntoView {
+    let query = use_query_map();
+    let list = create_memo(move |_| query.with(RecipeListQuery::from_query));
+    let searching = create_memo(move |_| list.with(|list| !list.search.trim().is_empty()));
+
+    let navigate = store_value(use_navigate());
+    let go = Callback::new(move |list: RecipeListQuery| {
+        navigate.with_value(|navigate| navigate(&list.url(), Default::default()))
+    });
+
+    let draft = create_rw_signal(list.get_untracked().search);
+    let submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        go.call(RecipeListQuery {
+            search: draft.get_untracked(),
+            page: 0,
+            ..list.get_untracked()
+        });
+    };
+
+    let apply_filter = Callback::new(move |filter| {
+        go.call(RecipeListQuery {
+            filter,
+            page: 0,
+            ..list.get_untracked()
+        })
+    });
+
     view! {
         <h2> "Recipes" </h2>
-        <Await
-            future=move || get_recipes()
-            let:recipes
-        >
-            {
-                match recipes.as_ref() {
-                    Ok(recipes) => {
-                        recipes.iter().map(|recipe| {
-                            view! { <p> {&recipe.name} </p> }
-                        }).collect_view()
-                    },
-                    Err(_) => {
-                        view! { <p> "Failed to query recipes" </p> }.into_view()
-                    },
-                }
+        <form on:submit=submit>
+            <input
+                type="search"
+                placeholder="Search recipes"
+                prop:value=move || draft.get()
+                on:input=move |ev| draft.set(event_target_value(&ev))
+            />
+            <button type="submit"> "Search" </button>
+        </form>
+        <label hidden=move || searching.get()>
+            "Sort by "
+            <select on:change=move |ev| {
+                go.call(RecipeListQuery {
+                    sort: RecipeSort::from_query(Some(&event_target_value(&ev))),
+                    ..list.get_untracked()
+                })
+            }>
+                <option value="oldest" selected=move || list.with(|list| list.sort == RecipeSort::Oldest)> "Oldest" </option>
+                <option value="rating" selected=move || list.with(|list| list.sort == RecipeSort::Rating)> "Rating" </option>
+            </select>
+        </label>
+        {move || view! {
+            <RecipeFilterForm filter=list.with(|list| list.filter.clone()) on_apply=apply_filter/>
+        }}
+        {move || if searching.get() {
+            view! { <RecipeSearchResults list=list go=go/> }
+        } else {
+            view! { <RecipeBrowser list=list/> }
+        }}
+    }
+}
+
+/// Recipes of the list, loaded a page at a time with a "Load more" button
+#[component]
+fn RecipeBrowser(list: Memo<RecipeListQuery>) -> impl IntoView {
+    const PAGE_SIZE: usize = 20;
+
+    let source = move || list.with(|list| (list.sort, list.filter.clone()));
+    let first = create_resource(source, |(sort, filter)| {
+        get_recipes(sort, filter, None, PAGE_SIZE)
+    });
+
+    // Pages loaded after the first one, for the current source
+    let more = create_rw_signal(Vec::<RecipeListInfo>::new());
+    let error = create_rw_signal(None);
+    create_effect(move |_| {
+        source();
+        more.set(Vec::new());
+        error.set(None);
+    });
+
+    let load_more = create_action(move |cursor: &String| {
+        error.set(None);
+
+        let cursor = cursor.clone();
+        let loaded_source = source();
+
+        async move {
+            let page = get_recipes(
+                loaded_source.0,
+                loaded_source.1.clone(),
+                Some(cursor),
+                PAGE_SIZE,
+            )
+            .await;
+
+            // The list changed while loading
+            if list.with_untracked(|list| {
+                (list.sort, &list.filter) != (loaded_source.0, &loaded_source.1)
+            }) {
+                return;
+            }
+
+            match page {
+                Ok(page) => more.update(|more| more.push(page)),
+                Err(ServerFnError::Request(_)) => error.set(Some("Network error")),
+                Err(_) => error.set(Some("Failed to load more recipes")),
+            }
+        }
+    });
+
+    let next = move || match more.with(|more| more.last().map(|page| page.next.clone())) {
+        Some(next) => next,
+        None => first.with(|first| {
+            first
+                .as_ref()
+                .and_then(|first| first.as_ref().ok())
+                .and_then(|first| first.next.clone())
+        }),
+    };
+
+    view! {
+        <Transition fallback=|| view! { <p> "Loading..." </p> }>
+            {move || first.get().map(|first| match first {
+                Ok(first) if first.recipes.is_empty()
+                    && list.with(|list| list.filter != RecipeFilter::default()) => {
+                    view! { <p> "No recipe matches" </p> }.into_view()
+                },
+                Ok(first) => view! {
+                    <RecipeRows recipes=first.recipes/>
+                    {move || more.get().into_iter().map(|page| view! {
+                        <RecipeRows recipes=page.recipes/>
+                    }).collect_view()}
+                    {move || next().map(|cursor| view! {
+                        <button
+                            disabled=move || load_more.pending().get()
+                            on:click=move |_| load_more.dispatch(cursor.clone())
+                        > "Load more" </button>
+                    })}
+                    {move || error.get()}
+                }.into_view(),
+                Err(_) => view! { <p> "Failed to query recipes" </p> }.into_view(),
+            })}
+        </Transition>
+    }
+}
+
+/// Recipes of the category with the id `category`, oldest first,
+/// loaded a page at a time like the recipe list
+#[component]
+pub fn CategoryRecipes(category: String) -> impl IntoView {
+    let list = create_memo(move |_| RecipeListQuery {
+        filter: RecipeFilter {
+            categories: vec![category.clone()],
+            ..RecipeFilter::default()
+        },
+        ..RecipeListQuery::default()
+    });
+
+    view! { <RecipeBrowser list=list/> }
+}
+
+/// Recipes matching the search of the list, most relevant first, with numbered pages
+#[component]
+fn RecipeSearchResults(
+    list: Memo<RecipeListQuery>,
+    go: Callback<RecipeListQuery>,
+) -> impl IntoView {
+    let recipes = create_resource(
+        move || list.get(),
+        |list| search_recipes(list.search, list.filter, list.page),
+    );
+
+    view! {
+        <Transition fallback=|| view! { <p> "Loading..." </p> }>
+            {move || recipes.get().map(|recipes| match recipes {
+                Ok(recipes) if recipes.recipes.is_empty() => {
+                    view! { <p> "No recipe matches" </p> }.into_view()
+                },
+                Ok(recipes) => {
+                    let current = recipes.page;
+                    let page_count = recipes.page_count;
+                    let page = move |page| go.call(RecipeListQuery { page, ..list.get_untracked() });
+
+                    view! {
+                        <RecipeRows recipes=recipes.recipes/>
+                        <div hidden=page_count <= 1>
+                            <button
+                                disabled=current == 0
+                                on:click=move |_| page(current.saturating_sub(1))
+                            > "Previous" </button>
+                            " Page " {current + 1} "/" {page_count} " "
+                            <button
+                                disabled=current + 1 >= page_count
+                                on:click=move |_| page(current + 1)
+                            > "Next" </button>
+                        </div>
+                    }.into_view()
+                },
+                Err(_) => view! { <p> "Failed to query recipes" </p> }.into_view(),
+            })}
+        </Transition>
+    }
+}
+
+#[component]
+fn RecipeRows(recipes: Vec<RecipeInfo>) -> impl IntoView {
+    recipes
+        .into_iter()
+        .map(|recipe| {
+            view! {
+                <p>
+                    <A href=format!("/recipes/{}", recipe.id)> {recipe.name} </A>
+                    " by " <AuthorLink author=recipe.author/>
+                    " " {format_rating(recipe.rating, recipe.rating_count)}
+                </p>
             }
-        </Await>
+        })
+        .collect_view()
+}
+
+/// Controls editing a [`RecipeFilter`], which is given to `on_apply` when submitted
+#[component]
+fn RecipeFilterForm(filter: RecipeFilter, on_apply: Callback<RecipeFilter>) -> impl IntoView {
+    let level = |level: Option<u8>| {
+        create_rw_signal(level.map(|level| level.to_string()).unwrap_or_default())
+    };
+    let min_price_level = level(filter.min_price_level);
+    let max_price_level = level(filter.max_price_level);
+    let min_healthy_level = level(filter.min_healthy_level);
+    let max_healthy_level = level(filter.max_healthy_level);
+    let ingredients = create_rw_signal(filter.ingredients.join(", "));
+    let excluded_ingredients = create_rw_signal(filter.excluded_ingredients.join(", "));
+    let limit_tools = create_rw_signal(filter.tools.is_some());
+    let tools = create_rw_signal(filter.tools.unwrap_or_default().join(", "));
+    let categories = create_rw_signal(filter.categories);
+
+    let all_categories = create_resource(|| (), |()| get_categories());
+
+    let submit = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+
+        let level = |level: RwSignal<String>| level.get_untracked().trim().parse().ok();
+        on_apply.call(RecipeFilter {
+            min_price_level: level(min_price_level),
+            max_price_level: level(max_price_level),
+            min_healthy_level: level(min_healthy_level),
+            max_healthy_level: level(max_healthy_level),
+            ingredients: split_names(&ingredients.get_untracked()),
+            excluded_ingredients: split_names(&excluded_ingredients.get_untracked()),
+            tools: limit_tools
+                .get_untracked()
+                .then(|| split_names(&tools.get_untracked())),
+            categories: categories.get_untracked(),
+        });
+    };
+
+    view! {
+        <form class="recipe_filter" on:submit=submit>
+            <p>
+                "Price level from " <LevelInput value=min_price_level/>
+                " to " <LevelInput value=max_price_level/>
+            </p>
+            <p>
+                "Healthy level from " <LevelInput value=min_healthy_level/>
+                " to " <LevelInput value=max_healthy_level/>
+            </p>
+            <p>
+                "With the ingredients "
+                <input
+                    placeholder="eggs, milk"
+                    prop:value=move || ingredients.get()
+                    on:input=move |ev| ingredients.set(event_target_value(&ev))
+                />
+            </p>
+            <p>
+                "Without the ingredients "
+                <input
+                    placeholder="peanuts"
+                    prop:value=move || excluded_ingredients.get()
+                    on:input=move |ev| excluded_ingredients.set(event_target_value(&ev))
+                />
+            </p>
+            <p>
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=move || limit_tools.get()
+                        on:change=move |ev| limit_tools.set(event_target_checked(&ev))
+                    />
+                    " Only with the tools "
+                </label>
+                <input
+                    placeholder="pan, oven"
+                    disabled=move || !limit_tools.get()
+                    prop:value=move || tools.get()
+                    on:input=move |ev| tools.set(event_target_value(&ev))
+                />
+            </p>
+            <Suspense fallback=|| ()>
+                {move || all_categories.get().map(|all_categories| match all_categories {
+                    Ok(all_categories) => all_categories.into_iter().map(|category| {
+                        let id = category.id;
+                        let checked = {
+                            let id = id.clone();
+                            move || categories.with(|categories| categories.contains(&id))
+                        };
+                        let toggle = move |ev| {
+                            let checked = event_target_checked(&ev);
+                            categories.update(|categories| {
+                                categories.retain(|other| *other != id);
+                                if checked {
+                                    categories.push(id.clone());
+                                }
+                            });
+                        };
+
+                        view! {
+                            <label>
+                                <input type="checkbox" prop:checked=checked on:change=toggle/>
+                                {category.name}
+                            </label>
+                        }
+                    }).collect_view(),
+                    Err(_) => view! { <p> "Failed to query categories" </p> }.into_view(),
+                })}
+            </Suspense>
+            <button type="submit"> "Filter" </button>
+            <button type="button" on:click=move |_| on_apply.call(RecipeFilter::default())> "Clear" </button>
+        </form>
+    }
+}
+
+#[component]
+fn LevelInput(value: RwSignal<String>) -> impl IntoView {
+    view! {
+        <input
+            type="number"
+            min=0
+            max=4
+            prop:value=move || value.get()
+            on:input=move |ev| value.set(event_target_value(&ev))
+        />
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Names separated by commas, without the blank ones
+fn split_names(names: &str) -> Vec<String> {
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// State of the recipe list, kept in the query parameters so it can be shared
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RecipeListQuery {
+    search: String,
+    sort: RecipeSort,
+    filter: RecipeFilter,
+    page: usize,
+}
+
+impl RecipeListQuery {
+    fn from_query(query: &ParamsMap) -> Self {
+        let level = |key: &str| query.get(key).and_then(|level| level.parse().ok());
+        let names = |key: &str| query.get(key).map(|names| split_names(names));
+
+        Self {
+            search: query.get("q").cloned().unwrap_or_default(),
+            sort: RecipeSort::from_query(query.get("sort").map(String::as_str)),
+            filter: RecipeFilter {
+                min_price_level: level("min_price"),
+                max_price_level: level("max_price"),
+                min_healthy_level: level("min_healthy"),
+                max_healthy_level: level("max_healthy"),
+                ingredients: names("ingredients").unwrap_or_default(),
+                excluded_ingredients: names("exclude").unwrap_or_default(),
+                tools: names("tools"),
+                categories: names("categories").unwrap_or_default(),
+            },
+            page: query
+                .get("page")
+                .and_then(|page| page.parse().ok())
+                .unwrap_or(0),
+        }
+    }
+
+    /// Url of the recipe list, only with the query parameters differing from the defaults
+    fn url(&self) -> String {
+        let mut query = ParamsMap::new();
+        let mut insert = |key: &str, value: String| {
+            query.insert(key.to_string(), value);
+        };
+
+        if !self.search.trim().is_empty() {
+            insert("q", self.search.trim().to_string());
+        }
+        if self.sort != RecipeSort::default() {
+            insert("sort", self.sort.to_query().to_string());
+        }
+
+        let filter = &self.filter;
+        let levels = [
+            ("min_price", filter.min_price_level),
+            ("max_price", filter.max_price_level),
+            ("min_healthy", filter.min_healthy_level),
+            ("max_healthy", filter.max_healthy_level),
+        ];
+        for (key, level) in levels {
+            if let Some(level) = level {
+                insert(key, level.to_string());
+            }
+        }
+        let names = [
+            ("ingredients", &filter.ingredients),
+            ("exclude", &filter.excluded_ingredients),
+            ("categories", &filter.categories),
+        ];
+        for (key, names) in names {
+            if !names.is_empty() {
+                insert(key, names.join(","));
+            }
+        }
+        if let Some(tools) = &filter.tools {
+            insert("tools", tools.join(","));
+        }
+
+        if self.page != 0 {
+            insert("page", self.page.to_string());
+        }
+
+        format!("/recipes{}", query.to_query_string())
+    }
+}
+
+/// Average rating along with the number of ratings, like "★ 4.2/5 (12)"
+pub fn format_rating(rating: Option<f32>, rating_count: u32) -> String {
+    match rating {
+        Some(rating) => format!(
+            "★ {:.1}/{} ({})",
+            rating,
+            crate::model::NOTE_RANGE.end(),
+            rating_count
+        ),
+        None => "Not rated yet".to_string(),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct RecipeInfo {
-    name: String,
-    icon_url: String,
+    pub id: String,
+    /// Name of the author, `None` if the author doesn't exist anymore
+    pub author: Option<String>,
+    pub name: String,
+    pub icon_url: String,
+    /// Average note, `None` if nobody rated the recipe
+    pub rating: Option<f32>,
+    pub rating_count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecipePageInfo {
+    pub recipes: Vec<RecipeInfo>,
+    pub page: usize,
+    pub page_count: usize,
+}
+
+/// Page of the recipe list
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecipeListInfo {
+    pub recipes: Vec<RecipeInfo>,
+    /// Opaque cursor to get the next page, `None` if this is the last one
+    pub next: Option<String>,
+}
+
+#[cfg(feature = "server")]
+impl From<(crate::model::RecipeSummary, Option<String>)> for RecipeInfo {
+    fn from((recipe, author): (crate::model::RecipeSummary, Option<String>)) -> Self {
+        Self {
+            id: recipe.id.to_hex(),
+            author,
+            name: recipe.name,
+            icon_url: recipe.icon_url,
+            rating: recipe.rating.map(|rating| rating as f32),
+            rating_count: recipe.rating_count,
+        }
+    }
+}
+
+/// Conditions on the recipes of the list, ignored when unset or empty
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecipeFilter {
+    pub min_price_level: Option<u8>,
+    pub max_price_level: Option<u8>,
+    pub min_healthy_level: Option<u8>,
+    pub max_healthy_level: Option<u8>,
+    /// Ingredients the recipes must all use
+    pub ingredients: Vec<String>,
+    /// Ingredients the recipes must not use
+    pub excluded_ingredients: Vec<String>,
+    /// The only tools the recipes may need, `None` to allow any tool
+    pub tools: Option<Vec<String>>,
+    /// Ids of categories the recipes must be in at least one of
+    pub categories: Vec<String>,
+}
+
+/// Fails if an id of category isn't an object id,
+/// dropping it would list the recipes of every category instead of none
+#[cfg(feature = "server")]
+impl TryFrom<RecipeFilter> for crate::database::recipes::RecipeCriteria {
+    type Error = ServerFnError;
+
+    fn try_from(filter: RecipeFilter) -> Result<Self, Self::Error> {
+        let categories = filter
+            .categories
+            .iter()
+            .map(bson::oid::ObjectId::parse_str)
+            .collect::<Result<_, _>>()
+            .map_err(|_| ServerFnError::Args("Invalid category id".into()))?;
+
+        Ok(Self {
+            min_price_level: filter.min_price_level,
+            max_price_level: filter.max_price_level,
+            min_healthy_level: filter.min_healthy_level,
+            max_healthy_level: filter.max_healthy_level,
+            ingredients: filter.ingredients,
+            excluded_ingredients: filter.excluded_ingredients,
+            tools: filter.tools,
+            categories,
+        })
+    }
 }
 
-#[server(GetRecipes, encoding = "GetCbor")]
-async fn get_recipes() -> Result<Vec<RecipeInfo>, ServerFnError> {
+/// Order of the recipe list, selected by the `sort` query parameter
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecipeSort {
+    #[default]
+    Oldest,
+    Rating,
+}
+
+impl RecipeSort {
+    fn from_query(value: Option<&str>) -> Self {
+        match value {
+            Some("rating") => Self::Rating,
+            _ => Self::Oldest,
+        }
+    }
+
+    fn to_query(self) -> &'static str {
+        match self {
+            Self::Oldest => "oldest",
+            Self::Rating => "rating",
+        }
+    }
+}
+
+// Not GetCbor, its query string can't tell an empty filter or list from a missing one
+#[server(GetRecipes, encoding = "Cbor")]
+pub async fn get_recipes(
+    sort: RecipeSort,
+    filter: RecipeFilter,
+    cursor: Option<String>,
+    page_size: usize,
+) -> Result<RecipeListInfo, ServerFnError> {
     use leptos::logging::error;
 
-    use crate::database::{recipes::GetRecipesError, Database};
+    use crate::database::{
+        recipes::{GetRecipesError, RecipeCriteria, RecipeCursor, RecipeOrder},
+        Database,
+    };
+
+    const MAX_PAGE_SIZE: usize = 100;
+
+    let after = match cursor.as_deref().map(RecipeCursor::decode) {
+        None => None,
+        Some(Some(after)) => Some(after),
+        Some(None) => return Err(ServerFnError::Args("Invalid cursor".into())),
+    };
+
+    let criteria = RecipeCriteria::try_from(filter)?;
 
     let Ok(database) = Database::new().await else {
         return Err(ServerFnError::ServerError("".into()));
     };
 
-    match database.get_recipes(50).await {
-        Ok(recipes) => Ok(recipes
-            .into_iter()
-            .map(|recipe| RecipeInfo {
-                name: recipe.name,
-                icon_url: recipe.icon_url,
-            })
-            .collect()),
+    let order = match sort {
+        RecipeSort::Oldest => RecipeOrder::Insertion,
+        RecipeSort::Rating => RecipeOrder::Rating,
+    };
+
+    match database
+        .get_recipes(
+            page_size.min(MAX_PAGE_SIZE),
+            order,
+            &criteria,
+            after.as_ref(),
+        )
+        .await
+    {
+        Ok(list) => Ok(RecipeListInfo {
+            recipes: list.recipes.into_iter().map(RecipeInfo::from).collect(),
+            next: list.next.map(|next| next.encode()),
+        }),
         Err(GetRecipesError::Database(err)) => {
             error!("Database error while getting recipes, {:?}", err);
             Err(ServerFnError::ServerError("".into()))
         }
+        Err(GetRecipesError::InvalidCursor) => Err(ServerFnError::Args("Invalid cursor".into())),
+    }
+}
+
+// Not GetCbor for the same reason as `GetRecipes`
+#[server(SearchRecipes, encoding = "Cbor")]
+pub async fn search_recipes(
+    query: String,
+    filter: RecipeFilter,
+    page: usize,
+) -> Result<RecipePageInfo, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::database::{
+        recipes::{RecipeCriteria, SearchRecipesError},
+        Database,
+    };
+
+    const RECIPES_PER_PAGE: usize = 20;
+
+    let criteria = RecipeCriteria::try_from(filter)?;
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .search_recipes(&query, &criteria, page, RECIPES_PER_PAGE)
+        .await
+    {
+        Ok(page) => Ok(RecipePageInfo {
+            recipes: page.recipes.into_iter().map(RecipeInfo::from).collect(),
+            page: page.page,
+            page_count: page.page_count,
+        }),
+        Err(SearchRecipesError::Database(err)) => {
+            error!("Database error while searching recipes, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
     }
 }
@@ -0,0 +1,298 @@
+use leptos::{
+    component, create_action, create_resource, create_rw_signal, event_target_checked, server,
+    view, CollectView, IntoView, ServerFnError, SignalGet, SignalSet, Transition,
+};
+use leptos_router::A;
+use serde::{Deserialize, Serialize};
+
+use crate::model::ShoppingItem;
+
+/// Shopping list of the signed in user, at `/shopping`
+#[component]
+pub fn ShoppingListView() -> impl IntoView {
+    let error = create_rw_signal(None);
+    let export = create_rw_signal(None);
+
+    let list = create_resource(|| (), |()| get_shopping_list());
+
+    let check_action = create_action(move |(item, checked): &(String, bool)| {
+        error.set(None);
+
+        let item = item.clone();
+        let checked = *checked;
+
+        async move {
+            error.set(change_error(set_shopping_item_checked(item, checked).await));
+            list.refetch();
+        }
+    });
+
+    let clear_action = create_action(move |only_checked: &bool| {
+        error.set(None);
+
+        let only_checked = *only_checked;
+
+        async move {
+            error.set(change_error(clear_shopping_list(only_checked).await));
+            list.refetch();
+        }
+    });
+
+    let export_action = create_action(move |()| {
+        error.set(None);
+
+        async move {
+            match export_shopping_list().await {
+                Ok(Some(text)) => export.set(Some(text)),
+                Ok(None) => error.set(change_error(Ok(ShoppingChange::InvalidCredentials))),
+                Err(err) => error.set(change_error(Err(err))),
+            }
+        }
+    });
+
+    view! {
+        <h2> "Shopping list" </h2>
+        <Transition fallback=|| view! { <p> "Loading..." </p> }>
+            {move || list.get().map(|list| match list {
+                Ok(Some(items)) if items.is_empty() => view! {
+                    <p> "Your shopping list is empty, add the ingredients of a recipe from its page" </p>
+                }.into_view(),
+                Ok(Some(items)) => view! {
+                    <ul class="shopping_list">
+                        {items.into_iter().map(|item| {
+                            let quantities = item.quantities_text();
+                            let name = item.name.clone();
+                            view! {
+                                <li class:checked=item.checked>
+                                    <label>
+                                        <input
+                                            type="checkbox"
+                                            prop:checked=item.checked
+                                            on:change=move |ev| check_action.dispatch((name.clone(), event_target_checked(&ev)))
+                                        />
+                                        <img src=item.icon_url/>
+                                        {item.name}
+                                        {(!quantities.is_empty()).then(|| format!(": {}", quantities))}
+                                    </label>
+                                </li>
+                            }
+                        }).collect_view()}
+                    </ul>
+                    <button on:click=move |_| clear_action.dispatch(true)> "Remove checked items" </button>
+                    <button on:click=move |_| clear_action.dispatch(false)> "Clear" </button>
+                    <button on:click=move |_| export_action.dispatch(())> "Export as text" </button>
+                }.into_view(),
+                Ok(None) => view! {
+                    <p> <A href="/signin"> "Sign in" </A> " to use a shopping list" </p>
+                }.into_view(),
+                Err(_) => view! { <p> "Failed to query shopping list" </p> }.into_view(),
+            })}
+        </Transition>
+        {move || error.get()}
+        {move || export.get().map(|text| view! {
+            <textarea class="shopping_export" readonly prop:value=text/>
+        })}
+    }
+}
+
+/// Message telling why a change of the shopping list failed, `None` if it succeeded
+pub fn change_error(result: Result<ShoppingChange, ServerFnError>) -> Option<&'static str> {
+    match result {
+        Ok(ShoppingChange::Done) => None,
+        Ok(ShoppingChange::InvalidCredentials) => {
+            Some("You must be signed in to use a shopping list")
+        }
+        Ok(ShoppingChange::NotFound) => Some("This no longer exists"),
+        Ok(ShoppingChange::InvalidServings) => Some("Invalid number of servings"),
+        Err(ServerFnError::Request(_)) => Some("Network error"),
+        Err(_) => Some("Internal error, retry later"),
+    }
+}
+
+/// Outcome of a change requested on a shopping list
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShoppingChange {
+    Done,
+    InvalidCredentials,
+    /// The recipe or the item doesn't exist
+    NotFound,
+    InvalidServings,
+}
+
+#[server(GetShoppingList, encoding = "GetCbor")]
+pub async fn get_shopping_list() -> Result<Option<Vec<ShoppingItem>>, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{shopping::ShoppingListError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(None);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.get_shopping_list(user_name, user_token).await {
+        Ok(list) => Ok(Some(list.items)),
+        Err(ShoppingListError::Database(err)) => {
+            error!("Database error while getting shopping list, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(ShoppingListError::InvalidCredentials) => Ok(None),
+    }
+}
+
+#[server(AddToShoppingList, encoding = "Cbor")]
+pub async fn add_to_shopping_list(
+    recipe: String,
+    servings: Option<u8>,
+) -> Result<ShoppingChange, ServerFnError> {
+    use bson::oid::ObjectId;
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{shopping::AddToShoppingListError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(ShoppingChange::InvalidCredentials);
+    };
+
+    let Ok(recipe) = ObjectId::parse_str(recipe) else {
+        return Ok(ShoppingChange::NotFound);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .add_to_shopping_list(user_name, user_token, recipe, servings)
+        .await
+    {
+        Ok(()) => Ok(ShoppingChange::Done),
+        Err(AddToShoppingListError::Database(err)) => {
+            error!("Database error while adding to shopping list, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(AddToShoppingListError::InvalidCredentials) => Ok(ShoppingChange::InvalidCredentials),
+        Err(AddToShoppingListError::NotFound) => Ok(ShoppingChange::NotFound),
+        Err(AddToShoppingListError::InvalidServings) => Ok(ShoppingChange::InvalidServings),
+    }
+}
+
+#[server(SetShoppingItemChecked, encoding = "Cbor")]
+pub async fn set_shopping_item_checked(
+    item: String,
+    checked: bool,
+) -> Result<ShoppingChange, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{shopping::CheckShoppingItemError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(ShoppingChange::InvalidCredentials);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .set_shopping_item_checked(user_name, user_token, item, checked)
+        .await
+    {
+        Ok(()) => Ok(ShoppingChange::Done),
+        Err(CheckShoppingItemError::Database(err)) => {
+            error!("Database error while checking shopping item, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(CheckShoppingItemError::InvalidCredentials) => Ok(ShoppingChange::InvalidCredentials),
+        Err(CheckShoppingItemError::NotFound) => Ok(ShoppingChange::NotFound),
+    }
+}
+
+#[server(ClearShoppingList, encoding = "Cbor")]
+pub async fn clear_shopping_list(only_checked: bool) -> Result<ShoppingChange, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{shopping::ShoppingListError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(ShoppingChange::InvalidCredentials);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .clear_shopping_list(user_name, user_token, only_checked)
+        .await
+    {
+        Ok(()) => Ok(ShoppingChange::Done),
+        Err(ShoppingListError::Database(err)) => {
+            error!("Database error while clearing shopping list, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(ShoppingListError::InvalidCredentials) => Ok(ShoppingChange::InvalidCredentials),
+    }
+}
+
+#[server(ExportShoppingList, encoding = "GetCbor")]
+pub async fn export_shopping_list() -> Result<Option<String>, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{shopping::ShoppingListError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(None);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.get_shopping_list(user_name, user_token).await {
+        Ok(list) => Ok(Some(list.export())),
+        Err(ShoppingListError::Database(err)) => {
+            error!("Database error while exporting shopping list, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(ShoppingListError::InvalidCredentials) => Ok(None),
+    }
+}
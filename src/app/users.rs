@@ -0,0 +1,280 @@
+use leptos::{
+    component, create_action, create_resource, create_rw_signal, event_target_value, server, view,
+    CollectView, IntoView, ServerFnError, SignalGet, SignalGetUntracked, SignalSet, SignalWith,
+    Suspense,
+};
+use leptos_router::{use_params_map, A};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::recipes::{format_rating, RecipeInfo},
+    quantity::UnitSystem,
+};
+
+/// Public profile of a user, at `/users/:name`
+#[component]
+pub fn UserView() -> impl IntoView {
+    let params = use_params_map();
+    let profile = create_resource(
+        move || params.with(|params| profile_name(params.get("name").map_or("", String::as_str))),
+        get_user_profile,
+    );
+
+    view! {
+        <Suspense fallback=|| view! { <p> "Loading..." </p> }>
+            {move || profile.get().map(|profile| match profile {
+                Ok(Some(profile)) => view! { <UserProfileView profile=profile/> }.into_view(),
+                Ok(None) => view! { <h2> "User not found" </h2> }.into_view(),
+                Err(_) => view! { <p> "Failed to query user" </p> }.into_view(),
+            })}
+        </Suspense>
+    }
+}
+
+#[component]
+fn UserProfileView(profile: UserProfileInfo) -> impl IntoView {
+    view! {
+        <h2> {profile.name} </h2>
+        <p> "Member since " {profile.joined} </p>
+        <p> "Ratings received: " {format_rating(profile.rating, profile.rating_count)} </p>
+
+        <h3> "Recipes" </h3>
+        {if profile.recipes.is_empty() {
+            view! { <p> "No recipe yet" </p> }.into_view()
+        } else {
+            profile.recipes.into_iter().map(|recipe| view! {
+                <p>
+                    <A href=format!("/recipes/{}", recipe.id)> {recipe.name} </A>
+                    " " {format_rating(recipe.rating, recipe.rating_count)}
+                </p>
+            }).collect_view()
+        }}
+    }
+}
+
+/// Link to the profile of `author`, for content whose author may have been deleted
+#[component]
+pub fn AuthorLink(author: Option<String>) -> impl IntoView {
+    match author {
+        Some(author) => view! { <A href=profile_path(&author)> {author} </A> }.into_view(),
+        None => "Deleted user".into_view(),
+    }
+}
+
+/// Characters left as is in a path segment, the unreserved ones of RFC 3986
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Path of the profile of the user named `name`, percent-encoded so any name is a single segment
+pub fn profile_path(name: &str) -> String {
+    format!("/users/{}", utf8_percent_encode(name, PATH_SEGMENT))
+}
+
+/// Name of the user from the `:name` segment of a path built by [`profile_path`],
+/// the segment may already be partially decoded by the router after a client side navigation,
+/// decoding it again is harmless since user names can't contain `%`
+pub fn profile_name(segment: &str) -> String {
+    percent_decode_str(segment).decode_utf8_lossy().into_owned()
+}
+
+/// Admin page to ban and unban users
+#[component]
+pub fn UsersAdminView() -> impl IntoView {
+    let message = create_rw_signal(None);
+    let name = create_rw_signal(String::new());
+
+    let ban_action = create_action(move |(name, banned): &(String, bool)| {
+        message.set(None);
+
+        let name = name.clone();
+        let banned = *banned;
+
+        async move {
+            message.set(Some(match set_banned(name, banned).await {
+                Ok(UserChange::Done) if banned => "User banned",
+                Ok(UserChange::Done) => "User unbanned",
+                Ok(UserChange::InvalidCredentials) => "You must be signed in to manage users",
+                Ok(UserChange::NotFound) => "No user has this name",
+                Ok(UserChange::Forbidden) => {
+                    "Only admins can ban users, and admins can't be banned"
+                }
+                Err(ServerFnError::Request(_)) => "Network error",
+                Err(_) => "Internal error, retry later",
+            }));
+        }
+    });
+
+    view! {
+        <h2> "Manage users" </h2>
+        <div>
+            <input
+                placeholder="Name"
+                prop:value=move || name.get()
+                on:input=move |ev| name.set(event_target_value(&ev))
+            />
+            <button on:click=move |_| ban_action.dispatch((name.get_untracked(), true))> "Ban" </button>
+            <button on:click=move |_| ban_action.dispatch((name.get_untracked(), false))> "Unban" </button>
+        </div>
+        {move || message.get()}
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UserProfileInfo {
+    pub name: String,
+    /// Date at which the user signed up, formatted as `YYYY-MM-DD`
+    pub joined: String,
+    /// Recipes written by the user, oldest first
+    pub recipes: Vec<RecipeInfo>,
+    /// Average of the notes received on all the recipes of the user
+    pub rating: Option<f32>,
+    pub rating_count: u32,
+}
+
+/// Outcome of a change requested on a user
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserChange {
+    Done,
+    InvalidCredentials,
+    NotFound,
+    Forbidden,
+}
+
+#[server(SetBanned, encoding = "Cbor")]
+async fn set_banned(name: String, banned: bool) -> Result<UserChange, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{users::BanUserError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(UserChange::InvalidCredentials);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database
+        .set_banned(user_name, user_token, name, banned)
+        .await
+    {
+        Ok(()) => Ok(UserChange::Done),
+        Err(BanUserError::Database(err)) => {
+            error!("Database error while banning user, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(BanUserError::InvalidCredentials) => Ok(UserChange::InvalidCredentials),
+        Err(BanUserError::NotFound) => Ok(UserChange::NotFound),
+        Err(BanUserError::Forbidden) => Ok(UserChange::Forbidden),
+    }
+}
+
+#[server(GetUnitPreference, encoding = "GetCbor")]
+pub async fn get_unit_preference() -> Result<Option<UnitSystem>, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{users::UnitsError, Database},
+    };
+
+    // Quantities are shown as written to signed out users
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(None);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.get_units(user_name, user_token).await {
+        Ok(units) => Ok(units),
+        Err(UnitsError::Database(err)) => {
+            error!("Database error while getting unit preference, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(UnitsError::InvalidCredentials) => Ok(None),
+    }
+}
+
+#[server(SetUnitPreference, encoding = "Cbor")]
+pub async fn set_unit_preference(units: Option<UnitSystem>) -> Result<UserChange, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::{
+        cookies::{Cookies, Credentials},
+        database::{users::UnitsError, Database},
+    };
+
+    let Ok(Credentials {
+        name: user_name,
+        token: user_token,
+    }) = Cookies::credentials()
+    else {
+        return Ok(UserChange::InvalidCredentials);
+    };
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    match database.set_units(user_name, user_token, units).await {
+        Ok(()) => Ok(UserChange::Done),
+        Err(UnitsError::Database(err)) => {
+            error!("Database error while setting unit preference, {:?}", err);
+            Err(ServerFnError::ServerError("".into()))
+        }
+        Err(UnitsError::InvalidCredentials) => Ok(UserChange::InvalidCredentials),
+    }
+}
+
+#[server(GetUserProfile, encoding = "GetCbor")]
+pub async fn get_user_profile(name: String) -> Result<Option<UserProfileInfo>, ServerFnError> {
+    use leptos::logging::error;
+
+    use crate::database::{users::GetProfileError, Database};
+
+    let Ok(database) = Database::new().await else {
+        return Err(ServerFnError::ServerError("".into()));
+    };
+
+    let profile = match database.get_profile(&name).await {
+        Ok(Some(profile)) => profile,
+        Ok(None) => return Ok(None),
+        Err(GetProfileError::Database(err)) => {
+            error!("Database error while getting user profile, {:?}", err);
+            return Err(ServerFnError::ServerError("".into()));
+        }
+    };
+
+    Ok(Some(UserProfileInfo {
+        joined: profile
+            .joined
+            .try_to_rfc3339_string()
+            .map(|date| date[..10].to_string())
+            .unwrap_or_default(),
+        recipes: profile
+            .recipes
+            .into_iter()
+            .map(|recipe| RecipeInfo::from((recipe, Some(profile.name.clone()))))
+            .collect(),
+        name: profile.name,
+        rating: profile.rating.map(|rating| rating as f32),
+        rating_count: profile.rating_count,
+    }))
+}
@@ -0,0 +1,197 @@
+#![cfg(feature = "server")]
+
+use std::{env, sync::OnceLock, time::Duration};
+
+use thiserror::Error;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Server configuration, read from the environment at startup
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub mongodb_uri: String,
+    pub database_name: String,
+    pub min_pool_size: Option<u32>,
+    pub max_pool_size: Option<u32>,
+    pub token_lifetime: Duration,
+    pub bcrypt_cost: u32,
+    pub storage: Storage,
+    /// Name of a user to promote to admin at startup
+    pub admin: Option<String>,
+}
+
+/// Storage backend of the [`Database`](crate::database::Database)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Storage {
+    MongoDb,
+    /// Not persisted, lost when the server stops
+    Memory,
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("{variable} is not valid unicode")]
+    NotUnicode { variable: &'static str },
+    #[error("{variable} must be {expected}, got {value:?}")]
+    Invalid {
+        variable: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+    #[error("RECIPE_MIN_POOL_SIZE ({min}) is greater than RECIPE_MAX_POOL_SIZE ({max})")]
+    PoolSize { min: u32, max: u32 },
+    #[error("The configuration was already initialized")]
+    AlreadyInitialized,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            mongodb_uri: "mongodb://localhost:27017".to_string(),
+            database_name: "recipe_final_exercise".to_string(),
+            min_pool_size: None,
+            max_pool_size: None,
+            token_lifetime: Duration::from_secs(60 * 60 * 24 * 30),
+            bcrypt_cost: bcrypt::DEFAULT_COST,
+            storage: Storage::MongoDb,
+            admin: None,
+        }
+    }
+}
+
+impl Config {
+    /// Read the configuration from the `RECIPE_*` environment variables,
+    /// falling back to the defaults for the missing ones
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(|variable| env::var(variable))
+    }
+
+    /// Read the configuration from the `RECIPE_*` variables given by `lookup`,
+    /// which is called like [`env::var`]
+    pub fn from_vars(
+        lookup: impl Fn(&str) -> Result<String, env::VarError>,
+    ) -> Result<Self, ConfigError> {
+        let default = Self::default();
+
+        let mongodb_uri = var(&lookup, "RECIPE_MONGODB_URI")?.unwrap_or(default.mongodb_uri);
+        if !mongodb_uri.starts_with("mongodb://") && !mongodb_uri.starts_with("mongodb+srv://") {
+            return Err(ConfigError::Invalid {
+                variable: "RECIPE_MONGODB_URI",
+                value: mongodb_uri,
+                expected: "a mongodb:// or mongodb+srv:// uri",
+            });
+        }
+
+        let database_name = var(&lookup, "RECIPE_DATABASE_NAME")?.unwrap_or(default.database_name);
+        if database_name.is_empty()
+            || database_name.len() > 63
+            || database_name.contains(['/', '\\', '.', ' ', '"', '$'])
+        {
+            return Err(ConfigError::Invalid {
+                variable: "RECIPE_DATABASE_NAME",
+                value: database_name,
+                expected: "a mongodb database name",
+            });
+        }
+
+        let min_pool_size = parse_var(&lookup, "RECIPE_MIN_POOL_SIZE", "an integer", |_| true)?;
+        let max_pool_size = parse_var(
+            &lookup,
+            "RECIPE_MAX_POOL_SIZE",
+            "a positive integer",
+            |size| *size > 0,
+        )?;
+        if let (Some(min), Some(max)) = (min_pool_size, max_pool_size) {
+            if min > max {
+                return Err(ConfigError::PoolSize { min, max });
+            }
+        }
+
+        let token_lifetime = parse_var::<u64>(
+            &lookup,
+            "RECIPE_TOKEN_LIFETIME_DAYS",
+            "a positive number of days",
+            |days| (1..=3650).contains(days),
+        )?
+        .map_or(default.token_lifetime, |days| {
+            Duration::from_secs(60 * 60 * 24 * days)
+        });
+
+        let bcrypt_cost = parse_var(
+            &lookup,
+            "RECIPE_BCRYPT_COST",
+            "an integer between 4 and 31",
+            |cost| (4..=31).contains(cost),
+        )?
+        .unwrap_or(default.bcrypt_cost);
+
+        let storage = match var(&lookup, "RECIPE_STORAGE")?.as_deref() {
+            None | Some("mongodb") => Storage::MongoDb,
+            Some("memory") => Storage::Memory,
+            Some(value) => {
+                return Err(ConfigError::Invalid {
+                    variable: "RECIPE_STORAGE",
+                    value: value.to_string(),
+                    expected: "mongodb or memory",
+                })
+            }
+        };
+
+        let admin = var(&lookup, "RECIPE_ADMIN")?.filter(|name| !name.is_empty());
+
+        Ok(Self {
+            mongodb_uri,
+            database_name,
+            min_pool_size,
+            max_pool_size,
+            token_lifetime,
+            bcrypt_cost,
+            storage,
+            admin,
+        })
+    }
+
+    /// Make this configuration the one returned by [`Config::get`]
+    /// Should be called once at start of the program
+    pub fn init(self) -> Result<(), ConfigError> {
+        CONFIG
+            .set(self)
+            .map_err(|_| ConfigError::AlreadyInitialized)
+    }
+
+    /// Get the configuration of the program, the default one if it wasn't initialized
+    pub fn get() -> &'static Config {
+        CONFIG.get_or_init(Config::default)
+    }
+}
+
+fn var(
+    lookup: &impl Fn(&str) -> Result<String, env::VarError>,
+    variable: &'static str,
+) -> Result<Option<String>, ConfigError> {
+    match lookup(variable) {
+        Ok(value) => Ok(Some(value)),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(ConfigError::NotUnicode { variable }),
+    }
+}
+
+fn parse_var<T: std::str::FromStr>(
+    lookup: &impl Fn(&str) -> Result<String, env::VarError>,
+    variable: &'static str,
+    expected: &'static str,
+    is_valid: impl Fn(&T) -> bool,
+) -> Result<Option<T>, ConfigError> {
+    let Some(value) = var(lookup, variable)? else {
+        return Ok(None);
+    };
+
+    match value.trim().parse() {
+        Ok(parsed) if is_valid(&parsed) => Ok(Some(parsed)),
+        _ => Err(ConfigError::Invalid {
+            variable,
+            value,
+            expected,
+        }),
+    }
+}
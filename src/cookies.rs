@@ -1,66 +1,71 @@
+#![cfg(feature = "server")]
+
+use actix_web::{
+    cookie::{time::OffsetDateTime, Cookie, SameSite},
+    http::header::{HeaderValue, SET_COOKIE},
+    HttpRequest,
+};
 use bson::DateTime;
-use wasm_bindgen::JsCast;
-use web_sys::HtmlDocument;
+use leptos::use_context;
+use leptos_actix::ResponseOptions;
 
 pub struct Credentials {
     pub name: String,
     pub token: String,
 }
 
-pub struct Cookies(HtmlDocument);
+/// Session cookies of the request handled by the current server function
+///
+/// The cookies are `HttpOnly` so the token is never readable from javascript
+pub struct Cookies;
 impl Cookies {
     pub fn set_credentials(name: &str, token: &str, expiration: &DateTime) -> Result<(), ()> {
-        Cookies::new().map_or(Err(()), |cookies| {
-            cookies.set("name", name, expiration)?;
-            cookies.set("token", token, expiration)?;
-            Ok(())
-        })
-    }
-    pub fn credentials() -> Result<Credentials, ()> {
-        Cookies::new().map_or(Err(()), |cookies| {
-            Ok(Credentials {
-                name: cookies.get("name")?,
-                token: cookies.get("token")?,
-            })
-        })
+        let expiration = OffsetDateTime::from_unix_timestamp(expiration.timestamp_millis() / 1000)
+            .map_err(|_| ())?;
+
+        Self::set(Self::cookie("name", name.to_string(), expiration))?;
+        Self::set(Self::cookie("token", token.to_string(), expiration))?;
+        Ok(())
     }
 
-    fn new() -> Result<Self, ()> {
-        Ok(Self(
-            web_sys::window()
-                .ok_or(())?
-                .document()
-                .ok_or(())?
-                .dyn_into::<HtmlDocument>()
-                .map_err(|_| ())?,
-        ))
+    pub fn clear_credentials() -> Result<(), ()> {
+        Self::set(Self::cookie(
+            "name",
+            String::new(),
+            OffsetDateTime::UNIX_EPOCH,
+        ))?;
+        Self::set(Self::cookie(
+            "token",
+            String::new(),
+            OffsetDateTime::UNIX_EPOCH,
+        ))?;
+        Ok(())
     }
 
-    fn get(&self, key: &str) -> Result<String, ()> {
-        let cookies = self.0.cookie().map_err(|_| ())?;
+    pub fn credentials() -> Result<Credentials, ()> {
+        let request = use_context::<HttpRequest>().ok_or(())?;
 
-        let cookie = match cookies
-            .split(';')
-            .find(|cookie| cookie.trim_start().starts_with(&format!("{}=", key)))
-        {
-            Some(some) => some,
-            None => return Err(()),
-        };
+        Ok(Credentials {
+            name: request.cookie("name").ok_or(())?.value().to_string(),
+            token: request.cookie("token").ok_or(())?.value().to_string(),
+        })
+    }
 
-        match cookie.split_once('=') {
-            Some(value) => Ok(value.1.to_string()),
-            None => Err(()),
-        }
+    fn cookie(key: &'static str, value: String, expiration: OffsetDateTime) -> Cookie<'static> {
+        Cookie::build(key, value)
+            .expires(expiration)
+            .path("/")
+            .http_only(true)
+            .secure(true)
+            .same_site(SameSite::Lax)
+            .finish()
     }
 
-    fn set(&self, key: &str, value: &str, expiration: &DateTime) -> Result<(), ()> {
-        self.0
-            .set_cookie(&format!(
-                "{}={};expires={};path=/",
-                key,
-                value,
-                expiration.try_to_rfc3339_string().map_err(|_| ())?
-            ))
-            .map_err(|_| ())
+    fn set(cookie: Cookie<'static>) -> Result<(), ()> {
+        use_context::<ResponseOptions>().ok_or(())?.append_header(
+            SET_COOKIE,
+            HeaderValue::from_str(&cookie.encoded().to_string()).map_err(|_| ())?,
+        );
+        Ok(())
     }
 }
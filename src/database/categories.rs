@@ -0,0 +1,173 @@
+use bson::oid::ObjectId;
+use thiserror::Error;
+
+use crate::{model::Category, validation::is_valid_category_name};
+
+use super::{store::StoreError, Database};
+
+#[derive(Error, Debug)]
+pub enum GetCategoriesError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+}
+
+#[derive(Error, Debug)]
+pub enum GetCategoryError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+}
+
+#[derive(Error, Debug)]
+pub enum NewCategoryError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Only admins can manage categories")]
+    Forbidden,
+    #[error("Name already taken")]
+    NameAlreadyTaken,
+    #[error("Invalid name")]
+    InvalidName,
+}
+
+#[derive(Error, Debug)]
+pub enum RenameCategoryError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Only admins can manage categories")]
+    Forbidden,
+    #[error("Category not found")]
+    NotFound,
+    #[error("Name already taken")]
+    NameAlreadyTaken,
+    #[error("Invalid name")]
+    InvalidName,
+}
+
+#[derive(Error, Debug)]
+pub enum DeleteCategoryError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Only admins can manage categories")]
+    Forbidden,
+    #[error("Category not found")]
+    NotFound,
+}
+
+impl Database {
+    pub async fn get_categories(&self) -> Result<Vec<Category>, GetCategoriesError> {
+        Ok(self.store.find_categories().await?)
+    }
+
+    /// Get the category `id`, its recipes are listed by [`Database::get_recipes`]
+    pub async fn get_category(&self, id: ObjectId) -> Result<Option<Category>, GetCategoryError> {
+        Ok(self.store.find_category(id).await?)
+    }
+
+    pub async fn new_category(
+        &self,
+        user_name: String,
+        user_token: String,
+        name: String,
+    ) -> Result<(), NewCategoryError> {
+        let name = name.trim();
+        if !is_valid_category_name(name) {
+            return Err(NewCategoryError::InvalidName);
+        }
+
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(NewCategoryError::InvalidCredentials);
+        };
+
+        if !user.can_manage_categories() {
+            return Err(NewCategoryError::Forbidden);
+        }
+
+        let category = Category {
+            id: ObjectId::new(),
+            name: name.to_string(),
+        };
+
+        if !self.store.insert_category(category).await? {
+            return Err(NewCategoryError::NameAlreadyTaken);
+        }
+
+        Ok(())
+    }
+
+    pub async fn rename_category(
+        &self,
+        user_name: String,
+        user_token: String,
+        id: ObjectId,
+        name: String,
+    ) -> Result<(), RenameCategoryError> {
+        let name = name.trim();
+        if !is_valid_category_name(name) {
+            return Err(RenameCategoryError::InvalidName);
+        }
+
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(RenameCategoryError::InvalidCredentials);
+        };
+
+        if !user.can_manage_categories() {
+            return Err(RenameCategoryError::Forbidden);
+        }
+
+        if self.store.find_category(id).await?.is_none() {
+            return Err(RenameCategoryError::NotFound);
+        }
+
+        if !self.store.rename_category(id, name.to_string()).await? {
+            return Err(RenameCategoryError::NameAlreadyTaken);
+        }
+
+        Ok(())
+    }
+
+    pub async fn delete_category(
+        &self,
+        user_name: String,
+        user_token: String,
+        id: ObjectId,
+    ) -> Result<(), DeleteCategoryError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(DeleteCategoryError::InvalidCredentials);
+        };
+
+        if !user.can_manage_categories() {
+            return Err(DeleteCategoryError::Forbidden);
+        }
+
+        if self.store.find_category(id).await?.is_none() {
+            return Err(DeleteCategoryError::NotFound);
+        }
+
+        self.store.delete_category(id).await?;
+
+        Ok(())
+    }
+
+    /// Keep the ids of `categories` that belong to an existing category, without duplicates
+    pub(super) async fn existing_categories(
+        &self,
+        mut categories: Vec<ObjectId>,
+    ) -> Result<Vec<ObjectId>, StoreError> {
+        let existing = self.store.find_categories().await?;
+
+        let mut seen = Vec::new();
+        categories.retain(|id| {
+            let keep = !seen.contains(id) && existing.iter().any(|category| category.id == *id);
+            seen.push(*id);
+            keep
+        });
+
+        Ok(categories)
+    }
+}
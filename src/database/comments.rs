@@ -0,0 +1,242 @@
+use bson::{oid::ObjectId, DateTime};
+use thiserror::Error;
+
+use crate::{
+    model::Comment,
+    validation::{validate_comment, ValidationError},
+};
+
+use super::{
+    store::{StoreError, MAX_COMMENT_SLICE},
+    Database,
+};
+
+#[derive(Error, Debug)]
+pub enum GetCommentsError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+}
+
+#[derive(Error, Debug)]
+pub enum NewCommentError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Recipe not found")]
+    NotFound,
+    #[error("Banned users can't comment")]
+    Forbidden,
+    #[error("Invalid content, {0}")]
+    InvalidContent(ValidationError),
+}
+
+#[derive(Error, Debug)]
+pub enum UpdateCommentError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Comment not found")]
+    NotFound,
+    #[error("Only the author or an admin can edit this comment")]
+    Forbidden,
+    #[error("Invalid content, {0}")]
+    InvalidContent(ValidationError),
+}
+
+#[derive(Error, Debug)]
+pub enum DeleteCommentError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Comment not found")]
+    NotFound,
+    #[error("Only the author or an admin can delete this comment")]
+    Forbidden,
+}
+
+/// Page of the comment thread of a recipe
+pub struct CommentPage {
+    pub comments: Vec<CommentEntry>,
+    /// Index of this page, the last one if the requested page was after it
+    pub page: usize,
+    pub page_count: usize,
+}
+
+pub struct CommentEntry {
+    pub comment: Comment,
+    /// Name of the author, `None` if the author no longer exists
+    pub author: Option<String>,
+    /// Whether the viewer can edit and delete the comment
+    pub editable: bool,
+}
+
+impl Database {
+    /// Get the page number `page` of the comments of `recipe`, oldest comments first,
+    /// or the last page if `page` is `None`
+    ///
+    /// `viewer` is the name and token of the user reading the comments, if signed in
+    pub async fn get_comments(
+        &self,
+        viewer: Option<(&str, &str)>,
+        recipe: ObjectId,
+        page: Option<usize>,
+        page_size: usize,
+    ) -> Result<Option<CommentPage>, GetCommentsError> {
+        let page_size = page_size.clamp(1, MAX_COMMENT_SLICE);
+        let skip = page
+            .map_or(MAX_COMMENT_SLICE, |page| page.saturating_mul(page_size))
+            .min(MAX_COMMENT_SLICE);
+
+        let Some((mut comments, total)) = self.store.find_comments(recipe, skip, page_size).await?
+        else {
+            return Ok(None);
+        };
+
+        let page_count = total.div_ceil(page_size).max(1);
+        let page = page.map_or(page_count - 1, |page| page.min(page_count - 1));
+
+        // The requested page is after the last one
+        if comments.is_empty() && total > 0 {
+            let Some((last_comments, _)) = self
+                .store
+                .find_comments(recipe, page * page_size, page_size)
+                .await?
+            else {
+                return Ok(None);
+            };
+            comments = last_comments;
+        }
+
+        let viewer = match viewer {
+            Some((name, token)) => self.authenticate(name, token).await?,
+            None => None,
+        };
+
+        let comments = self
+            .with_author_names(comments, |comment| comment.user)
+            .await?
+            .into_iter()
+            .map(|(comment, author)| CommentEntry {
+                editable: viewer
+                    .as_ref()
+                    .is_some_and(|viewer| viewer.can_modify(comment.user)),
+                comment,
+                author,
+            })
+            .collect();
+
+        Ok(Some(CommentPage {
+            comments,
+            page,
+            page_count,
+        }))
+    }
+
+    pub async fn new_comment(
+        &self,
+        user_name: String,
+        user_token: String,
+        recipe: ObjectId,
+        content: String,
+    ) -> Result<(), NewCommentError> {
+        if let Some(err) = validate_comment(&content) {
+            return Err(NewCommentError::InvalidContent(err));
+        }
+
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(NewCommentError::InvalidCredentials);
+        };
+
+        if !user.can_contribute() {
+            return Err(NewCommentError::Forbidden);
+        }
+
+        if self.store.find_recipe(recipe).await?.is_none() {
+            return Err(NewCommentError::NotFound);
+        }
+
+        let comment = Comment {
+            id: ObjectId::new(),
+            content: content.trim().to_string(),
+            date: DateTime::now(),
+            user: user.id,
+        };
+
+        self.store.push_comment(recipe, comment).await?;
+
+        Ok(())
+    }
+
+    pub async fn update_comment(
+        &self,
+        user_name: String,
+        user_token: String,
+        recipe: ObjectId,
+        comment: ObjectId,
+        content: String,
+    ) -> Result<(), UpdateCommentError> {
+        if let Some(err) = validate_comment(&content) {
+            return Err(UpdateCommentError::InvalidContent(err));
+        }
+
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(UpdateCommentError::InvalidCredentials);
+        };
+
+        let Some(author) = self.comment_author(recipe, comment).await? else {
+            return Err(UpdateCommentError::NotFound);
+        };
+
+        if !user.can_modify(author) {
+            return Err(UpdateCommentError::Forbidden);
+        }
+
+        self.store
+            .set_comment_content(recipe, comment, content.trim().to_string())
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_comment(
+        &self,
+        user_name: String,
+        user_token: String,
+        recipe: ObjectId,
+        comment: ObjectId,
+    ) -> Result<(), DeleteCommentError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(DeleteCommentError::InvalidCredentials);
+        };
+
+        let Some(author) = self.comment_author(recipe, comment).await? else {
+            return Err(DeleteCommentError::NotFound);
+        };
+
+        if !user.can_modify(author) {
+            return Err(DeleteCommentError::Forbidden);
+        }
+
+        self.store.pull_comment(recipe, comment).await?;
+
+        Ok(())
+    }
+
+    /// Get the id of the author of the comment `comment` of `recipe`
+    async fn comment_author(
+        &self,
+        recipe: ObjectId,
+        comment: ObjectId,
+    ) -> Result<Option<ObjectId>, StoreError> {
+        Ok(self.store.find_recipe(recipe).await?.and_then(|recipe| {
+            recipe
+                .comment
+                .into_iter()
+                .find(|other| other.id == comment)
+                .map(|comment| comment.user)
+        }))
+    }
+}
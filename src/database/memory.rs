@@ -0,0 +1,476 @@
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use async_trait::async_trait;
+use bson::{oid::ObjectId, DateTime};
+
+use crate::{
+    date::Date,
+    model::{Category, Comment, MealPlan, Note, Recipe, RecipeSummary, ShoppingList, Token, User},
+    quantity::UnitSystem,
+};
+
+use super::{
+    recipes::{RecipeCriteria, RecipeCursor, RecipeOrder, RecipeUpdate},
+    store::{RecipeStore, StoreError, MAX_COMMENT_SLICE},
+};
+
+static SHARED: OnceLock<Arc<MemoryStore>> = OnceLock::new();
+
+/// Storage backend keeping the data in memory, mostly useful for tests
+#[derive(Default)]
+pub struct MemoryStore {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    users: Vec<User>,
+    recipes: Vec<Recipe>,
+    categories: Vec<Category>,
+    shopping_lists: Vec<ShoppingList>,
+    meal_plans: Vec<MealPlan>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the store shared by the whole program
+    pub fn shared() -> Arc<Self> {
+        Arc::clone(SHARED.get_or_init(|| Arc::new(Self::new())))
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // The state is always left consistent, even by a panicking thread
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl State {
+    fn user_mut(&mut self, id: ObjectId) -> Option<&mut User> {
+        self.users.iter_mut().find(|user| user.id == id)
+    }
+
+    fn recipe_mut(&mut self, id: ObjectId) -> Option<&mut Recipe> {
+        self.recipes.iter_mut().find(|recipe| recipe.id == id)
+    }
+}
+
+#[async_trait]
+impl RecipeStore for MemoryStore {
+    async fn setup(&self) -> Result<(), ()> {
+        Ok(())
+    }
+
+    async fn find_user(&self, name: &str) -> Result<Option<User>, StoreError> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .find(|user| user.name == name)
+            .cloned())
+    }
+
+    async fn find_users(&self, ids: &[ObjectId]) -> Result<Vec<User>, StoreError> {
+        Ok(self
+            .state()
+            .users
+            .iter()
+            .filter(|user| ids.contains(&user.id))
+            .cloned()
+            .collect())
+    }
+
+    async fn insert_user(&self, user: User) -> Result<bool, StoreError> {
+        let mut state = self.state();
+
+        // Same behavior as the unique index on the name of mongodb users
+        if state.users.iter().any(|other| other.name == user.name) {
+            return Ok(false);
+        }
+
+        state.users.push(user);
+        Ok(true)
+    }
+
+    async fn set_admin(&self, user: ObjectId, admin: bool) -> Result<(), StoreError> {
+        if let Some(user) = self.state().user_mut(user) {
+            user.admin = admin;
+        }
+        Ok(())
+    }
+
+    async fn set_banned(&self, user: ObjectId, banned: bool) -> Result<(), StoreError> {
+        if let Some(user) = self.state().user_mut(user) {
+            user.banned = banned;
+        }
+        Ok(())
+    }
+
+    async fn set_units(&self, user: ObjectId, units: Option<UnitSystem>) -> Result<(), StoreError> {
+        if let Some(user) = self.state().user_mut(user) {
+            user.units = units;
+        }
+        Ok(())
+    }
+
+    async fn push_token(&self, user: ObjectId, token: Token) -> Result<(), StoreError> {
+        if let Some(user) = self.state().user_mut(user) {
+            user.tokens.push(token);
+        }
+        Ok(())
+    }
+
+    async fn pull_token(&self, user: ObjectId, token: &str) -> Result<(), StoreError> {
+        if let Some(user) = self.state().user_mut(user) {
+            user.tokens.retain(|user_token| user_token.token != token);
+        }
+        Ok(())
+    }
+
+    async fn clear_tokens(&self, user: ObjectId) -> Result<(), StoreError> {
+        if let Some(user) = self.state().user_mut(user) {
+            user.tokens.clear();
+        }
+        Ok(())
+    }
+
+    async fn pull_expired_tokens(
+        &self,
+        user: Option<ObjectId>,
+        now: DateTime,
+    ) -> Result<(), StoreError> {
+        self.state()
+            .users
+            .iter_mut()
+            .filter(|candidate| user.is_none_or(|user| candidate.id == user))
+            .for_each(|user| user.tokens.retain(|token| token.expiration > now));
+        Ok(())
+    }
+
+    async fn find_recipes(
+        &self,
+        limit: usize,
+        order: RecipeOrder,
+        criteria: &RecipeCriteria,
+        after: Option<&RecipeCursor>,
+    ) -> Result<Vec<RecipeSummary>, StoreError> {
+        let mut recipes: Vec<_> = self
+            .state()
+            .recipes
+            .iter()
+            .filter(|recipe| criteria.matches(recipe))
+            .map(RecipeSummary::from)
+            .filter(|recipe| after.is_none_or(|after| after.precedes(recipe)))
+            .collect();
+
+        match order {
+            RecipeOrder::Insertion => recipes.sort_by_key(|recipe| recipe.id),
+            // Same order as mongodb, where a missing rating is lower than any rating
+            RecipeOrder::Rating => recipes.sort_by(|a, b| {
+                b.rating
+                    .partial_cmp(&a.rating)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(a.id.cmp(&b.id))
+            }),
+        }
+
+        recipes.truncate(limit);
+        Ok(recipes)
+    }
+
+    async fn search_recipes(
+        &self,
+        query: &str,
+        criteria: &RecipeCriteria,
+        skip: usize,
+        limit: usize,
+    ) -> Result<(Vec<RecipeSummary>, usize), StoreError> {
+        let query = words(query);
+
+        let state = self.state();
+        let mut recipes: Vec<_> = state
+            .recipes
+            .iter()
+            .filter(|recipe| criteria.matches(recipe))
+            .map(|recipe| (relevance(recipe, &query), recipe))
+            .filter(|(relevance, _)| *relevance > 0)
+            .collect();
+        recipes.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        let total = recipes.len();
+        let recipes = recipes
+            .into_iter()
+            .skip(skip)
+            .take(limit)
+            .map(|(_, recipe)| RecipeSummary::from(recipe))
+            .collect();
+
+        Ok((recipes, total))
+    }
+
+    async fn find_author_recipes(
+        &self,
+        author: ObjectId,
+    ) -> Result<Vec<RecipeSummary>, StoreError> {
+        Ok(self
+            .state()
+            .recipes
+            .iter()
+            .filter(|recipe| recipe.author == author)
+            .map(RecipeSummary::from)
+            .collect())
+    }
+
+    async fn find_recipe(&self, id: ObjectId) -> Result<Option<Recipe>, StoreError> {
+        Ok(self
+            .state()
+            .recipes
+            .iter()
+            .find(|recipe| recipe.id == id)
+            .cloned())
+    }
+
+    async fn insert_recipe(&self, recipe: Recipe) -> Result<(), StoreError> {
+        self.state().recipes.push(recipe);
+        Ok(())
+    }
+
+    async fn update_recipe(&self, id: ObjectId, update: RecipeUpdate) -> Result<(), StoreError> {
+        if let Some(recipe) = self.state().recipe_mut(id) {
+            recipe.name = update.name;
+            recipe.icon_url = update.icon_url;
+            recipe.price_level = update.price_level;
+            recipe.healthy_level = update.healthy_level;
+            recipe.servings = update.servings;
+            recipe.instructions = update.instructions;
+            recipe.ingredients = update.ingredients;
+            recipe.tools = update.tools;
+            recipe.categories = update.categories;
+        }
+        Ok(())
+    }
+
+    async fn delete_recipe(&self, id: ObjectId) -> Result<(), StoreError> {
+        self.state().recipes.retain(|recipe| recipe.id != id);
+        Ok(())
+    }
+
+    async fn set_note(&self, recipe: ObjectId, note: Note) -> Result<(), StoreError> {
+        if let Some(recipe) = self.state().recipe_mut(recipe) {
+            recipe.notes.retain(|other| other.user != note.user);
+            recipe.notes.push(note);
+        }
+        Ok(())
+    }
+
+    async fn find_comments(
+        &self,
+        recipe: ObjectId,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Option<(Vec<Comment>, usize)>, StoreError> {
+        // Same behavior as mongodb, which rejects slices not fitting in 32 bits
+        if skip > MAX_COMMENT_SLICE || limit > MAX_COMMENT_SLICE {
+            return Err(StoreError::new("Comment slice out of range"));
+        }
+
+        Ok(self.state().recipe_mut(recipe).map(|recipe| {
+            let comments = recipe.comment.iter().skip(skip).take(limit).cloned();
+            (comments.collect(), recipe.comment.len())
+        }))
+    }
+
+    async fn push_comment(&self, recipe: ObjectId, comment: Comment) -> Result<(), StoreError> {
+        if let Some(recipe) = self.state().recipe_mut(recipe) {
+            recipe.comment.push(comment);
+        }
+        Ok(())
+    }
+
+    async fn set_comment_content(
+        &self,
+        recipe: ObjectId,
+        comment: ObjectId,
+        content: String,
+    ) -> Result<(), StoreError> {
+        if let Some(recipe) = self.state().recipe_mut(recipe) {
+            if let Some(comment) = recipe.comment.iter_mut().find(|other| other.id == comment) {
+                comment.content = content;
+            }
+        }
+        Ok(())
+    }
+
+    async fn pull_comment(&self, recipe: ObjectId, comment: ObjectId) -> Result<(), StoreError> {
+        if let Some(recipe) = self.state().recipe_mut(recipe) {
+            recipe.comment.retain(|other| other.id != comment);
+        }
+        Ok(())
+    }
+
+    async fn find_categories(&self) -> Result<Vec<Category>, StoreError> {
+        let mut categories = self.state().categories.clone();
+        categories.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(categories)
+    }
+
+    async fn find_category(&self, id: ObjectId) -> Result<Option<Category>, StoreError> {
+        Ok(self
+            .state()
+            .categories
+            .iter()
+            .find(|category| category.id == id)
+            .cloned())
+    }
+
+    async fn insert_category(&self, category: Category) -> Result<bool, StoreError> {
+        let mut state = self.state();
+
+        // Same behavior as the unique index on the name of mongodb categories
+        if state
+            .categories
+            .iter()
+            .any(|other| other.name == category.name)
+        {
+            return Ok(false);
+        }
+
+        state.categories.push(category);
+        Ok(true)
+    }
+
+    async fn rename_category(&self, id: ObjectId, name: String) -> Result<bool, StoreError> {
+        let mut state = self.state();
+
+        if state
+            .categories
+            .iter()
+            .any(|other| other.id != id && other.name == name)
+        {
+            return Ok(false);
+        }
+
+        if let Some(category) = state.categories.iter_mut().find(|other| other.id == id) {
+            category.name = name;
+        }
+        Ok(true)
+    }
+
+    async fn delete_category(&self, id: ObjectId) -> Result<(), StoreError> {
+        let mut state = self.state();
+
+        state.categories.retain(|category| category.id != id);
+        for recipe in &mut state.recipes {
+            recipe.categories.retain(|category| *category != id);
+        }
+        Ok(())
+    }
+
+    async fn find_shopping_list(&self, user: ObjectId) -> Result<Option<ShoppingList>, StoreError> {
+        Ok(self
+            .state()
+            .shopping_lists
+            .iter()
+            .find(|list| list.user == user)
+            .cloned())
+    }
+
+    async fn set_shopping_list(&self, list: ShoppingList) -> Result<bool, StoreError> {
+        let mut state = self.state();
+
+        let stored = state
+            .shopping_lists
+            .iter()
+            .position(|other| other.user == list.user);
+        let stored_version = stored.map_or(0, |index| state.shopping_lists[index].version);
+        if stored_version != list.version {
+            return Ok(false);
+        }
+
+        let list = ShoppingList {
+            version: list.version + 1,
+            ..list
+        };
+        match stored {
+            Some(index) => state.shopping_lists[index] = list,
+            None => state.shopping_lists.push(list),
+        }
+        Ok(true)
+    }
+
+    async fn find_meal_plans(
+        &self,
+        user: ObjectId,
+        from: Date,
+        to: Date,
+    ) -> Result<Vec<MealPlan>, StoreError> {
+        let mut plans: Vec<_> = self
+            .state()
+            .meal_plans
+            .iter()
+            .filter(|plan| plan.user == user && (from..to).contains(&plan.date))
+            .cloned()
+            .collect();
+        plans.sort_by_key(|plan| plan.date);
+        Ok(plans)
+    }
+
+    async fn set_meal_plan(&self, plan: MealPlan) -> Result<bool, StoreError> {
+        let mut state = self.state();
+
+        let stored = state
+            .meal_plans
+            .iter()
+            .position(|other| other.user == plan.user && other.date == plan.date);
+        let stored_version = stored.map_or(0, |index| state.meal_plans[index].version);
+        if stored_version != plan.version {
+            return Ok(false);
+        }
+
+        let plan = MealPlan {
+            version: plan.version + 1,
+            ..plan
+        };
+        match stored {
+            Some(index) if plan.meals.is_empty() => {
+                state.meal_plans.remove(index);
+            }
+            Some(index) => state.meal_plans[index] = plan,
+            None if plan.meals.is_empty() => {}
+            None => state.meal_plans.push(plan),
+        }
+        Ok(true)
+    }
+}
+
+/// Lowercase words of `text`
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Rough equivalent of the mongodb text score, with the same weights as the text index
+fn relevance(recipe: &Recipe, query: &[String]) -> usize {
+    let matches = |text: &str| {
+        words(text)
+            .iter()
+            .filter(|word| query.contains(word))
+            .count()
+    };
+
+    10 * matches(&recipe.name)
+        + 5 * recipe
+            .ingredients
+            .iter()
+            .map(|ingredient| matches(&ingredient.name))
+            .sum::<usize>()
+        + recipe
+            .instructions
+            .iter()
+            .map(|instruction| matches(instruction))
+            .sum::<usize>()
+}
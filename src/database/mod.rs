@@ -1,93 +1,95 @@
 #![cfg(feature = "server")]
 
+pub mod categories;
+pub mod comments;
+pub mod memory;
+pub mod mongo;
+mod permissions;
+pub mod planner;
 pub mod recipes;
+pub mod shopping;
 pub mod signin;
+pub mod signout;
 pub mod signup;
+pub mod store;
+mod tokens;
+pub mod users;
 
-use std::cell::RefCell;
+use std::sync::Arc;
 
 use base64::{engine::general_purpose::STANDARD, Engine};
-use leptos::logging::error;
-use mongodb::{
-    bson::{doc, DateTime},
-    options::IndexOptions,
-    Client, Collection, Database as MongoDatabase, IndexModel,
-};
+use leptos::use_context;
+use mongodb::bson::DateTime;
 use rand::{thread_rng, Rng};
 
-use crate::model::{Token, User};
+use crate::{
+    config::{Config, Storage},
+    model::Token,
+};
 
-thread_local! {
-    pub static CLIENT: RefCell<Option<Client>> = RefCell::new(None);
-}
+use self::{
+    memory::MemoryStore,
+    mongo::MongoStore,
+    store::{RecipeStore, StoreError},
+    tokens::hash_token,
+};
 
-/// Wrapper around mongodb's database implementing
+/// Wrapper around the storage backend implementing
 /// all the function of the project database
+#[derive(Clone)]
 pub struct Database {
-    database: MongoDatabase,
+    store: Arc<dyn RecipeStore>,
 }
 impl Database {
-    const URI: &'static str = "mongodb://localhost:27017";
-    const NAME: &'static str = "recipe_final_exercise";
-
-    /// Get the thread local database or create one
+    /// Get the database provided in the leptos context,
+    /// or the one of the storage backend selected in the config
     pub async fn new() -> Result<Self, ()> {
-        let client = match CLIENT.with_borrow(|client| client.clone()) {
-            Some(some) => some,
-            None => {
-                let new_client = match Client::with_uri_str(Self::URI).await {
-                    Ok(ok) => ok,
-                    Err(err) => {
-                        error!("Failed to create mongodb client {:?}", err);
-                        return Err(());
-                    }
-                };
-                CLIENT.with_borrow_mut(|client| *client = Some(new_client.clone()));
-                new_client
-            }
+        match use_context::<Database>() {
+            Some(database) => Ok(database),
+            None => Self::configured().await,
+        }
+    }
+
+    /// Get the database of the storage backend selected in the config
+    pub async fn configured() -> Result<Self, ()> {
+        let store: Arc<dyn RecipeStore> = match Config::get().storage {
+            Storage::MongoDb => Arc::new(MongoStore::new().await?),
+            Storage::Memory => MemoryStore::shared(),
         };
 
-        Ok(Self {
-            database: client.database(Self::NAME),
-        })
+        Ok(Self::with_store(store))
+    }
+
+    /// Database on top of `store`,
+    /// provide it in the leptos context to have server functions use it
+    pub fn with_store(store: Arc<dyn RecipeStore>) -> Self {
+        Self { store }
     }
 
     /// Setup the database
     /// Should be called once at start of the program
-    /// Setup mongodb indices
     pub async fn setup() -> Result<(), ()> {
-        let Ok(database) = Self::new().await else {
-            return Err(());
-        };
-
-        database
-            .users()
-            .create_index(
-                IndexModel::builder()
-                    .keys(doc! { "name": 1 })
-                    .options(Some(IndexOptions::builder().unique(true).build()))
-                    .build(),
-                None,
-            )
-            .await
-            .map(|_| ())
-            .map_err(|err| {
-                error!("Failed to create mongodb unique indices, {:?}", err);
-            })
+        Self::configured().await?.store.setup().await
     }
+}
 
-    /// Get the user collection
-    fn users(&self) -> Collection<User> {
-        self.database.collection("users")
-    }
+/// Number of times a change is computed again from the stored data
+/// when the data was changed concurrently, before giving up
+const MAX_CHANGE_ATTEMPTS: usize = 10;
+
+/// Failure to apply a change after [`MAX_CHANGE_ATTEMPTS`] concurrent changes
+fn too_many_concurrent_changes() -> StoreError {
+    StoreError::new("Too many concurrent changes")
 }
 
 /// Generate a random 32 bytes token for use as authentification token
+/// Only its hash must be stored, see [`hash_token`]
 fn generate_token() -> Token {
     Token {
         token: STANDARD.encode(thread_rng().gen::<[u8; 32]>()),
         expiration: DateTime::from_millis(
-            DateTime::now().timestamp_millis() + 1000 * 60 * 60 * 24 * 30,
+            DateTime::now().timestamp_millis()
+                + i64::try_from(Config::get().token_lifetime.as_millis()).unwrap_or(i64::MAX),
         ),
     }
 }
@@ -0,0 +1,246 @@
+use bson::{doc, oid::ObjectId, to_bson, Bson, DateTime, Document};
+use futures::TryStreamExt;
+use leptos::logging::log;
+use mongodb::{error::Error as MongoError, options::ReplaceOptions, Collection};
+use serde::Deserialize;
+
+use crate::{
+    model::{Migration, Token, User},
+    quantity::Quantity,
+};
+
+use crate::database::hash_token;
+
+use super::MongoStore;
+
+/// User document as stored before recipes had their own collection
+#[derive(Deserialize)]
+struct EmbeddedRecipes {
+    #[serde(rename = "_id")]
+    id: ObjectId,
+    recipes: Vec<Document>,
+}
+
+impl MongoStore {
+    /// Run every migration that wasn't already applied on this database
+    pub(super) async fn migrate(&self) -> Result<(), MongoError> {
+        if !self.is_migrated("embedded_recipes").await? {
+            self.migrate_embedded_recipes().await?;
+            self.set_migrated("embedded_recipes").await?;
+        }
+
+        if !self.is_migrated("hashed_tokens").await? {
+            self.migrate_hashed_tokens().await?;
+            self.set_migrated("hashed_tokens").await?;
+        }
+
+        if !self.is_migrated("structured_quantities").await? {
+            self.migrate_structured_quantities().await?;
+            self.set_migrated("structured_quantities").await?;
+        }
+
+        if !self.is_migrated("versioned_shopping_lists").await? {
+            self.migrate_versioned("shopping_lists").await?;
+            self.set_migrated("versioned_shopping_lists").await?;
+        }
+
+        if !self.is_migrated("versioned_meal_plans").await? {
+            self.migrate_versioned("meal_plans").await?;
+            self.set_migrated("versioned_meal_plans").await?;
+        }
+
+        Ok(())
+    }
+
+    /// Move the recipes embedded in `User.recipes` to the recipes collection
+    ///
+    /// Every step can be rerun safely, so an interrupted migration
+    /// neither loses nor duplicates recipes
+    async fn migrate_embedded_recipes(&self) -> Result<(), MongoError> {
+        let users = self.database.collection::<EmbeddedRecipes>("users");
+        let recipes = self.database.collection::<Document>("recipes");
+
+        let mut cursor = users
+            .find(doc! { "recipes": { "$exists": true } }, None)
+            .await?;
+
+        while let Some(user) = cursor.try_next().await? {
+            let with_ids = user
+                .recipes
+                .into_iter()
+                .map(|mut recipe| {
+                    if !recipe.contains_key("_id") {
+                        recipe.insert("_id", ObjectId::new());
+                    }
+                    recipe.insert("author", user.id);
+                    recipe
+                })
+                .collect::<Vec<_>>();
+
+            // Persist the ids first so a rerun upserts the same documents
+            users
+                .update_one(
+                    doc! { "_id": user.id },
+                    doc! { "$set": { "recipes": with_ids.clone() } },
+                    None,
+                )
+                .await?;
+
+            for recipe in &with_ids {
+                recipes
+                    .replace_one(
+                        doc! { "_id": recipe.get("_id").cloned() },
+                        recipe,
+                        ReplaceOptions::builder().upsert(true).build(),
+                    )
+                    .await?;
+            }
+
+            users
+                .update_one(
+                    doc! { "_id": user.id },
+                    doc! { "$unset": { "recipes": "" } },
+                    None,
+                )
+                .await?;
+
+            log!(
+                "Migrated {} embedded recipes of user {}",
+                with_ids.len(),
+                user.id
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Replace the plain tokens stored before tokens were hashed by their hash
+    ///
+    /// Existing sessions stay valid since clients still hold the plain tokens
+    ///
+    /// Migrated users are marked with `tokens_hashed` in the same update as their tokens,
+    /// so a rerun after an interruption doesn't hash their tokens twice
+    async fn migrate_hashed_tokens(&self) -> Result<(), MongoError> {
+        let users = self.database.collection::<User>("users");
+
+        let mut cursor = users
+            .find(
+                doc! { "tokens.0": { "$exists": true }, "tokens_hashed": { "$ne": true } },
+                None,
+            )
+            .await?;
+
+        while let Some(user) = cursor.try_next().await? {
+            let tokens = user
+                .tokens
+                .iter()
+                .map(|token| Token {
+                    token: hash_token(&token.token),
+                    expiration: token.expiration,
+                })
+                .collect::<Vec<_>>();
+
+            // Filtering on the plain tokens prevents hashing twice tokens
+            // modified concurrently by a signin or a signout
+            users
+                .update_one(
+                    doc! {
+                        "_id": user.id,
+                        "tokens": to_bson(&user.tokens).unwrap(),
+                        "tokens_hashed": { "$ne": true },
+                    },
+                    doc! { "$set": { "tokens": to_bson(&tokens).unwrap(), "tokens_hashed": true } },
+                    None,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Parse the free text quantities of the ingredients stored before quantities were structured,
+    /// the ones that can't be parsed are kept as [`Quantity::Raw`]
+    ///
+    /// Only text quantities are parsed, so a rerun skips the migrated recipes
+    async fn migrate_structured_quantities(&self) -> Result<(), MongoError> {
+        let recipes = self.database.collection::<Document>("recipes");
+
+        let mut cursor = recipes
+            .find(doc! { "ingredients.quantity": { "$type": "string" } }, None)
+            .await?;
+
+        while let Some(recipe) = cursor.try_next().await? {
+            let Ok(ingredients) = recipe.get_array("ingredients") else {
+                continue;
+            };
+
+            let ingredients = ingredients
+                .iter()
+                .cloned()
+                .map(|mut ingredient| {
+                    if let Bson::Document(ingredient) = &mut ingredient {
+                        if let Ok(text) = ingredient.get_str("quantity") {
+                            let quantity = to_bson(&Quantity::parse(text)).unwrap();
+                            ingredient.insert("quantity", quantity);
+                        }
+                    }
+                    ingredient
+                })
+                .collect::<Vec<_>>();
+
+            // Filtering on the old ingredients keeps edits made concurrently
+            recipes
+                .update_one(
+                    doc! { "_id": recipe.get("_id").cloned(), "ingredients": recipe.get("ingredients").cloned() },
+                    doc! { "$set": { "ingredients": ingredients } },
+                    None,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Set the version of the documents of `collection` stored before they had one,
+    /// they count as stored once so that they aren't inserted again
+    ///
+    /// Only documents without version are updated, so a rerun changes nothing
+    async fn migrate_versioned(&self, collection: &str) -> Result<(), MongoError> {
+        self.database
+            .collection::<Document>(collection)
+            .update_many(
+                doc! { "version": { "$exists": false } },
+                doc! { "$set": { "version": 1_i64 } },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn is_migrated(&self, name: &str) -> Result<bool, MongoError> {
+        Ok(self
+            .migrations()
+            .find_one(doc! { "name": name }, None)
+            .await?
+            .is_some())
+    }
+
+    async fn set_migrated(&self, name: &str) -> Result<(), MongoError> {
+        self.migrations()
+            .insert_one(
+                Migration {
+                    name: name.to_string(),
+                    date: DateTime::now(),
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    fn migrations(&self) -> Collection<Migration> {
+        self.database.collection("migrations")
+    }
+}
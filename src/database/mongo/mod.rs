@@ -0,0 +1,741 @@
+mod migrations;
+
+use std::cell::RefCell;
+
+use async_trait::async_trait;
+use bson::{doc, oid::ObjectId, to_bson, Bson, DateTime, Document, Regex};
+use futures::TryStreamExt;
+use leptos::logging::error;
+use mongodb::{
+    error::{Error as MongoError, ErrorKind, WriteError, WriteFailure},
+    options::{ClientOptions, FindOptions, IndexOptions},
+    Client, Collection, Database as MongoDatabase, IndexModel,
+};
+use serde::Deserialize;
+
+use crate::{
+    config::Config,
+    date::Date,
+    model::{Category, Comment, MealPlan, Note, Recipe, RecipeSummary, ShoppingList, Token, User},
+    quantity::UnitSystem,
+};
+
+use super::{
+    recipes::{RecipeCriteria, RecipeCursor, RecipeOrder, RecipeUpdate},
+    store::{RecipeStore, StoreError},
+};
+
+thread_local! {
+    pub static CLIENT: RefCell<Option<Client>> = RefCell::new(None);
+}
+
+/// Storage backend keeping the data in mongodb
+pub struct MongoStore {
+    database: MongoDatabase,
+}
+impl MongoStore {
+    /// Get the thread local database or create one
+    pub async fn new() -> Result<Self, ()> {
+        Self::with_database(&Config::get().database_name).await
+    }
+
+    /// Store on the database `name` of the configured server instead of the configured database,
+    /// to keep the data of the tests apart
+    pub async fn with_database(name: &str) -> Result<Self, ()> {
+        let config = Config::get();
+
+        let client = match CLIENT.with_borrow(|client| client.clone()) {
+            Some(some) => some,
+            None => {
+                let mut options = match ClientOptions::parse(&config.mongodb_uri).await {
+                    Ok(ok) => ok,
+                    Err(err) => {
+                        error!("Failed to parse mongodb uri {:?}", err);
+                        return Err(());
+                    }
+                };
+                options.min_pool_size = config.min_pool_size;
+                options.max_pool_size = config.max_pool_size;
+
+                let new_client = match Client::with_options(options) {
+                    Ok(ok) => ok,
+                    Err(err) => {
+                        error!("Failed to create mongodb client {:?}", err);
+                        return Err(());
+                    }
+                };
+                CLIENT.with_borrow_mut(|client| *client = Some(new_client.clone()));
+                new_client
+            }
+        };
+
+        Ok(Self {
+            database: client.database(name),
+        })
+    }
+
+    /// Get the user collection
+    fn users(&self) -> Collection<User> {
+        self.database.collection("users")
+    }
+
+    /// Get the recipe collection
+    fn recipes(&self) -> Collection<Recipe> {
+        self.database.collection("recipes")
+    }
+
+    /// Get the category collection
+    fn categories(&self) -> Collection<Category> {
+        self.database.collection("categories")
+    }
+
+    /// Get the shopping list collection, lists have the id of their user
+    fn shopping_lists(&self) -> Collection<ShoppingList> {
+        self.database.collection("shopping_lists")
+    }
+
+    /// Get the meal plan collection, with one plan per user and date
+    fn meal_plans(&self) -> Collection<MealPlan> {
+        self.database.collection("meal_plans")
+    }
+}
+
+/// Mongodb filter selecting the recipes meeting `criteria`, see [`RecipeCriteria::matches`]
+fn criteria_filter(criteria: &RecipeCriteria) -> Document {
+    let mut conditions = Vec::new();
+
+    let levels = [
+        ("price_level", "$gte", criteria.min_price_level),
+        ("price_level", "$lte", criteria.max_price_level),
+        ("healthy_level", "$gte", criteria.min_healthy_level),
+        ("healthy_level", "$lte", criteria.max_healthy_level),
+    ];
+    for (field, operator, level) in levels {
+        if let Some(level) = level {
+            conditions.push(doc! {field: {operator: i32::from(level)}});
+        }
+    }
+
+    for ingredient in &criteria.ingredients {
+        conditions.push(doc! {"ingredients.name": exact_name(ingredient)});
+    }
+
+    if !criteria.excluded_ingredients.is_empty() {
+        let excluded: Vec<_> = criteria
+            .excluded_ingredients
+            .iter()
+            .map(|name| exact_name(name))
+            .collect();
+        conditions.push(doc! {"ingredients.name": {"$nin": excluded}});
+    }
+
+    if let Some(tools) = &criteria.tools {
+        let tools: Vec<_> = tools.iter().map(|name| exact_name(name)).collect();
+        conditions.push(doc! {"tools": {"$not": {"$elemMatch": {"name": {"$nin": tools}}}}});
+    }
+
+    if !criteria.categories.is_empty() {
+        conditions.push(doc! {"categories": {"$in": &criteria.categories}});
+    }
+
+    if conditions.is_empty() {
+        Document::new()
+    } else {
+        doc! {"$and": conditions}
+    }
+}
+
+/// Mongodb filter selecting the recipes listed after `cursor`, see [`RecipeCursor::precedes`]
+///
+/// The `rating` field must have been computed beforehand
+fn cursor_filter(cursor: &RecipeCursor) -> Document {
+    match *cursor {
+        RecipeCursor::Insertion { id } => doc! {"_id": {"$gt": id}},
+        // A missing rating is lower than any rating
+        RecipeCursor::Rating {
+            rating: Some(rating),
+            id,
+        } => doc! {"$or": [
+            {"rating": {"$lt": rating}},
+            {"rating": null},
+            {"rating": rating, "_id": {"$gt": id}},
+        ]},
+        RecipeCursor::Rating { rating: None, id } => doc! {"rating": null, "_id": {"$gt": id}},
+    }
+}
+
+/// Projection keeping only the fields of a [`RecipeSummary`],
+/// the comments of the recipes can be big
+///
+/// The `rating` field must have been computed beforehand
+fn summary_projection() -> Document {
+    doc! {"$project": {
+        "author": 1,
+        "name": 1,
+        "icon_url": 1,
+        "rating": 1,
+        "rating_count": {"$size": "$notes"},
+    }}
+}
+
+/// Regex matching exactly `name`, ignoring case
+fn exact_name(name: &str) -> Bson {
+    let mut pattern = String::from("^");
+    for c in name.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('$');
+
+    Bson::RegularExpression(Regex {
+        pattern,
+        options: "i".to_string(),
+    })
+}
+
+impl From<MongoError> for StoreError {
+    fn from(err: MongoError) -> Self {
+        Self::new(err)
+    }
+}
+
+/// Whether `err` is caused by a violation of a unique index
+pub fn is_duplicate_key(err: &MongoError) -> bool {
+    const DUPLICATE_KEY: i32 = 11000;
+
+    matches!(
+        err.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(WriteError {
+            code: DUPLICATE_KEY,
+            ..
+        }))
+    )
+}
+
+#[async_trait]
+impl RecipeStore for MongoStore {
+    /// Setup mongodb indices and run pending migrations
+    async fn setup(&self) -> Result<(), ()> {
+        self.users()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "name": 1 })
+                    .options(Some(IndexOptions::builder().unique(true).build()))
+                    .build(),
+                None,
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to create mongodb unique indices, {:?}", err);
+            })?;
+
+        self.recipes()
+            .create_indexes(
+                [
+                    IndexModel::builder().keys(doc! { "author": 1 }).build(),
+                    IndexModel::builder().keys(doc! { "categories": 1 }).build(),
+                    IndexModel::builder()
+                        .keys(doc! {
+                            "name": "text",
+                            "ingredients.name": "text",
+                            "instructions": "text",
+                        })
+                        .options(Some(
+                            IndexOptions::builder()
+                                .name("recipe_text".to_string())
+                                .weights(doc! {
+                                    "name": 10,
+                                    "ingredients.name": 5,
+                                    "instructions": 1,
+                                })
+                                .build(),
+                        ))
+                        .build(),
+                ],
+                None,
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to create mongodb recipe indices, {:?}", err);
+            })?;
+
+        self.categories()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "name": 1 })
+                    .options(Some(IndexOptions::builder().unique(true).build()))
+                    .build(),
+                None,
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to create mongodb category indices, {:?}", err);
+            })?;
+
+        self.meal_plans()
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "user": 1, "date": 1 })
+                    .options(Some(IndexOptions::builder().unique(true).build()))
+                    .build(),
+                None,
+            )
+            .await
+            .map_err(|err| {
+                error!("Failed to create mongodb meal plan indices, {:?}", err);
+            })?;
+
+        self.migrate().await.map_err(|err| {
+            error!("Failed to migrate the database, {:?}", err);
+        })
+    }
+
+    async fn find_user(&self, name: &str) -> Result<Option<User>, StoreError> {
+        Ok(self.users().find_one(doc! {"name": name}, None).await?)
+    }
+
+    async fn find_users(&self, ids: &[ObjectId]) -> Result<Vec<User>, StoreError> {
+        Ok(self
+            .users()
+            .find(doc! {"_id": {"$in": ids}}, None)
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    async fn insert_user(&self, user: User) -> Result<bool, StoreError> {
+        match self.users().insert_one(user, None).await {
+            Ok(_) => Ok(true),
+            Err(err) if is_duplicate_key(&err) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn set_admin(&self, user: ObjectId, admin: bool) -> Result<(), StoreError> {
+        self.users()
+            .update_one(doc! {"_id": user}, doc! {"$set": {"admin": admin}}, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_banned(&self, user: ObjectId, banned: bool) -> Result<(), StoreError> {
+        self.users()
+            .update_one(doc! {"_id": user}, doc! {"$set": {"banned": banned}}, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_units(&self, user: ObjectId, units: Option<UnitSystem>) -> Result<(), StoreError> {
+        self.users()
+            .update_one(
+                doc! {"_id": user},
+                doc! {"$set": {"units": to_bson(&units).unwrap()}},
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn push_token(&self, user: ObjectId, token: Token) -> Result<(), StoreError> {
+        self.users()
+            .update_one(
+                doc! {"_id": user},
+                doc! {"$push": { "tokens": to_bson(&token).unwrap() }},
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn pull_token(&self, user: ObjectId, token: &str) -> Result<(), StoreError> {
+        self.users()
+            .update_one(
+                doc! {"_id": user},
+                doc! {"$pull": {"tokens": {"token": token}}},
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn clear_tokens(&self, user: ObjectId) -> Result<(), StoreError> {
+        self.users()
+            .update_one(doc! {"_id": user}, doc! {"$set": {"tokens": []}}, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn pull_expired_tokens(
+        &self,
+        user: Option<ObjectId>,
+        now: DateTime,
+    ) -> Result<(), StoreError> {
+        let mut filter = doc! {"tokens.expiration": {"$lte": now}};
+        if let Some(user) = user {
+            filter.insert("_id", user);
+        }
+
+        self.users()
+            .update_many(
+                filter,
+                doc! {"$pull": {"tokens": {"expiration": {"$lte": now}}}},
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn find_recipes(
+        &self,
+        limit: usize,
+        order: RecipeOrder,
+        criteria: &RecipeCriteria,
+        after: Option<&RecipeCursor>,
+    ) -> Result<Vec<RecipeSummary>, StoreError> {
+        let mut pipeline = vec![
+            doc! {"$match": criteria_filter(criteria)},
+            doc! {"$addFields": {"rating": {"$avg": "$notes.note"}}},
+        ];
+
+        if let Some(after) = after {
+            pipeline.push(doc! {"$match": cursor_filter(after)});
+        }
+
+        pipeline.extend([
+            match order {
+                RecipeOrder::Insertion => doc! {"$sort": {"_id": 1}},
+                RecipeOrder::Rating => doc! {"$sort": {"rating": -1, "_id": 1}},
+            },
+            doc! {"$limit": i64::try_from(limit).unwrap_or(i64::MAX)},
+            summary_projection(),
+        ]);
+
+        let recipes = self.recipes().aggregate(pipeline, None).await?;
+
+        Ok(recipes
+            .and_then(|recipe| async { Ok(bson::from_document(recipe)?) })
+            .try_collect()
+            .await?)
+    }
+
+    async fn search_recipes(
+        &self,
+        query: &str,
+        criteria: &RecipeCriteria,
+        skip: usize,
+        limit: usize,
+    ) -> Result<(Vec<RecipeSummary>, usize), StoreError> {
+        let mut filter = criteria_filter(criteria);
+        filter.insert("$text", doc! {"$search": query});
+
+        let total = self.recipes().count_documents(filter.clone(), None).await?;
+
+        let pipeline = [
+            doc! {"$match": filter},
+            doc! {"$sort": {"score": {"$meta": "textScore"}, "_id": 1}},
+            doc! {"$skip": i64::try_from(skip).unwrap_or(i64::MAX)},
+            doc! {"$limit": i64::try_from(limit).unwrap_or(i64::MAX)},
+            doc! {"$addFields": {"rating": {"$avg": "$notes.note"}}},
+            summary_projection(),
+        ];
+
+        let recipes = self.recipes().aggregate(pipeline, None).await?;
+
+        Ok((
+            recipes
+                .and_then(|recipe| async { Ok(bson::from_document(recipe)?) })
+                .try_collect()
+                .await?,
+            usize::try_from(total).unwrap_or(usize::MAX),
+        ))
+    }
+
+    async fn find_author_recipes(
+        &self,
+        author: ObjectId,
+    ) -> Result<Vec<RecipeSummary>, StoreError> {
+        let pipeline = [
+            doc! {"$match": {"author": author}},
+            doc! {"$sort": {"_id": 1}},
+            doc! {"$addFields": {"rating": {"$avg": "$notes.note"}}},
+            summary_projection(),
+        ];
+
+        let recipes = self.recipes().aggregate(pipeline, None).await?;
+
+        Ok(recipes
+            .and_then(|recipe| async { Ok(bson::from_document(recipe)?) })
+            .try_collect()
+            .await?)
+    }
+
+    async fn find_recipe(&self, id: ObjectId) -> Result<Option<Recipe>, StoreError> {
+        Ok(self.recipes().find_one(doc! { "_id": id }, None).await?)
+    }
+
+    async fn insert_recipe(&self, recipe: Recipe) -> Result<(), StoreError> {
+        self.recipes().insert_one(recipe, None).await?;
+        Ok(())
+    }
+
+    async fn update_recipe(&self, id: ObjectId, update: RecipeUpdate) -> Result<(), StoreError> {
+        self.recipes()
+            .update_one(
+                doc! {"_id": id},
+                doc! {"$set": {
+                    "name": update.name,
+                    "icon_url": update.icon_url,
+                    "price_level": i32::from(update.price_level),
+                    "healthy_level": i32::from(update.healthy_level),
+                    "servings": i32::from(update.servings),
+                    "instructions": update.instructions,
+                    "ingredients": to_bson(&update.ingredients).unwrap(),
+                    "tools": to_bson(&update.tools).unwrap(),
+                    "categories": update.categories,
+                }},
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_recipe(&self, id: ObjectId) -> Result<(), StoreError> {
+        self.recipes().delete_one(doc! {"_id": id}, None).await?;
+        Ok(())
+    }
+
+    async fn set_note(&self, recipe: ObjectId, note: Note) -> Result<(), StoreError> {
+        let replaced = self
+            .recipes()
+            .update_one(
+                doc! {"_id": recipe, "notes.user": note.user},
+                doc! {"$set": {"notes.$.note": i32::from(note.note)}},
+                None,
+            )
+            .await?;
+
+        if replaced.matched_count == 0 {
+            // The filter on the user prevents a concurrent rating from adding a second note
+            self.recipes()
+                .update_one(
+                    doc! {"_id": recipe, "notes.user": {"$ne": note.user}},
+                    doc! {"$push": {"notes": to_bson(&note).unwrap()}},
+                    None,
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn find_comments(
+        &self,
+        recipe: ObjectId,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Option<(Vec<Comment>, usize)>, StoreError> {
+        #[derive(Deserialize)]
+        struct CommentSlice {
+            comment: Vec<Comment>,
+            total: usize,
+        }
+
+        let mut slices = self
+            .recipes()
+            .aggregate(
+                [
+                    doc! {"$match": {"_id": recipe}},
+                    doc! {"$project": {
+                        "total": {"$size": "$comment"},
+                        "comment": {"$slice": [
+                            "$comment",
+                            i32::try_from(skip).map_err(StoreError::new)?,
+                            i32::try_from(limit).map_err(StoreError::new)?,
+                        ]},
+                    }},
+                ],
+                None,
+            )
+            .await?;
+
+        let Some(slice) = slices.try_next().await? else {
+            return Ok(None);
+        };
+
+        let slice: CommentSlice = bson::from_document(slice).map_err(StoreError::new)?;
+        Ok(Some((slice.comment, slice.total)))
+    }
+
+    async fn push_comment(&self, recipe: ObjectId, comment: Comment) -> Result<(), StoreError> {
+        self.recipes()
+            .update_one(
+                doc! {"_id": recipe},
+                doc! {"$push": {"comment": to_bson(&comment).unwrap()}},
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn set_comment_content(
+        &self,
+        recipe: ObjectId,
+        comment: ObjectId,
+        content: String,
+    ) -> Result<(), StoreError> {
+        self.recipes()
+            .update_one(
+                doc! {"_id": recipe, "comment._id": comment},
+                doc! {"$set": {"comment.$.content": content}},
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn pull_comment(&self, recipe: ObjectId, comment: ObjectId) -> Result<(), StoreError> {
+        self.recipes()
+            .update_one(
+                doc! {"_id": recipe},
+                doc! {"$pull": {"comment": {"_id": comment}}},
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn find_categories(&self) -> Result<Vec<Category>, StoreError> {
+        Ok(self
+            .categories()
+            .find(None, FindOptions::builder().sort(doc! {"name": 1}).build())
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    async fn find_category(&self, id: ObjectId) -> Result<Option<Category>, StoreError> {
+        Ok(self.categories().find_one(doc! {"_id": id}, None).await?)
+    }
+
+    async fn insert_category(&self, category: Category) -> Result<bool, StoreError> {
+        match self.categories().insert_one(category, None).await {
+            Ok(_) => Ok(true),
+            Err(err) if is_duplicate_key(&err) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn rename_category(&self, id: ObjectId, name: String) -> Result<bool, StoreError> {
+        match self
+            .categories()
+            .update_one(doc! {"_id": id}, doc! {"$set": {"name": name}}, None)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(err) if is_duplicate_key(&err) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn delete_category(&self, id: ObjectId) -> Result<(), StoreError> {
+        self.categories().delete_one(doc! {"_id": id}, None).await?;
+        self.recipes()
+            .update_many(
+                doc! {"categories": id},
+                doc! {"$pull": {"categories": id}},
+                None,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn find_shopping_list(&self, user: ObjectId) -> Result<Option<ShoppingList>, StoreError> {
+        Ok(self
+            .shopping_lists()
+            .find_one(doc! {"_id": user}, None)
+            .await?)
+    }
+
+    async fn set_shopping_list(&self, list: ShoppingList) -> Result<bool, StoreError> {
+        let version = list.version;
+        let list = ShoppingList {
+            version: version + 1,
+            ..list
+        };
+
+        // The unique id makes the insertion fail if a list was inserted concurrently
+        if version == 0 {
+            return match self.shopping_lists().insert_one(&list, None).await {
+                Ok(_) => Ok(true),
+                Err(err) if is_duplicate_key(&err) => Ok(false),
+                Err(err) => Err(err.into()),
+            };
+        }
+
+        let result = self
+            .shopping_lists()
+            .replace_one(
+                doc! {"_id": list.user, "version": to_bson(&version).unwrap()},
+                &list,
+                None,
+            )
+            .await?;
+        Ok(result.matched_count == 1)
+    }
+
+    async fn find_meal_plans(
+        &self,
+        user: ObjectId,
+        from: Date,
+        to: Date,
+    ) -> Result<Vec<MealPlan>, StoreError> {
+        Ok(self
+            .meal_plans()
+            .find(
+                doc! {
+                    "user": user,
+                    "date": {"$gte": from.to_string(), "$lt": to.to_string()},
+                },
+                FindOptions::builder().sort(doc! {"date": 1}).build(),
+            )
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    async fn set_meal_plan(&self, plan: MealPlan) -> Result<bool, StoreError> {
+        let version = plan.version;
+        let plan = MealPlan {
+            version: version + 1,
+            ..plan
+        };
+
+        if version == 0 {
+            if plan.meals.is_empty() {
+                return Ok(true);
+            }
+
+            // The unique index makes the insertion fail if a plan was inserted concurrently
+            return match self.meal_plans().insert_one(&plan, None).await {
+                Ok(_) => Ok(true),
+                Err(err) if is_duplicate_key(&err) => Ok(false),
+                Err(err) => Err(err.into()),
+            };
+        }
+
+        let filter = doc! {
+            "user": plan.user,
+            "date": plan.date.to_string(),
+            "version": to_bson(&version).unwrap(),
+        };
+        if plan.meals.is_empty() {
+            let result = self.meal_plans().delete_one(filter, None).await?;
+            Ok(result.deleted_count == 1)
+        } else {
+            let result = self.meal_plans().replace_one(filter, &plan, None).await?;
+            Ok(result.matched_count == 1)
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use bson::oid::ObjectId;
+
+use crate::model::User;
+
+/// What an authenticated user is allowed to do
+///
+/// Every operation changing the data of someone else should be checked here
+impl User {
+    /// Whether the user can create recipes, comments and ratings
+    pub fn can_contribute(&self) -> bool {
+        !self.banned
+    }
+
+    /// Whether the user can edit or delete something written by `author`
+    pub fn can_modify(&self, author: ObjectId) -> bool {
+        self.can_contribute() && (self.id == author || self.admin)
+    }
+
+    /// Whether the user can create, rename and delete categories
+    pub fn can_manage_categories(&self) -> bool {
+        self.admin && !self.banned
+    }
+
+    /// Whether the user can ban or unban `target`
+    pub fn can_ban(&self, target: &User) -> bool {
+        self.admin && !self.banned && !target.admin
+    }
+}
@@ -0,0 +1,264 @@
+use bson::oid::ObjectId;
+use thiserror::Error;
+
+use crate::{
+    date::Date,
+    model::{Meal, MealPlan, PlannedMeal, SERVINGS_RANGE},
+    quantity::Amount,
+};
+
+use super::{store::StoreError, too_many_concurrent_changes, Database, MAX_CHANGE_ATTEMPTS};
+
+#[derive(Error, Debug)]
+pub enum PlannerError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+}
+
+#[derive(Error, Debug)]
+pub enum PlanMealError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Recipe not found")]
+    NotFound,
+    #[error("Invalid number of servings")]
+    InvalidServings,
+}
+
+#[derive(Error, Debug)]
+pub enum MoveMealError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("No meal is planned there")]
+    NotFound,
+}
+
+impl Database {
+    /// Meals planned by the user during the week of `day`, from monday to sunday,
+    /// with the name of their recipe, `None` if the recipe doesn't exist anymore
+    pub async fn get_week_plan(
+        &self,
+        user_name: String,
+        user_token: String,
+        day: Date,
+    ) -> Result<Vec<(Date, PlannedMeal, Option<String>)>, PlannerError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(PlannerError::InvalidCredentials);
+        };
+
+        let mut meals = Vec::new();
+        for plan in self.week_plans(user.id, day).await? {
+            for meal in plan.meals {
+                let name = self
+                    .store
+                    .find_recipe(meal.recipe)
+                    .await?
+                    .map(|recipe| recipe.name);
+                meals.push((plan.date, meal, name));
+            }
+        }
+
+        Ok(meals)
+    }
+
+    /// Plan `recipe` at `meal` on `date`, for `servings` people or the servings of the recipe,
+    /// replacing the recipe planned there if any
+    pub async fn plan_meal(
+        &self,
+        user_name: String,
+        user_token: String,
+        date: Date,
+        meal: Meal,
+        recipe: ObjectId,
+        servings: Option<u8>,
+    ) -> Result<(), PlanMealError> {
+        if servings.is_some_and(|servings| !SERVINGS_RANGE.contains(&servings)) {
+            return Err(PlanMealError::InvalidServings);
+        }
+
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(PlanMealError::InvalidCredentials);
+        };
+
+        let Some(recipe) = self.store.find_recipe(recipe).await? else {
+            return Err(PlanMealError::NotFound);
+        };
+
+        self.change_meal_plan(user.id, date, |plan| {
+            plan.meals.retain(|other| other.meal != meal);
+            plan.meals.push(PlannedMeal {
+                meal,
+                recipe: recipe.id,
+                servings: servings.unwrap_or(recipe.servings),
+            });
+            Ok(())
+        })
+        .await
+    }
+
+    /// Move the meal planned at `from` to `to`, the meal planned at `to` if any goes to `from`
+    ///
+    /// Across two days the meal is removed from `from` before being added to `to`,
+    /// so a failure in between loses the meal instead of planning it twice
+    pub async fn move_meal(
+        &self,
+        user_name: String,
+        user_token: String,
+        from: (Date, Meal),
+        to: (Date, Meal),
+    ) -> Result<(), MoveMealError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(MoveMealError::InvalidCredentials);
+        };
+
+        if to.0 == from.0 {
+            return self
+                .change_meal_plan(user.id, from.0, |plan| {
+                    let mut moved = take_meal(plan, from.1).ok_or(MoveMealError::NotFound)?;
+                    moved.meal = to.1;
+                    if let Some(mut swapped) = take_meal(plan, to.1) {
+                        swapped.meal = from.1;
+                        plan.meals.push(swapped);
+                    }
+                    plan.meals.push(moved);
+                    Ok(())
+                })
+                .await;
+        }
+
+        let mut moved = self
+            .change_meal_plan(user.id, from.0, |plan| {
+                take_meal(plan, from.1).ok_or(MoveMealError::NotFound)
+            })
+            .await?;
+        moved.meal = to.1;
+
+        let swapped = self
+            .change_meal_plan(user.id, to.0, |plan| {
+                let swapped = take_meal(plan, to.1);
+                plan.meals.push(moved.clone());
+                Ok::<_, MoveMealError>(swapped)
+            })
+            .await?;
+
+        if let Some(mut swapped) = swapped {
+            swapped.meal = from.1;
+            self.change_meal_plan(user.id, from.0, |plan| {
+                plan.meals.retain(|other| other.meal != from.1);
+                plan.meals.push(swapped.clone());
+                Ok::<_, MoveMealError>(())
+            })
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove the recipe planned at `meal` on `date`, if any
+    pub async fn unplan_meal(
+        &self,
+        user_name: String,
+        user_token: String,
+        date: Date,
+        meal: Meal,
+    ) -> Result<(), PlannerError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(PlannerError::InvalidCredentials);
+        };
+
+        self.change_meal_plan(user.id, date, |plan| {
+            plan.meals.retain(|other| other.meal != meal);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Add the ingredients of the meals planned during the week of `day`
+    /// to the shopping list of the user, for the servings of each meal
+    pub async fn add_week_to_shopping_list(
+        &self,
+        user_name: String,
+        user_token: String,
+        day: Date,
+    ) -> Result<(), PlannerError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(PlannerError::InvalidCredentials);
+        };
+
+        let mut recipes = Vec::new();
+        for plan in self.week_plans(user.id, day).await? {
+            for meal in plan.meals {
+                // Deleted recipes are still shown in the planner, but have nothing to buy
+                let Some(recipe) = self.store.find_recipe(meal.recipe).await? else {
+                    continue;
+                };
+
+                let factor = Amount::new(u64::from(meal.servings), u64::from(recipe.servings))
+                    .unwrap_or(Amount::ONE);
+                recipes.push((recipe, factor));
+            }
+        }
+
+        self.change_shopping_list(user.id, |list| {
+            for (recipe, factor) in &recipes {
+                for ingredient in &recipe.ingredients {
+                    list.add(ingredient, *factor);
+                }
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn week_plans(&self, user: ObjectId, day: Date) -> Result<Vec<MealPlan>, StoreError> {
+        let monday = day.monday();
+        self.store
+            .find_meal_plans(user, monday, monday.add_days(7))
+            .await
+    }
+
+    /// Apply `change` to the meal plan of `user` on `date` and store it,
+    /// starting again from the stored plan when it was changed concurrently
+    async fn change_meal_plan<T, E: From<StoreError>>(
+        &self,
+        user: ObjectId,
+        date: Date,
+        mut change: impl FnMut(&mut MealPlan) -> Result<T, E>,
+    ) -> Result<T, E> {
+        for _ in 0..MAX_CHANGE_ATTEMPTS {
+            let mut plan = self.meal_plan_of(user, date).await?;
+            let output = change(&mut plan)?;
+            if self.store.set_meal_plan(plan).await? {
+                return Ok(output);
+            }
+        }
+
+        Err(too_many_concurrent_changes().into())
+    }
+
+    async fn meal_plan_of(&self, user: ObjectId, date: Date) -> Result<MealPlan, StoreError> {
+        Ok(self
+            .store
+            .find_meal_plans(user, date, date.add_days(1))
+            .await?
+            .pop()
+            .unwrap_or(MealPlan {
+                user,
+                date,
+                meals: Vec::new(),
+                version: 0,
+            }))
+    }
+}
+
+/// Remove and return the meal planned at `meal` in `plan`
+fn take_meal(plan: &mut MealPlan, meal: Meal) -> Option<PlannedMeal> {
+    let index = plan.meals.iter().position(|other| other.meal == meal)?;
+    Some(plan.meals.remove(index))
+}
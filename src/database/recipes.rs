@@ -1,48 +1,339 @@
-use bson::{doc, to_bson};
-use mongodb::{error::Error as MongoError, options::FindOptions};
+use std::cmp::Ordering;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::model::{Ingredient, Recipe, Tool};
+use crate::{
+    model::{Ingredient, Note, Recipe, RecipeSummary, Tool, NOTE_RANGE},
+    validation::{validate_recipe, RecipeDraft, ValidationError},
+};
 
-use super::Database;
+use super::{store::StoreError, Database};
 
 #[derive(Error, Debug)]
 pub enum GetRecipesError {
     #[error(transparent)]
-    Database(#[from] MongoError),
+    Database(#[from] StoreError),
+    #[error("The cursor was made for another order")]
+    InvalidCursor,
+}
+
+#[derive(Error, Debug)]
+pub enum GetRecipeError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
 }
 
 #[derive(Error, Debug)]
 pub enum NewRecipeError {
     #[error(transparent)]
-    Database(#[from] MongoError),
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Banned users can't create recipes")]
+    Forbidden,
+    #[error("Invalid recipe")]
+    Invalid(Vec<ValidationError>),
+}
+
+#[derive(Error, Debug)]
+pub enum UpdateRecipeError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
     #[error("Invalid credentials")]
     InvalidCredentials,
+    #[error("Recipe not found")]
+    NotFound,
+    #[error("Only the author or an admin can edit this recipe")]
+    Forbidden,
+    #[error("Invalid recipe")]
+    Invalid(Vec<ValidationError>),
+}
+
+#[derive(Error, Debug)]
+pub enum DeleteRecipeError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Recipe not found")]
+    NotFound,
+    #[error("Only the author or an admin can delete this recipe")]
+    Forbidden,
+}
+
+#[derive(Error, Debug)]
+pub enum RateRecipeError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Recipe not found")]
+    NotFound,
+    #[error("Banned users can't rate recipes")]
+    Forbidden,
+    #[error("Invalid note")]
+    InvalidNote,
+}
+
+/// Order in which recipes are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecipeOrder {
+    /// Oldest first
+    #[default]
+    Insertion,
+    /// Best rated first, then the unrated ones
+    Rating,
+}
+
+/// New values of the editable fields of a recipe
+pub struct RecipeUpdate {
+    pub name: String,
+    pub icon_url: String,
+    pub price_level: u8,
+    pub healthy_level: u8,
+    pub servings: u8,
+    pub instructions: Vec<String>,
+    pub ingredients: Vec<Ingredient>,
+    pub tools: Vec<Tool>,
+    pub categories: Vec<ObjectId>,
+}
+
+/// Position in the recipe list after which the next page starts
+///
+/// Made of the sort key of the last recipe of the previous page,
+/// so pages don't shift when recipes are added or removed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RecipeCursor {
+    Insertion { id: ObjectId },
+    Rating { rating: Option<f64>, id: ObjectId },
+}
+
+impl RecipeCursor {
+    /// Cursor of the page starting after `recipe` in the order `order`
+    fn after(order: RecipeOrder, recipe: &RecipeSummary) -> Self {
+        match order {
+            RecipeOrder::Insertion => Self::Insertion { id: recipe.id },
+            RecipeOrder::Rating => Self::Rating {
+                rating: recipe.rating,
+                id: recipe.id,
+            },
+        }
+    }
+
+    pub fn order(&self) -> RecipeOrder {
+        match self {
+            Self::Insertion { .. } => RecipeOrder::Insertion,
+            Self::Rating { .. } => RecipeOrder::Rating,
+        }
+    }
+
+    /// Whether `recipe` is listed after this cursor
+    ///
+    /// Same rules as the mongodb filter built from it
+    pub fn precedes(&self, recipe: &RecipeSummary) -> bool {
+        match *self {
+            Self::Insertion { id } => recipe.id > id,
+            // A missing rating is lower than any rating
+            Self::Rating { rating, id } => match recipe.rating.partial_cmp(&rating) {
+                Some(Ordering::Less) => true,
+                Some(Ordering::Equal) => recipe.id > id,
+                _ => false,
+            },
+        }
+    }
+
+    /// Opaque representation given to the client
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(bson::to_vec(self).unwrap_or_default())
+    }
+
+    /// Read a cursor from [`RecipeCursor::encode`], `None` if it isn't one
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        bson::from_slice(&bytes).ok()
+    }
+}
+
+/// Page of the recipe list
+pub struct RecipeList {
+    /// Recipes along with the name of their author
+    pub recipes: Vec<(RecipeSummary, Option<String>)>,
+    /// Cursor of the next page, `None` if this is the last one
+    pub next: Option<RecipeCursor>,
+}
+
+/// Conditions the recipes listed by [`Database::get_recipes`] must meet
+#[derive(Debug, Clone, Default)]
+pub struct RecipeCriteria {
+    pub min_price_level: Option<u8>,
+    pub max_price_level: Option<u8>,
+    pub min_healthy_level: Option<u8>,
+    pub max_healthy_level: Option<u8>,
+    /// Names of ingredients the recipes must all use, ignoring case
+    pub ingredients: Vec<String>,
+    /// Names of ingredients the recipes must not use, ignoring case
+    pub excluded_ingredients: Vec<String>,
+    /// Names of the only tools the recipes may need, ignoring case, `None` to allow any tool
+    pub tools: Option<Vec<String>>,
+    /// Categories the recipes must be in at least one of, any category if empty
+    pub categories: Vec<ObjectId>,
+}
+
+impl RecipeCriteria {
+    /// Whether `recipe` meets these criteria
+    ///
+    /// Same rules as the mongodb filter built from them
+    pub fn matches(&self, recipe: &Recipe) -> bool {
+        let in_range = |level: u8, min: Option<u8>, max: Option<u8>| {
+            min.is_none_or(|min| level >= min) && max.is_none_or(|max| level <= max)
+        };
+        let same_name = |a: &str, b: &str| a.to_lowercase() == b.to_lowercase();
+        let uses = |name: &String| {
+            recipe
+                .ingredients
+                .iter()
+                .any(|ingredient| same_name(&ingredient.name, name))
+        };
+
+        in_range(
+            recipe.price_level,
+            self.min_price_level,
+            self.max_price_level,
+        ) && in_range(
+            recipe.healthy_level,
+            self.min_healthy_level,
+            self.max_healthy_level,
+        ) && self.ingredients.iter().all(uses)
+            && !self.excluded_ingredients.iter().any(uses)
+            && self.tools.as_ref().is_none_or(|tools| {
+                recipe
+                    .tools
+                    .iter()
+                    .all(|tool| tools.iter().any(|name| same_name(&tool.name, name)))
+            })
+            && (self.categories.is_empty()
+                || self
+                    .categories
+                    .iter()
+                    .any(|category| recipe.categories.contains(category)))
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SearchRecipesError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+}
+
+/// Page of the recipes matching a search
+pub struct RecipePage {
+    /// Recipes along with the name of their author
+    pub recipes: Vec<(RecipeSummary, Option<String>)>,
+    /// Index of this page, the last one if the requested page was after it
+    pub page: usize,
+    pub page_count: usize,
 }
 
 impl Database {
-    pub async fn get_recipes(&self, limit: usize) -> Result<Vec<Recipe>, GetRecipesError> {
-        let users = self.users();
+    /// Get at most `page_size` recipes meeting `criteria`, starting after `after`
+    pub async fn get_recipes(
+        &self,
+        page_size: usize,
+        order: RecipeOrder,
+        criteria: &RecipeCriteria,
+        after: Option<&RecipeCursor>,
+    ) -> Result<RecipeList, GetRecipesError> {
+        let page_size = page_size.max(1);
 
-        let mut users = users.find(None, FindOptions::builder().build()).await?;
+        if after.is_some_and(|after| after.order() != order) {
+            return Err(GetRecipesError::InvalidCursor);
+        }
 
-        let mut recipes = Vec::with_capacity(limit);
+        // One more recipe to know if there is a next page
+        let mut recipes = self
+            .store
+            .find_recipes(page_size.saturating_add(1), order, criteria, after)
+            .await?;
 
-        while recipes.len() < limit {
-            if !users.advance().await? {
-                break;
-            };
+        let next = if recipes.len() > page_size {
+            recipes.truncate(page_size);
+            recipes
+                .last()
+                .map(|recipe| RecipeCursor::after(order, recipe))
+        } else {
+            None
+        };
+
+        let recipes = self
+            .with_author_names(recipes, |recipe| recipe.author)
+            .await?;
+
+        Ok(RecipeList { recipes, next })
+    }
 
-            let user = users.deserialize_current()?;
+    /// Get the page number `page` of the recipes matching `query` and meeting `criteria`,
+    /// most relevant first, or the last page if `page` is after it
+    pub async fn search_recipes(
+        &self,
+        query: &str,
+        criteria: &RecipeCriteria,
+        page: usize,
+        page_size: usize,
+    ) -> Result<RecipePage, SearchRecipesError> {
+        let page_size = page_size.max(1);
 
-            recipes.extend_from_slice(&user.recipes);
+        if query.trim().is_empty() {
+            return Ok(RecipePage {
+                recipes: Vec::new(),
+                page: 0,
+                page_count: 1,
+            });
         }
 
-        if recipes.len() > limit {
-            recipes = recipes.into_iter().take(limit).collect();
+        let (mut recipes, total) = self
+            .store
+            .search_recipes(query, criteria, page.saturating_mul(page_size), page_size)
+            .await?;
+
+        let page_count = total.div_ceil(page_size).max(1);
+        let page = page.min(page_count - 1);
+
+        // The requested page is after the last one
+        if recipes.is_empty() && total > 0 {
+            (recipes, _) = self
+                .store
+                .search_recipes(query, criteria, page * page_size, page_size)
+                .await?;
         }
 
-        Ok(recipes)
+        let recipes = self
+            .with_author_names(recipes, |recipe| recipe.author)
+            .await?;
+
+        Ok(RecipePage {
+            recipes,
+            page,
+            page_count,
+        })
+    }
+
+    /// Get the recipe `id` along with the name of its author
+    pub async fn get_recipe(
+        &self,
+        id: ObjectId,
+    ) -> Result<Option<(Recipe, Option<String>)>, GetRecipeError> {
+        let Some(recipe) = self.store.find_recipe(id).await? else {
+            return Ok(None);
+        };
+
+        let mut recipes = self
+            .with_author_names(vec![recipe], |recipe| recipe.author)
+            .await?;
+
+        Ok(recipes.pop())
     }
 
     pub async fn new_recipe(
@@ -53,43 +344,174 @@ impl Database {
         icon_url: String,
         price_level: u8,
         healthy_level: u8,
+        servings: u8,
         instructions: Vec<String>,
         ingredients: Vec<Ingredient>,
         tools: Vec<Tool>,
+        categories: Vec<ObjectId>,
     ) -> Result<(), NewRecipeError> {
-        let users = self.users();
+        let errors = validate_recipe(&RecipeDraft {
+            name: &name,
+            icon_url: &icon_url,
+            price_level,
+            healthy_level,
+            servings,
+            instructions: &instructions,
+            ingredients: &ingredients,
+            tools: &tools,
+        });
+        if !errors.is_empty() {
+            return Err(NewRecipeError::Invalid(errors));
+        }
 
-        let Some(user) = users.find_one(doc! {"name": &user_name}, None).await? else {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
             return Err(NewRecipeError::InvalidCredentials);
         };
 
-        if user
-            .tokens
-            .iter()
-            .find(|token| token.token == user_token)
-            .is_none()
-        {
-            return Err(NewRecipeError::InvalidCredentials);
+        if !user.can_contribute() {
+            return Err(NewRecipeError::Forbidden);
         }
 
+        let categories = self.existing_categories(categories).await?;
+
         let recipe = Recipe {
+            id: ObjectId::new(),
+            author: user.id,
             name,
             instructions,
             icon_url,
             price_level,
             healthy_level,
+            servings,
             comment: Vec::new(),
             notes: Vec::new(),
             ingredients,
             tools,
-            categories: Vec::new(),
+            categories,
+        };
+
+        self.store.insert_recipe(recipe).await?;
+
+        Ok(())
+    }
+
+    pub async fn update_recipe(
+        &self,
+        user_name: String,
+        user_token: String,
+        id: ObjectId,
+        name: String,
+        icon_url: String,
+        price_level: u8,
+        healthy_level: u8,
+        servings: u8,
+        instructions: Vec<String>,
+        ingredients: Vec<Ingredient>,
+        tools: Vec<Tool>,
+        categories: Vec<ObjectId>,
+    ) -> Result<(), UpdateRecipeError> {
+        let errors = validate_recipe(&RecipeDraft {
+            name: &name,
+            icon_url: &icon_url,
+            price_level,
+            healthy_level,
+            servings,
+            instructions: &instructions,
+            ingredients: &ingredients,
+            tools: &tools,
+        });
+        if !errors.is_empty() {
+            return Err(UpdateRecipeError::Invalid(errors));
+        }
+
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(UpdateRecipeError::InvalidCredentials);
         };
 
-        users
-            .update_one(
-                doc! { "name": &user_name},
-                doc! { "$push": {"recipes": to_bson(&recipe).unwrap()}},
-                None,
+        let Some(recipe) = self.store.find_recipe(id).await? else {
+            return Err(UpdateRecipeError::NotFound);
+        };
+
+        if !user.can_modify(recipe.author) {
+            return Err(UpdateRecipeError::Forbidden);
+        }
+
+        let categories = self.existing_categories(categories).await?;
+
+        self.store
+            .update_recipe(
+                id,
+                RecipeUpdate {
+                    name,
+                    icon_url,
+                    price_level,
+                    healthy_level,
+                    servings,
+                    instructions,
+                    ingredients,
+                    tools,
+                    categories,
+                },
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_recipe(
+        &self,
+        user_name: String,
+        user_token: String,
+        id: ObjectId,
+    ) -> Result<(), DeleteRecipeError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(DeleteRecipeError::InvalidCredentials);
+        };
+
+        let Some(recipe) = self.store.find_recipe(id).await? else {
+            return Err(DeleteRecipeError::NotFound);
+        };
+
+        if !user.can_modify(recipe.author) {
+            return Err(DeleteRecipeError::Forbidden);
+        }
+
+        self.store.delete_recipe(id).await?;
+
+        Ok(())
+    }
+
+    /// Set the note given by the user to the recipe `id`, replacing its previous note
+    pub async fn rate_recipe(
+        &self,
+        user_name: String,
+        user_token: String,
+        id: ObjectId,
+        note: u8,
+    ) -> Result<(), RateRecipeError> {
+        if !NOTE_RANGE.contains(&note) {
+            return Err(RateRecipeError::InvalidNote);
+        }
+
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(RateRecipeError::InvalidCredentials);
+        };
+
+        if !user.can_contribute() {
+            return Err(RateRecipeError::Forbidden);
+        }
+
+        if self.store.find_recipe(id).await?.is_none() {
+            return Err(RateRecipeError::NotFound);
+        }
+
+        self.store
+            .set_note(
+                id,
+                Note {
+                    note,
+                    user: user.id,
+                },
             )
             .await?;
 
@@ -0,0 +1,160 @@
+use bson::oid::ObjectId;
+use thiserror::Error;
+
+use crate::{
+    model::{ShoppingItem, ShoppingList, SERVINGS_RANGE},
+    quantity::Amount,
+};
+
+use super::{store::StoreError, too_many_concurrent_changes, Database, MAX_CHANGE_ATTEMPTS};
+
+#[derive(Error, Debug)]
+pub enum ShoppingListError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+}
+
+#[derive(Error, Debug)]
+pub enum AddToShoppingListError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Recipe not found")]
+    NotFound,
+    #[error("Invalid number of servings")]
+    InvalidServings,
+}
+
+#[derive(Error, Debug)]
+pub enum CheckShoppingItemError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Item not found")]
+    NotFound,
+}
+
+impl Database {
+    /// Shopping list of the user, empty if nothing was ever added to it
+    pub async fn get_shopping_list(
+        &self,
+        user_name: String,
+        user_token: String,
+    ) -> Result<ShoppingList, ShoppingListError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(ShoppingListError::InvalidCredentials);
+        };
+
+        Ok(self.shopping_list_of(user.id).await?)
+    }
+
+    /// Add the ingredients of `recipe` to the shopping list of the user,
+    /// scaled to `servings` if any
+    pub async fn add_to_shopping_list(
+        &self,
+        user_name: String,
+        user_token: String,
+        recipe: ObjectId,
+        servings: Option<u8>,
+    ) -> Result<(), AddToShoppingListError> {
+        if servings.is_some_and(|servings| !SERVINGS_RANGE.contains(&servings)) {
+            return Err(AddToShoppingListError::InvalidServings);
+        }
+
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(AddToShoppingListError::InvalidCredentials);
+        };
+
+        let Some(recipe) = self.store.find_recipe(recipe).await? else {
+            return Err(AddToShoppingListError::NotFound);
+        };
+
+        let factor = servings
+            .and_then(|servings| Amount::new(u64::from(servings), u64::from(recipe.servings)))
+            .unwrap_or(Amount::ONE);
+
+        self.change_shopping_list(user.id, |list| {
+            for ingredient in &recipe.ingredients {
+                list.add(ingredient, factor);
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Check or uncheck the item named `item` in the shopping list of the user
+    pub async fn set_shopping_item_checked(
+        &self,
+        user_name: String,
+        user_token: String,
+        item: String,
+        checked: bool,
+    ) -> Result<(), CheckShoppingItemError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(CheckShoppingItemError::InvalidCredentials);
+        };
+
+        let key = ShoppingItem::key(&item);
+        self.change_shopping_list(user.id, |list| {
+            let Some(item) = list
+                .items
+                .iter_mut()
+                .find(|other| ShoppingItem::key(&other.name) == key)
+            else {
+                return Err(CheckShoppingItemError::NotFound);
+            };
+
+            item.checked = checked;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Remove the items of the shopping list of the user, or only the checked ones
+    pub async fn clear_shopping_list(
+        &self,
+        user_name: String,
+        user_token: String,
+        only_checked: bool,
+    ) -> Result<(), ShoppingListError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(ShoppingListError::InvalidCredentials);
+        };
+
+        self.change_shopping_list(user.id, |list| {
+            list.items.retain(|item| only_checked && !item.checked);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Apply `change` to the shopping list of `user` and store it,
+    /// starting again from the stored list when it was changed concurrently
+    pub(super) async fn change_shopping_list<E: From<StoreError>>(
+        &self,
+        user: ObjectId,
+        mut change: impl FnMut(&mut ShoppingList) -> Result<(), E>,
+    ) -> Result<(), E> {
+        for _ in 0..MAX_CHANGE_ATTEMPTS {
+            let mut list = self.shopping_list_of(user).await?;
+            change(&mut list)?;
+            if self.store.set_shopping_list(list).await? {
+                return Ok(());
+            }
+        }
+
+        Err(too_many_concurrent_changes().into())
+    }
+
+    async fn shopping_list_of(&self, user: ObjectId) -> Result<ShoppingList, StoreError> {
+        Ok(self
+            .store
+            .find_shopping_list(user)
+            .await?
+            .unwrap_or_else(|| ShoppingList::new(user)))
+    }
+}
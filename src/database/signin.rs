@@ -1,16 +1,15 @@
-use bson::{doc, to_bson};
+use bson::DateTime;
 use leptos::logging::error;
-use mongodb::error::Error as MongoError;
 use thiserror::Error;
 
 use crate::model::Token;
 
-use super::{generate_token, Database};
+use super::{generate_token, hash_token, store::StoreError, Database};
 
 #[derive(Error, Debug)]
 pub enum SigninError {
     #[error(transparent)]
-    Database(#[from] MongoError),
+    Database(#[from] StoreError),
     #[error("Wrong name or password")]
     WrongNameOrPassword,
     #[error("Internal error")]
@@ -19,9 +18,7 @@ pub enum SigninError {
 
 impl Database {
     pub async fn signin(&self, name: String, password: String) -> Result<Token, SigninError> {
-        let users = self.users();
-
-        let Some(user) = users.find_one(doc! {"name": &name}, None).await? else {
+        let Some(user) = self.store.find_user(&name).await? else {
             return Err(SigninError::WrongNameOrPassword);
         };
 
@@ -35,14 +32,15 @@ impl Database {
         }
 
         let token = generate_token();
+        let hashed_token = Token {
+            token: hash_token(&token.token),
+            expiration: token.expiration,
+        };
 
-        users
-            .update_one(
-                doc! {"name": &name},
-                doc! {"$push": doc! { "tokens": to_bson(&token).unwrap() }},
-                None,
-            )
+        self.store
+            .pull_expired_tokens(Some(user.id), DateTime::now())
             .await?;
+        self.store.push_token(user.id, hashed_token).await?;
 
         Ok(token)
     }
@@ -0,0 +1,35 @@
+use thiserror::Error;
+
+use super::{hash_token, store::StoreError, Database};
+
+#[derive(Error, Debug)]
+pub enum SignoutError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+}
+
+impl Database {
+    /// Revoke `token`
+    pub async fn signout(&self, name: String, token: String) -> Result<(), SignoutError> {
+        let Some(user) = self.authenticate(&name, &token).await? else {
+            return Err(SignoutError::InvalidCredentials);
+        };
+
+        self.store.pull_token(user.id, &hash_token(&token)).await?;
+
+        Ok(())
+    }
+
+    /// Revoke every token of the user, `token` included
+    pub async fn signout_all(&self, name: String, token: String) -> Result<(), SignoutError> {
+        let Some(user) = self.authenticate(&name, &token).await? else {
+            return Err(SignoutError::InvalidCredentials);
+        };
+
+        self.store.clear_tokens(user.id).await?;
+
+        Ok(())
+    }
+}
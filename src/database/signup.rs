@@ -1,29 +1,36 @@
-use bcrypt::DEFAULT_COST;
+use bson::oid::ObjectId;
 use leptos::logging::error;
-use mongodb::error::Error as MongoError;
 use thiserror::Error;
 
-use crate::model::{Token, User};
+use crate::{
+    config::Config,
+    model::{Token, User},
+    validation::is_valid_user_name,
+};
 
-use super::{generate_token, Database};
+use super::{generate_token, hash_token, store::StoreError, Database};
 
 #[derive(Error, Debug)]
 pub enum SignupError {
     #[error(transparent)]
-    Database(#[from] MongoError),
+    Database(#[from] StoreError),
     #[error("Name already taken")]
     NameAlreadyTaken,
+    #[error("Invalid name")]
+    InvalidName,
     #[error("Internal error")]
     Internal,
 }
 
 impl Database {
     pub async fn signup(&self, name: String, password: String) -> Result<Token, SignupError> {
-        let users = self.users();
+        if !is_valid_user_name(&name) {
+            return Err(SignupError::InvalidName);
+        }
 
         let token = generate_token();
 
-        let hashed_password = match bcrypt::hash(password, DEFAULT_COST) {
+        let hashed_password = match bcrypt::hash(password, Config::get().bcrypt_cost) {
             Ok(ok) => ok,
             Err(err) => {
                 error!("Failed to hash password, {:?}", err);
@@ -32,15 +39,27 @@ impl Database {
         };
 
         let user = User {
+            id: ObjectId::new(),
             name,
             password: hashed_password,
             admin: false,
-            tokens: vec![token.clone()],
-            recipes: Vec::new(),
+            banned: false,
+            units: None,
+            tokens: vec![Token {
+                token: hash_token(&token.token),
+                expiration: token.expiration,
+            }],
         };
 
-        users.insert_one(user, None).await?;
+        if !self.store.insert_user(user).await? {
+            return Err(SignupError::NameAlreadyTaken);
+        }
 
         Ok(token)
     }
+
+    /// Whether no user is named `name`
+    pub async fn is_name_available(&self, name: &str) -> Result<bool, StoreError> {
+        Ok(self.store.find_user(name).await?.is_none())
+    }
 }
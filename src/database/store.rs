@@ -0,0 +1,131 @@
+use std::error::Error as StdError;
+
+use async_trait::async_trait;
+use bson::{oid::ObjectId, DateTime};
+use thiserror::Error;
+
+use crate::{
+    date::Date,
+    model::{Category, Comment, MealPlan, Note, Recipe, RecipeSummary, ShoppingList, Token, User},
+    quantity::UnitSystem,
+};
+
+use super::recipes::{RecipeCriteria, RecipeCursor, RecipeOrder, RecipeUpdate};
+
+/// Failure of a storage backend, whatever the backend
+#[derive(Error, Debug)]
+#[error(transparent)]
+pub struct StoreError(Box<dyn StdError + Send + Sync>);
+impl StoreError {
+    pub fn new(err: impl Into<Box<dyn StdError + Send + Sync>>) -> Self {
+        Self(err.into())
+    }
+}
+
+/// Maximum `skip` and `limit` of [`RecipeStore::find_comments`], mongodb slices are 32 bits
+pub const MAX_COMMENT_SLICE: usize = i32::MAX as usize;
+
+/// Storage backend of the [`Database`](super::Database)
+///
+/// Implementations only store and query the data,
+/// credentials and permissions are checked by the [`Database`](super::Database)
+#[async_trait]
+pub trait RecipeStore: Send + Sync {
+    /// Prepare the storage, called once at start of the program
+    async fn setup(&self) -> Result<(), ()>;
+
+    async fn find_user(&self, name: &str) -> Result<Option<User>, StoreError>;
+    /// Users whose id is in `ids`, in any order
+    async fn find_users(&self, ids: &[ObjectId]) -> Result<Vec<User>, StoreError>;
+    /// Insert `user`, returns `false` if a user with the same name already exists
+    async fn insert_user(&self, user: User) -> Result<bool, StoreError>;
+    async fn set_admin(&self, user: ObjectId, admin: bool) -> Result<(), StoreError>;
+    async fn set_banned(&self, user: ObjectId, banned: bool) -> Result<(), StoreError>;
+    async fn set_units(&self, user: ObjectId, units: Option<UnitSystem>) -> Result<(), StoreError>;
+    async fn push_token(&self, user: ObjectId, token: Token) -> Result<(), StoreError>;
+    /// Remove the token whose hash is `token`
+    async fn pull_token(&self, user: ObjectId, token: &str) -> Result<(), StoreError>;
+    async fn clear_tokens(&self, user: ObjectId) -> Result<(), StoreError>;
+    /// Remove the tokens expired at `now` of `user`, or of every user if `None`
+    async fn pull_expired_tokens(
+        &self,
+        user: Option<ObjectId>,
+        now: DateTime,
+    ) -> Result<(), StoreError>;
+
+    /// At most `limit` recipes meeting `criteria`, starting after `after`
+    async fn find_recipes(
+        &self,
+        limit: usize,
+        order: RecipeOrder,
+        criteria: &RecipeCriteria,
+        after: Option<&RecipeCursor>,
+    ) -> Result<Vec<RecipeSummary>, StoreError>;
+    /// Recipes matching the text `query` and meeting `criteria`, most relevant first,
+    /// after skipping `skip` of them, along with the total number of matching recipes
+    async fn search_recipes(
+        &self,
+        query: &str,
+        criteria: &RecipeCriteria,
+        skip: usize,
+        limit: usize,
+    ) -> Result<(Vec<RecipeSummary>, usize), StoreError>;
+    /// Every recipe written by `author`, oldest first
+    async fn find_author_recipes(&self, author: ObjectId)
+        -> Result<Vec<RecipeSummary>, StoreError>;
+    async fn find_recipe(&self, id: ObjectId) -> Result<Option<Recipe>, StoreError>;
+    async fn insert_recipe(&self, recipe: Recipe) -> Result<(), StoreError>;
+    async fn update_recipe(&self, id: ObjectId, update: RecipeUpdate) -> Result<(), StoreError>;
+    async fn delete_recipe(&self, id: ObjectId) -> Result<(), StoreError>;
+    /// Set the note of `note.user` on `recipe`, replacing the previous one of this user
+    async fn set_note(&self, recipe: ObjectId, note: Note) -> Result<(), StoreError>;
+
+    /// At most `limit` comments of `recipe` starting at `skip`, with the total number of comments
+    /// `None` if the recipe doesn't exist, `limit` must not be 0
+    ///
+    /// Fails if `skip` or `limit` is above [`MAX_COMMENT_SLICE`]
+    async fn find_comments(
+        &self,
+        recipe: ObjectId,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Option<(Vec<Comment>, usize)>, StoreError>;
+    /// Append `comment` to the comments of `recipe`
+    async fn push_comment(&self, recipe: ObjectId, comment: Comment) -> Result<(), StoreError>;
+    async fn set_comment_content(
+        &self,
+        recipe: ObjectId,
+        comment: ObjectId,
+        content: String,
+    ) -> Result<(), StoreError>;
+    async fn pull_comment(&self, recipe: ObjectId, comment: ObjectId) -> Result<(), StoreError>;
+
+    /// Every category, sorted by name
+    async fn find_categories(&self) -> Result<Vec<Category>, StoreError>;
+    async fn find_category(&self, id: ObjectId) -> Result<Option<Category>, StoreError>;
+    /// Insert `category`, returns `false` if a category with the same name already exists
+    async fn insert_category(&self, category: Category) -> Result<bool, StoreError>;
+    /// Rename the category `id`, returns `false` if a category is already named `name`
+    async fn rename_category(&self, id: ObjectId, name: String) -> Result<bool, StoreError>;
+    /// Delete the category `id` and remove it from the recipes
+    async fn delete_category(&self, id: ObjectId) -> Result<(), StoreError>;
+
+    async fn find_shopping_list(&self, user: ObjectId) -> Result<Option<ShoppingList>, StoreError>;
+    /// Replace the shopping list of `list.user` by `list` with its version incremented,
+    /// returns `false` without storing it if the stored list isn't at `list.version` anymore,
+    /// a list at version 0 is only stored if the user has none
+    async fn set_shopping_list(&self, list: ShoppingList) -> Result<bool, StoreError>;
+
+    /// Meal plans of `user` from `from` included to `to` excluded, ordered by date
+    async fn find_meal_plans(
+        &self,
+        user: ObjectId,
+        from: Date,
+        to: Date,
+    ) -> Result<Vec<MealPlan>, StoreError>;
+    /// Replace the meal plan of `plan.user` on `plan.date` by `plan` with its version incremented,
+    /// removing it if it has no meals, returns `false` without storing it
+    /// if the stored plan isn't at `plan.version` anymore,
+    /// a plan at version 0 is only stored if the user has none on this date
+    async fn set_meal_plan(&self, plan: MealPlan) -> Result<bool, StoreError>;
+}
@@ -0,0 +1,45 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bson::DateTime;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::model::User;
+
+use super::{store::StoreError, Database};
+
+/// One way hash of a token, the only form under which tokens are stored
+pub(super) fn hash_token(token: &str) -> String {
+    STANDARD.encode(Sha256::digest(token.as_bytes()))
+}
+
+impl Database {
+    /// Get the user named `name` if `token` is one of its unexpired tokens
+    ///
+    /// Every authenticated operation should go through this function
+    pub(super) async fn authenticate(
+        &self,
+        name: &str,
+        token: &str,
+    ) -> Result<Option<User>, StoreError> {
+        let Some(user) = self.store.find_user(name).await? else {
+            return Ok(None);
+        };
+
+        let now = DateTime::now();
+        let hash = hash_token(token);
+
+        if user.tokens.iter().any(|user_token| {
+            bool::from(user_token.token.as_bytes().ct_eq(hash.as_bytes()))
+                && user_token.expiration > now
+        }) {
+            Ok(Some(user))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Remove the expired tokens of every user
+    pub async fn prune_expired_tokens(&self) -> Result<(), StoreError> {
+        self.store.pull_expired_tokens(None, DateTime::now()).await
+    }
+}
@@ -0,0 +1,166 @@
+use bson::{oid::ObjectId, DateTime};
+use thiserror::Error;
+
+use crate::{model::RecipeSummary, quantity::UnitSystem};
+
+use super::{store::StoreError, Database};
+
+#[derive(Error, Debug)]
+pub enum BanUserError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("User not found")]
+    NotFound,
+    #[error("Only admins can ban users, and admins can't be banned")]
+    Forbidden,
+}
+
+#[derive(Error, Debug)]
+pub enum UnitsError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+}
+
+#[derive(Error, Debug)]
+pub enum GetProfileError {
+    #[error(transparent)]
+    Database(#[from] StoreError),
+}
+
+/// Public informations about a user
+#[derive(Debug)]
+pub struct Profile {
+    pub name: String,
+    pub joined: DateTime,
+    /// Recipes written by the user, oldest first
+    pub recipes: Vec<RecipeSummary>,
+    /// Average of the notes received on all the recipes of the user
+    pub rating: Option<f64>,
+    pub rating_count: u32,
+}
+
+impl Database {
+    /// Profile of the user named `name`, `None` if there is no such user
+    pub async fn get_profile(&self, name: &str) -> Result<Option<Profile>, GetProfileError> {
+        let Some(user) = self.store.find_user(name).await? else {
+            return Ok(None);
+        };
+
+        let recipes = self.store.find_author_recipes(user.id).await?;
+
+        let (sum, rating_count) = recipes
+            .iter()
+            .filter_map(|recipe| {
+                Some((
+                    recipe.rating? * f64::from(recipe.rating_count),
+                    recipe.rating_count,
+                ))
+            })
+            .fold((0.0, 0), |(sum, count), (recipe_sum, recipe_count)| {
+                (sum + recipe_sum, count + recipe_count)
+            });
+
+        Ok(Some(Profile {
+            name: user.name,
+            joined: user.id.timestamp(),
+            recipes,
+            rating: (rating_count > 0).then(|| sum / f64::from(rating_count)),
+            rating_count,
+        }))
+    }
+
+    /// Pair each of `items` with the name of its author,
+    /// `None` if the author doesn't exist anymore
+    pub(super) async fn with_author_names<T>(
+        &self,
+        items: Vec<T>,
+        author: impl Fn(&T) -> ObjectId,
+    ) -> Result<Vec<(T, Option<String>)>, StoreError> {
+        let mut ids: Vec<_> = items.iter().map(&author).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let users = self.store.find_users(&ids).await?;
+
+        Ok(items
+            .into_iter()
+            .map(|item| {
+                let id = author(&item);
+                let name = users
+                    .iter()
+                    .find(|user| user.id == id)
+                    .map(|user| user.name.clone());
+                (item, name)
+            })
+            .collect())
+    }
+
+    /// Ban or unban the user named `target`
+    pub async fn set_banned(
+        &self,
+        user_name: String,
+        user_token: String,
+        target: String,
+        banned: bool,
+    ) -> Result<(), BanUserError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(BanUserError::InvalidCredentials);
+        };
+
+        let Some(target) = self.store.find_user(&target).await? else {
+            return Err(BanUserError::NotFound);
+        };
+
+        if !user.can_ban(&target) {
+            return Err(BanUserError::Forbidden);
+        }
+
+        self.store.set_banned(target.id, banned).await?;
+
+        Ok(())
+    }
+
+    /// System the user wants quantities shown in, `None` to show them as written
+    pub async fn get_units(
+        &self,
+        user_name: String,
+        user_token: String,
+    ) -> Result<Option<UnitSystem>, UnitsError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(UnitsError::InvalidCredentials);
+        };
+
+        Ok(user.units)
+    }
+
+    pub async fn set_units(
+        &self,
+        user_name: String,
+        user_token: String,
+        units: Option<UnitSystem>,
+    ) -> Result<(), UnitsError> {
+        let Some(user) = self.authenticate(&user_name, &user_token).await? else {
+            return Err(UnitsError::InvalidCredentials);
+        };
+
+        self.store.set_units(user.id, units).await?;
+
+        Ok(())
+    }
+
+    /// Make the user named `name` an admin, returns `false` if there is no such user
+    ///
+    /// Not exposed to the client, used to promote the first admin at startup
+    pub async fn promote_admin(&self, name: &str) -> Result<bool, StoreError> {
+        let Some(user) = self.store.find_user(name).await? else {
+            return Ok(false);
+        };
+
+        self.store.set_admin(user.id, true).await?;
+
+        Ok(true)
+    }
+}
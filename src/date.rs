@@ -0,0 +1,134 @@
+//! Calendar days without time of day nor time zone, used by the meal planner
+
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+/// Day of the Gregorian calendar, serialized as `YYYY-MM-DD` so dates sort like strings
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(try_from = "String", into = "String")]
+pub struct Date {
+    /// Days since 1970-01-01
+    days: i64,
+}
+
+impl Date {
+    /// Parse a date formatted as `YYYY-MM-DD`, `None` if it isn't a valid date
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split('-');
+        let (Some(year), Some(month), Some(day), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return None;
+        }
+
+        let year: i64 = year.parse().ok()?;
+        let month: i64 = month.parse().ok()?;
+        let day: i64 = day.parse().ok()?;
+
+        if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+            return None;
+        }
+
+        Some(Self {
+            days: days_from_civil(year, month, day),
+        })
+    }
+
+    /// Current day in UTC
+    #[cfg(feature = "server")]
+    pub fn today() -> Self {
+        const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+        Self {
+            days: bson::DateTime::now()
+                .timestamp_millis()
+                .div_euclid(MILLIS_PER_DAY),
+        }
+    }
+
+    /// Day of the week, from 0 for monday to 6 for sunday
+    pub fn weekday(self) -> u8 {
+        // 1970-01-01 was a thursday
+        (self.days + 3).rem_euclid(7) as u8
+    }
+
+    /// Monday of the week of this day
+    pub fn monday(self) -> Self {
+        self.add_days(-i64::from(self.weekday()))
+    }
+
+    pub fn add_days(self, days: i64) -> Self {
+        Self {
+            days: self.days + days,
+        }
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.days);
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+impl TryFrom<String> for Date {
+    type Error = &'static str;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::parse(&text).ok_or("invalid date, expected YYYY-MM-DD")
+    }
+}
+
+impl From<Date> for String {
+    fn from(date: Date) -> Self {
+        date.to_string()
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a date, with the algorithm of Howard Hinnant
+/// where years start in march so that leap days are at their end
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Year, month and day of the date `days` after 1970-01-01, inverse of [`days_from_civil`]
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
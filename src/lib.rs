@@ -1,8 +1,13 @@
 pub mod app;
+pub mod config;
 mod cookies;
 pub mod database;
-mod model;
+pub mod date;
+pub mod model;
+pub mod quantity;
 mod reactive_vec;
+pub mod server;
+pub mod validation;
 
 #[cfg(feature = "client")]
 #[wasm_bindgen::prelude::wasm_bindgen]
@@ -1,24 +1,56 @@
 #[cfg(feature = "server")]
 #[actix_web::main]
 async fn main() {
-    use actix_files::Files;
+    use std::time::Duration;
+
     use actix_web::HttpServer;
-    use leptos::get_configuration;
-    use leptos_actix::{generate_route_list, LeptosRoutes};
-    use recipe_final_exercise::{app::App, database::Database};
+    use leptos::{get_configuration, logging::error};
+    use recipe_final_exercise::{config::Config, database::Database, server};
+
+    const TOKEN_PRUNING_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+    let config = match Config::from_env() {
+        Ok(ok) => ok,
+        Err(err) => {
+            eprintln!("Invalid configuration: {}", err);
+            std::process::exit(1);
+        }
+    };
+    config.init().unwrap();
 
     Database::setup().await.unwrap();
 
+    if let Some(admin) = &Config::get().admin {
+        let database = Database::configured().await.unwrap();
+        match database.promote_admin(admin).await {
+            Ok(true) => {}
+            Ok(false) => eprintln!("Can't promote {:?} to admin, no user has this name", admin),
+            Err(err) => error!("Failed to promote {:?} to admin, {:?}", admin, err),
+        }
+    }
+
+    actix_web::rt::spawn(async {
+        let mut interval = actix_web::rt::time::interval(TOKEN_PRUNING_PERIOD);
+        loop {
+            interval.tick().await;
+
+            let Ok(database) = Database::configured().await else {
+                continue;
+            };
+
+            if let Err(err) = database.prune_expired_tokens().await {
+                error!("Failed to prune expired tokens, {:?}", err);
+            }
+        }
+    });
+
     let config = get_configuration(None).await.unwrap().leptos_options;
 
     let adress = config.site_addr;
-    let routes = generate_route_list(App);
 
     HttpServer::new(move || {
-        actix_web::App::new()
-            .route("/api/{tail:.*}", leptos_actix::handle_server_fns())
-            .service(Files::new("pkg", [&config.site_root, "/pkg"].concat()))
-            .leptos_routes(config.clone(), routes.clone(), App)
+        let config = config.clone();
+        actix_web::App::new().configure(|service| server::configure(service, config, None))
     })
     .bind(&adress)
     .unwrap()
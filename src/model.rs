@@ -1,19 +1,44 @@
 #[cfg(feature = "server")]
-use bson::oid::ObjectId;
+use bson::{oid::ObjectId, DateTime};
+
+use std::ops::RangeInclusive;
 
-use bson::DateTime;
 use serde::{Deserialize, Serialize};
 
+use crate::quantity::Quantity;
+#[cfg(feature = "server")]
+use crate::{
+    date::Date,
+    quantity::{Amount, UnitSystem},
+};
+
+/// Number of stars a user can give to a recipe
+pub const NOTE_RANGE: RangeInclusive<u8> = 1..=5;
+/// Possible values of the price and healthy levels of a recipe
+pub const LEVEL_RANGE: RangeInclusive<u8> = 0..=4;
+/// Possible numbers of people a recipe serves
+pub const SERVINGS_RANGE: RangeInclusive<u8> = 1..=100;
+/// Servings of new recipes, and of the ones stored before recipes had servings
+pub const DEFAULT_SERVINGS: u8 = 4;
+
 #[cfg(feature = "server")]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct User {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
     pub name: String,
     pub password: String,
     pub admin: bool,
+    /// Banned users can still sign in but can no longer contribute
+    #[serde(default)]
+    pub banned: bool,
     pub tokens: Vec<Token>,
-    pub recipes: Vec<Recipe>,
+    /// System quantities are shown in, `None` to show them as written
+    #[serde(default)]
+    pub units: Option<UnitSystem>,
 }
 
+#[cfg(feature = "server")]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Token {
     pub token: String,
@@ -23,11 +48,17 @@ pub struct Token {
 #[cfg(feature = "server")]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Recipe {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub author: ObjectId,
     pub name: String,
     pub instructions: Vec<String>,
     pub icon_url: String,
     pub price_level: u8,
     pub healthy_level: u8,
+    /// Number of people served with the quantities of the ingredients
+    #[serde(default = "default_servings")]
+    pub servings: u8,
     pub comment: Vec<Comment>,
     pub notes: Vec<Note>,
     pub ingredients: Vec<Ingredient>,
@@ -35,9 +66,60 @@ pub struct Recipe {
     pub categories: Vec<ObjectId>,
 }
 
+#[cfg(feature = "server")]
+fn default_servings() -> u8 {
+    DEFAULT_SERVINGS
+}
+
+#[cfg(feature = "server")]
+impl Recipe {
+    /// Average of the notes, `None` if nobody rated the recipe
+    pub fn rating(&self) -> Option<f32> {
+        if self.notes.is_empty() {
+            return None;
+        }
+
+        let sum: u32 = self.notes.iter().map(|note| u32::from(note.note)).sum();
+        Some(sum as f32 / self.notes.len() as f32)
+    }
+}
+
+/// Fields of a [`Recipe`] shown in the recipe list
+#[cfg(feature = "server")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecipeSummary {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub author: ObjectId,
+    pub name: String,
+    pub icon_url: String,
+    /// Average of the notes, `None` if nobody rated the recipe
+    pub rating: Option<f64>,
+    pub rating_count: u32,
+}
+
+#[cfg(feature = "server")]
+impl From<&Recipe> for RecipeSummary {
+    fn from(recipe: &Recipe) -> Self {
+        let sum: u32 = recipe.notes.iter().map(|note| u32::from(note.note)).sum();
+
+        Self {
+            id: recipe.id,
+            author: recipe.author,
+            name: recipe.name.clone(),
+            icon_url: recipe.icon_url.clone(),
+            // Computed like the $avg of mongodb, so that cursors compare equal to it
+            rating: (!recipe.notes.is_empty()).then(|| f64::from(sum) / recipe.notes.len() as f64),
+            rating_count: u32::try_from(recipe.notes.len()).unwrap_or(u32::MAX),
+        }
+    }
+}
+
 #[cfg(feature = "server")]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Comment {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
     pub content: String,
     pub date: DateTime,
     pub user: ObjectId,
@@ -54,7 +136,7 @@ pub struct Note {
 pub struct Ingredient {
     pub name: String,
     pub icon_url: String,
-    pub quantity: String,
+    pub quantity: Quantity,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -63,8 +145,168 @@ pub struct Tool {
     pub icon_url: String,
 }
 
+/// Groceries of a user, filled from the ingredients of recipes
+#[cfg(feature = "server")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShoppingList {
+    #[serde(rename = "_id")]
+    pub user: ObjectId,
+    pub items: Vec<ShoppingItem>,
+    /// Number of times the list was stored, to detect concurrent changes, 0 if it never was
+    pub version: u64,
+}
+
+#[cfg(feature = "server")]
+impl ShoppingList {
+    pub fn new(user: ObjectId) -> Self {
+        Self {
+            user,
+            items: Vec::new(),
+            version: 0,
+        }
+    }
+
+    /// Add `factor` times the quantity of `ingredient`, to the item of the same name if any
+    ///
+    /// The item is unchecked since more of it must be bought
+    pub fn add(&mut self, ingredient: &Ingredient, factor: Amount) {
+        let key = ShoppingItem::key(&ingredient.name);
+        if key.is_empty() {
+            return;
+        }
+
+        let item = match self
+            .items
+            .iter()
+            .position(|item| ShoppingItem::key(&item.name) == key)
+        {
+            Some(index) => &mut self.items[index],
+            None => {
+                self.items.push(ShoppingItem {
+                    name: ingredient.name.trim().to_string(),
+                    icon_url: ingredient.icon_url.clone(),
+                    quantities: Vec::new(),
+                    checked: false,
+                });
+                self.items.last_mut().unwrap()
+            }
+        };
+
+        item.checked = false;
+
+        let quantity = ingredient.quantity.scaled(factor);
+        if quantity == Quantity::default() {
+            return;
+        }
+
+        match item
+            .quantities
+            .iter()
+            .enumerate()
+            .find_map(|(index, other)| Some((index, other.combined(&quantity)?)))
+        {
+            Some((index, sum)) => item.quantities[index] = sum,
+            None => item.quantities.push(quantity),
+        }
+    }
+
+    /// Plain text version of the list, one item per line
+    pub fn export(&self) -> String {
+        self.items
+            .iter()
+            .map(|item| {
+                let check = if item.checked { "[x]" } else { "[ ]" };
+                if item.quantities.is_empty() {
+                    format!("{} {}\n", check, item.name)
+                } else {
+                    format!("{} {}: {}\n", check, item.name, item.quantities_text())
+                }
+            })
+            .collect()
+    }
+}
+
+/// Ingredient to buy, with the quantities needed by all the recipes it was added from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ShoppingItem {
+    pub name: String,
+    pub icon_url: String,
+    /// Quantities that can't be summed together, like a mass and a volume
+    pub quantities: Vec<Quantity>,
+    pub checked: bool,
+}
+
+impl ShoppingItem {
+    /// Name identifying the item, ingredients with the same key are merged
+    pub fn key(name: &str) -> String {
+        name.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    }
+
+    /// Quantities joined like "500 g + 2 cups"
+    pub fn quantities_text(&self) -> String {
+        self.quantities
+            .iter()
+            .map(Quantity::to_string)
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
+
+/// Meals planned by a user on a day
+#[cfg(feature = "server")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MealPlan {
+    pub user: ObjectId,
+    pub date: Date,
+    /// At most one per meal of the day
+    pub meals: Vec<PlannedMeal>,
+    /// Number of times the plan was stored, to detect concurrent changes, 0 if it isn't stored
+    pub version: u64,
+}
+
+#[cfg(feature = "server")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlannedMeal {
+    pub meal: Meal,
+    pub recipe: ObjectId,
+    /// Number of people the recipe is cooked for
+    pub servings: u8,
+}
+
+/// Meal of the day a recipe can be planned for
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Meal {
+    Breakfast,
+    Lunch,
+    Dinner,
+}
+
+impl Meal {
+    pub const ALL: [Meal; 3] = [Meal::Breakfast, Meal::Lunch, Meal::Dinner];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Breakfast => "Breakfast",
+            Self::Lunch => "Lunch",
+            Self::Dinner => "Dinner",
+        }
+    }
+}
+
 #[cfg(feature = "server")]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Category {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
     pub name: String,
 }
+
+#[cfg(feature = "server")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Migration {
+    pub name: String,
+    pub date: DateTime,
+}
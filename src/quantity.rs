@@ -0,0 +1,692 @@
+//! Structured quantities of ingredients, and parsing of the free text
+//! quantities written by the authors
+
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::{Add, Mul},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Quantity of an ingredient
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub enum Quantity {
+    /// `amount` of `unit`, like "1 1/2 cups"
+    Measure { amount: Amount, unit: Unit },
+    /// As much as the cook likes, like salt and pepper
+    ToTaste,
+    /// Text that couldn't be understood, kept as the author wrote it
+    Raw { text: String },
+}
+
+impl Quantity {
+    /// Parse a free text quantity like "200g", "1 1/2 cups", "½ tsp", "3" or "to taste",
+    /// anything else is kept as [`Quantity::Raw`]
+    pub fn parse(text: &str) -> Self {
+        let trimmed = text.trim();
+
+        if trimmed.eq_ignore_ascii_case("to taste") {
+            return Self::ToTaste;
+        }
+
+        match parse_measure(trimmed) {
+            Some((amount, unit)) => Self::Measure { amount, unit },
+            None => Self::Raw {
+                text: text.to_string(),
+            },
+        }
+    }
+
+    /// Quantity for `factor` times as many servings, in the most readable unit of the same
+    /// system and rounded to what can be measured in a kitchen, like 16 tbsp to 1 cup
+    ///
+    /// Only measures change, and only if `factor` isn't 1 so the author's units are kept
+    pub fn scaled(&self, factor: Amount) -> Self {
+        match self {
+            Self::Measure { amount, unit } if factor != Amount::ONE => readable(
+                amount.to_f64() * factor.to_f64() * unit.factor(),
+                unit.ladder(),
+            ),
+            _ => self.clone(),
+        }
+    }
+
+    /// Sum of the two quantities, `None` if they can't be added, like a mass and a volume
+    ///
+    /// Measures in different units are summed in the most readable unit of the system
+    /// of `self`, like 1 cup and 4 tbsp to 1 1/4 cups
+    pub fn combined(&self, other: &Self) -> Option<Self> {
+        match (self, other) {
+            (Self::ToTaste, Self::ToTaste) => Some(Self::ToTaste),
+            (
+                Self::Measure { amount, unit },
+                Self::Measure {
+                    amount: other_amount,
+                    unit: other_unit,
+                },
+            ) if unit == other_unit => Some(Self::Measure {
+                amount: *amount + *other_amount,
+                unit: *unit,
+            }),
+            (
+                Self::Measure { amount, unit },
+                Self::Measure {
+                    amount: other_amount,
+                    unit: other_unit,
+                },
+            ) if unit.dimension() == other_unit.dimension() => Some(readable(
+                amount.to_f64() * unit.factor() + other_amount.to_f64() * other_unit.factor(),
+                unit.ladder(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Quantity expressed in the units of `system`
+    ///
+    /// With the `density` of the ingredient in g/ml, volumes become masses in metric
+    /// and masses become volumes in imperial, like bakers of each system measure them
+    pub fn converted(&self, system: UnitSystem, density: Option<f64>) -> Self {
+        let Self::Measure { amount, unit } = self else {
+            return self.clone();
+        };
+
+        if unit.system().is_none_or(|from| from == system) {
+            return self.clone();
+        }
+
+        let value = amount.to_f64() * unit.factor();
+        let (dimension, value) = match (unit.dimension(), system, density) {
+            (Dimension::Volume, UnitSystem::Metric, Some(density)) => {
+                (Dimension::Mass, value * density)
+            }
+            (Dimension::Mass, UnitSystem::Imperial, Some(density)) => {
+                (Dimension::Volume, value / density)
+            }
+            (dimension, _, _) => (dimension, value),
+        };
+
+        readable(value, ladder(dimension, Some(system)))
+    }
+}
+
+/// Density in g/ml of the ingredient named `name`, if it is usually weighed in metric
+/// and measured by volume in imperial
+///
+/// Liquids aren't listed since they are measured by volume in both systems.
+/// The name matches if it ends with the words of an entry, so "all-purpose flour"
+/// is some flour but "rice vinegar" isn't some rice
+pub fn density(name: &str) -> Option<f64> {
+    const DENSITIES: [(&str, f64); 16] = [
+        ("flour", 0.53),
+        ("sugar", 0.85),
+        ("brown sugar", 0.93),
+        ("powdered sugar", 0.51),
+        ("icing sugar", 0.51),
+        ("butter", 0.96),
+        ("rice", 0.85),
+        ("oats", 0.38),
+        ("cocoa", 0.42),
+        ("cocoa powder", 0.42),
+        ("salt", 1.22),
+        ("honey", 1.42),
+        ("cornstarch", 0.54),
+        ("baking powder", 0.81),
+        ("baking soda", 1.22),
+        ("chocolate chips", 0.72),
+    ];
+
+    let name = name.to_lowercase().replace('-', " ");
+    let words: Vec<_> = name.split_whitespace().collect();
+
+    // The longest entry is the most specific, brown sugar isn't plain sugar
+    DENSITIES
+        .iter()
+        .filter(|(entry, _)| {
+            let entry: Vec<_> = entry.split(' ').collect();
+            words.ends_with(&entry)
+        })
+        .max_by_key(|(entry, _)| entry.len())
+        .map(|(_, density)| *density)
+}
+
+impl Default for Quantity {
+    fn default() -> Self {
+        Self::Raw {
+            text: String::new(),
+        }
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Measure {
+                amount,
+                unit: Unit::Count,
+            } => write!(f, "{amount}"),
+            Self::Measure { amount, unit } if unit.is_metric() => {
+                write!(f, "{} {}", amount.to_decimal_string(), unit.symbol(amount))
+            }
+            Self::Measure { amount, unit } => write!(f, "{} {}", amount, unit.symbol(amount)),
+            Self::ToTaste => write!(f, "to taste"),
+            Self::Raw { text } => write!(f, "{text}"),
+        }
+    }
+}
+
+/// System of units the quantities can be converted to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitSystem {
+    Metric,
+    Imperial,
+}
+
+/// Kind of physical quantity measured by a [`Unit`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Mass,
+    Volume,
+    Count,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Milligram,
+    Gram,
+    Kilogram,
+    Ounce,
+    Pound,
+    Milliliter,
+    Centiliter,
+    Deciliter,
+    Liter,
+    Teaspoon,
+    Tablespoon,
+    FluidOunce,
+    Cup,
+    Pint,
+    Quart,
+    Gallon,
+    /// Number of items, like 3 eggs
+    Count,
+}
+
+impl Unit {
+    pub fn dimension(self) -> Dimension {
+        match self {
+            Self::Milligram | Self::Gram | Self::Kilogram | Self::Ounce | Self::Pound => {
+                Dimension::Mass
+            }
+            Self::Milliliter
+            | Self::Centiliter
+            | Self::Deciliter
+            | Self::Liter
+            | Self::Teaspoon
+            | Self::Tablespoon
+            | Self::FluidOunce
+            | Self::Cup
+            | Self::Pint
+            | Self::Quart
+            | Self::Gallon => Dimension::Volume,
+            Self::Count => Dimension::Count,
+        }
+    }
+
+    /// Size of the unit, in grams for masses, milliliters for volumes and items for counts
+    pub fn factor(self) -> f64 {
+        match self {
+            Self::Milligram => 0.001,
+            Self::Gram => 1.0,
+            Self::Kilogram => 1000.0,
+            Self::Ounce => 28.349523125,
+            Self::Pound => 453.59237,
+            Self::Milliliter => 1.0,
+            Self::Centiliter => 10.0,
+            Self::Deciliter => 100.0,
+            Self::Liter => 1000.0,
+            Self::Teaspoon => 4.92892159375,
+            Self::Tablespoon => 14.78676478125,
+            Self::FluidOunce => 29.5735295625,
+            Self::Cup => 236.5882365,
+            Self::Pint => 473.176473,
+            Self::Quart => 946.352946,
+            Self::Gallon => 3785.411784,
+            Self::Count => 1.0,
+        }
+    }
+
+    /// Units amounts of this unit are shown in once scaled, see [`readable`]
+    fn ladder(self) -> &'static [(Unit, f64)] {
+        ladder(self.dimension(), self.system())
+    }
+
+    /// System the unit belongs to, `None` for counts which are the same in every system
+    pub fn system(self) -> Option<UnitSystem> {
+        match self {
+            Self::Count => None,
+            _ if self.is_metric() => Some(UnitSystem::Metric),
+            _ => Some(UnitSystem::Imperial),
+        }
+    }
+
+    /// Denominators of the fractions `value` of this unit can be rounded to
+    fn steps(self, value: f64) -> &'static [u32] {
+        match self {
+            _ if self.is_metric() && value >= 10.0 => &[1],
+            _ if self.is_metric() => &[10],
+            Self::Teaspoon => &[8],
+            Self::Tablespoon | Self::Count => &[2],
+            Self::Cup => &[3, 4],
+            _ => &[4],
+        }
+    }
+
+    /// Whether the unit belongs to the metric system, amounts are then shown as decimals
+    pub fn is_metric(self) -> bool {
+        matches!(
+            self,
+            Self::Milligram
+                | Self::Gram
+                | Self::Kilogram
+                | Self::Milliliter
+                | Self::Centiliter
+                | Self::Deciliter
+                | Self::Liter
+        )
+    }
+
+    /// Unit written as `text`, ignoring case, an empty text is a [`Unit::Count`]
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().trim_end_matches('.').to_lowercase();
+
+        Some(match text.as_str() {
+            "" | "x" | "pc" | "pcs" | "piece" | "pieces" => Self::Count,
+            "mg" | "milligram" | "milligrams" | "milligramme" | "milligrammes" => Self::Milligram,
+            "g" | "gr" | "gram" | "grams" | "gramme" | "grammes" => Self::Gram,
+            "kg" | "kilo" | "kilos" | "kilogram" | "kilograms" | "kilogramme" | "kilogrammes" => {
+                Self::Kilogram
+            }
+            "oz" | "ounce" | "ounces" => Self::Ounce,
+            "lb" | "lbs" | "pound" | "pounds" => Self::Pound,
+            "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => Self::Milliliter,
+            "cl" | "centiliter" | "centiliters" | "centilitre" | "centilitres" => Self::Centiliter,
+            "dl" | "deciliter" | "deciliters" | "decilitre" | "decilitres" => Self::Deciliter,
+            "l" | "liter" | "liters" | "litre" | "litres" => Self::Liter,
+            "tsp" | "tsps" | "teaspoon" | "teaspoons" => Self::Teaspoon,
+            "tbsp" | "tbsps" | "tbs" | "tablespoon" | "tablespoons" => Self::Tablespoon,
+            "fl oz" | "fl. oz" | "floz" | "fluid ounce" | "fluid ounces" => Self::FluidOunce,
+            "cup" | "cups" => Self::Cup,
+            "pt" | "pint" | "pints" => Self::Pint,
+            "qt" | "quart" | "quarts" => Self::Quart,
+            "gal" | "gallon" | "gallons" => Self::Gallon,
+            _ => return None,
+        })
+    }
+
+    /// Name of the unit shown after `amount`
+    pub fn symbol(self, amount: &Amount) -> &'static str {
+        let plural = *amount > Amount::ONE;
+
+        match self {
+            Self::Milligram => "mg",
+            Self::Gram => "g",
+            Self::Kilogram => "kg",
+            Self::Ounce => "oz",
+            Self::Pound => "lb",
+            Self::Milliliter => "ml",
+            Self::Centiliter => "cl",
+            Self::Deciliter => "dl",
+            Self::Liter => "l",
+            Self::Teaspoon => "tsp",
+            Self::Tablespoon => "tbsp",
+            Self::FluidOunce => "fl oz",
+            Self::Cup if plural => "cups",
+            Self::Cup => "cup",
+            Self::Pint if plural => "pints",
+            Self::Pint => "pint",
+            Self::Quart if plural => "quarts",
+            Self::Quart => "quart",
+            Self::Gallon if plural => "gallons",
+            Self::Gallon => "gallon",
+            Self::Count => "",
+        }
+    }
+}
+
+/// Non negative rational number, always stored as an irreducible fraction
+///
+/// Stored as a `[numerator, denominator]` pair
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "(u32, u32)", into = "(u32, u32)")]
+pub struct Amount {
+    numerator: u32,
+    denominator: u32,
+}
+
+impl Amount {
+    pub const ZERO: Self = Self {
+        numerator: 0,
+        denominator: 1,
+    };
+    pub const ONE: Self = Self {
+        numerator: 1,
+        denominator: 1,
+    };
+
+    /// Denominators small enough to be shown as a fraction
+    const FRACTION_DENOMINATORS: [u32; 4] = [2, 3, 4, 8];
+
+    /// `numerator / denominator`, `None` if `denominator` is 0
+    ///
+    /// Fractions too precise to be stored are rounded to the nearest thousandth
+    pub fn new(numerator: u64, denominator: u64) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+
+        Some(Self::reduced(
+            u128::from(numerator),
+            u128::from(denominator),
+        ))
+    }
+
+    /// Irreducible form of `numerator / denominator`, `denominator` must not be 0
+    fn reduced(numerator: u128, denominator: u128) -> Self {
+        let divisor = gcd(numerator, denominator);
+        let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+
+        match (u32::try_from(numerator), u32::try_from(denominator)) {
+            (Ok(numerator), Ok(denominator)) => Self {
+                numerator,
+                denominator,
+            },
+            _ => Self::approximate(numerator as f64 / denominator as f64),
+        }
+    }
+
+    pub fn integer(value: u32) -> Self {
+        Self {
+            numerator: value,
+            denominator: 1,
+        }
+    }
+
+    /// Fraction closest to `value` with a denominator of at most 1000, negative values are 0
+    pub fn approximate(value: f64) -> Self {
+        const PRECISION: f64 = 1000.0;
+
+        if value.is_nan() || value <= 0.0 {
+            return Self::ZERO;
+        }
+
+        let thousandths = (value * PRECISION).round().min(f64::from(u32::MAX));
+        Self::reduced(thousandths as u128, PRECISION as u128)
+    }
+
+    pub fn numerator(&self) -> u32 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> u32 {
+        self.denominator
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator == 0
+    }
+
+    pub fn to_f64(self) -> f64 {
+        f64::from(self.numerator) / f64::from(self.denominator)
+    }
+
+    /// The amount as a decimal number with at most 2 decimals, like "1.5"
+    pub fn to_decimal_string(&self) -> String {
+        if self.denominator == 1 {
+            return self.numerator.to_string();
+        }
+
+        let text = format!("{:.2}", self.to_f64());
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+impl TryFrom<(u32, u32)> for Amount {
+    type Error = &'static str;
+
+    fn try_from((numerator, denominator): (u32, u32)) -> Result<Self, Self::Error> {
+        Self::new(u64::from(numerator), u64::from(denominator)).ok_or("Zero denominator")
+    }
+}
+
+impl From<Amount> for (u32, u32) {
+    fn from(amount: Amount) -> Self {
+        (amount.numerator, amount.denominator)
+    }
+}
+
+impl PartialOrd for Amount {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Amount {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let left = u64::from(self.numerator) * u64::from(other.denominator);
+        let right = u64::from(other.numerator) * u64::from(self.denominator);
+        left.cmp(&right)
+    }
+}
+
+impl Add for Amount {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (a, b) = (u128::from(self.numerator), u128::from(self.denominator));
+        let (c, d) = (u128::from(other.numerator), u128::from(other.denominator));
+        Self::reduced(a * d + c * b, b * d)
+    }
+}
+
+impl Mul for Amount {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::reduced(
+            u128::from(self.numerator) * u128::from(other.numerator),
+            u128::from(self.denominator) * u128::from(other.denominator),
+        )
+    }
+}
+
+/// Shown as a mixed fraction like "1 1/2" when the denominator is small,
+/// as a decimal number otherwise
+impl Display for Amount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !Self::FRACTION_DENOMINATORS.contains(&self.denominator) {
+            return write!(f, "{}", self.to_decimal_string());
+        }
+
+        let whole = self.numerator / self.denominator;
+        let rest = self.numerator % self.denominator;
+
+        if whole == 0 {
+            write!(f, "{}/{}", rest, self.denominator)
+        } else {
+            write!(f, "{} {}/{}", whole, rest, self.denominator)
+        }
+    }
+}
+
+/// Units of a dimension and system from the smallest to the largest, with the smallest
+/// amount of each unit worth showing in it, a quarter cup is nicer than 4 tablespoons
+const METRIC_MASS: &[(Unit, f64)] = &[
+    (Unit::Milligram, 0.0),
+    (Unit::Gram, 1.0),
+    (Unit::Kilogram, 1.0),
+];
+const IMPERIAL_MASS: &[(Unit, f64)] = &[(Unit::Ounce, 0.0), (Unit::Pound, 1.0)];
+const METRIC_VOLUME: &[(Unit, f64)] = &[(Unit::Milliliter, 0.0), (Unit::Liter, 1.0)];
+const IMPERIAL_VOLUME: &[(Unit, f64)] = &[
+    (Unit::Teaspoon, 0.0),
+    (Unit::Tablespoon, 1.0),
+    (Unit::Cup, 0.25),
+    (Unit::Quart, 1.0),
+    (Unit::Gallon, 1.0),
+];
+
+/// Units of `dimension` in `system`, see [`readable`]
+fn ladder(dimension: Dimension, system: Option<UnitSystem>) -> &'static [(Unit, f64)] {
+    match (dimension, system) {
+        (Dimension::Mass, Some(UnitSystem::Metric)) => METRIC_MASS,
+        (Dimension::Mass, _) => IMPERIAL_MASS,
+        (Dimension::Volume, Some(UnitSystem::Metric)) => METRIC_VOLUME,
+        (Dimension::Volume, _) => IMPERIAL_VOLUME,
+        (Dimension::Count, _) => &[(Unit::Count, 0.0)],
+    }
+}
+
+/// `value` in the base unit of [`Unit::factor`], expressed in the largest unit of `ladder`
+/// it is worth showing in, and rounded to the steps of this unit
+fn readable(value: f64, ladder: &[(Unit, f64)]) -> Quantity {
+    // Conversions between units aren't exact, 3 tsp must still reach 1 tbsp
+    const EPSILON: f64 = 1e-9;
+
+    let (unit, value) = ladder
+        .iter()
+        .rev()
+        .map(|(unit, min)| (*unit, value / unit.factor(), *min))
+        .find(|(_, value, min)| *value >= min - EPSILON)
+        .map(|(unit, value, _)| (unit, value))
+        .unwrap_or((ladder[0].0, value / ladder[0].0.factor()));
+
+    Quantity::Measure {
+        amount: round_to(value, unit.steps(value)),
+        unit,
+    }
+}
+
+/// Fraction closest to `value` with one of `denominators`, the first one on ties
+///
+/// Positive values are never rounded to 0, the ingredient would disappear
+fn round_to(value: f64, denominators: &[u32]) -> Amount {
+    if value.is_nan() || value <= 0.0 {
+        return Amount::ZERO;
+    }
+
+    denominators
+        .iter()
+        .map(|denominator| {
+            let numerator = (value * f64::from(*denominator)).round().max(1.0);
+            Amount::new(numerator as u64, u64::from(*denominator)).unwrap_or(Amount::ZERO)
+        })
+        .min_by(|a, b| {
+            (a.to_f64() - value)
+                .abs()
+                .total_cmp(&(b.to_f64() - value).abs())
+        })
+        .unwrap_or(Amount::ZERO)
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.max(1)
+}
+
+/// Amount followed by a unit, like "200g" or "1 1/2 cups"
+fn parse_measure(text: &str) -> Option<(Amount, Unit)> {
+    let (amount, rest) = parse_amount(text)?;
+    let unit = Unit::parse(rest)?;
+    Some((amount, unit))
+}
+
+/// Amount at the start of `text`, with the rest of the text
+fn parse_amount(text: &str) -> Option<(Amount, &str)> {
+    // Only a fraction, like "½ cup" or "1/2 cup"
+    if let Some(fraction) = parse_vulgar_fraction(text) {
+        return Some(fraction);
+    }
+
+    let (whole, rest) = parse_integer(text)?;
+
+    // Decimal number, like "1.5" or "1,5"
+    if let Some(decimals) = rest.strip_prefix(['.', ',']) {
+        let digits = decimals.len()
+            - decimals
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        let (fraction, rest) = parse_integer(decimals)?;
+        let scale = 10u64.checked_pow(u32::try_from(digits).ok()?)?;
+        let amount = Amount::new(whole.checked_mul(scale)?.checked_add(fraction)?, scale)?;
+        return Some((amount, rest));
+    }
+
+    // Fraction, like "1/2"
+    if let Some(denominator) = rest.strip_prefix('/') {
+        let (denominator, rest) = parse_integer(denominator)?;
+        return Some((Amount::new(whole, denominator)?, rest));
+    }
+
+    let whole = Amount::new(whole, 1)?;
+
+    // Mixed number, like "1 1/2" or "1½"
+    let after_space = rest.trim_start();
+    if let Some((fraction, rest)) = parse_vulgar_fraction(after_space) {
+        return Some((whole + fraction, rest));
+    }
+    if let Some((numerator, fraction_rest)) = parse_integer(after_space) {
+        if let Some(denominator) = fraction_rest.strip_prefix('/') {
+            let (denominator, rest) = parse_integer(denominator)?;
+            return Some((whole + Amount::new(numerator, denominator)?, rest));
+        }
+    }
+
+    Some((whole, rest))
+}
+
+/// Integer at the start of `text`, small enough to not overflow computations
+fn parse_integer(text: &str) -> Option<(u64, &str)> {
+    const MAX_DIGITS: usize = 9;
+
+    let rest = text.trim_start_matches(|c: char| c.is_ascii_digit());
+    let digits = &text[..text.len() - rest.len()];
+
+    if digits.is_empty() || digits.len() > MAX_DIGITS {
+        return None;
+    }
+
+    Some((digits.parse().ok()?, rest))
+}
+
+/// Single character fraction at the start of `text`, like "½"
+fn parse_vulgar_fraction(text: &str) -> Option<(Amount, &str)> {
+    const FRACTIONS: [(char, u64, u64); 15] = [
+        ('½', 1, 2),
+        ('⅓', 1, 3),
+        ('⅔', 2, 3),
+        ('¼', 1, 4),
+        ('¾', 3, 4),
+        ('⅕', 1, 5),
+        ('⅖', 2, 5),
+        ('⅗', 3, 5),
+        ('⅘', 4, 5),
+        ('⅙', 1, 6),
+        ('⅚', 5, 6),
+        ('⅛', 1, 8),
+        ('⅜', 3, 8),
+        ('⅝', 5, 8),
+        ('⅞', 7, 8),
+    ];
+
+    let first = text.chars().next()?;
+    let (_, numerator, denominator) = FRACTIONS.iter().find(|(c, _, _)| *c == first)?;
+
+    Some((
+        Amount::new(*numerator, *denominator)?,
+        &text[first.len_utf8()..],
+    ))
+}
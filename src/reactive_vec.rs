@@ -1,5 +1,6 @@
 use leptos::{
     create_rw_signal, RwSignal, SignalGet, SignalGetUntracked, SignalUpdate, SignalUpdateUntracked,
+    SignalWith,
 };
 
 #[derive(Clone)]
@@ -15,10 +16,24 @@ impl<T: 'static + Clone> ReactiveVec<T> {
         }
     }
 
+    pub fn from_values(values: Vec<T>) -> Self {
+        let reactive_vec = Self::new();
+        for value in values {
+            reactive_vec.push(value);
+        }
+        reactive_vec
+    }
+
     pub fn get(&self) -> Vec<(usize, RwSignal<T>)> {
         self.vec.get()
     }
 
+    /// Position of the element `id` in the vec, `None` if it isn't in the vec
+    pub fn index_of(&self, id: usize) -> Option<usize> {
+        self.vec
+            .with(|vec| vec.iter().position(|(other, _)| *other == id))
+    }
+
     pub fn get_values_untracked(&self) -> Vec<T> {
         self.vec
             .get_untracked()
@@ -0,0 +1,31 @@
+#![cfg(feature = "server")]
+
+use actix_files::Files;
+use actix_web::web::ServiceConfig;
+use leptos::{provide_context, LeptosOptions};
+use leptos_actix::{generate_route_list, LeptosRoutes};
+
+use crate::{app::App, database::Database};
+
+/// Register the server functions, the static files and the pages of the app
+///
+/// Server functions and pages use `database` if provided,
+/// otherwise the one of the storage backend selected in the config
+pub fn configure(service: &mut ServiceConfig, options: LeptosOptions, database: Option<Database>) {
+    let routes = generate_route_list(App);
+
+    let additional_context = move || {
+        if let Some(database) = &database {
+            provide_context(database.clone());
+        }
+    };
+
+    service
+        .route(
+            "/api/{tail:.*}",
+            leptos_actix::handle_server_fns_with_context(additional_context.clone()),
+        )
+        .service(Files::new("pkg", [&options.site_root, "/pkg"].concat()));
+
+    service.leptos_routes_with_context(options, routes, additional_context, App);
+}
@@ -0,0 +1,260 @@
+//! Checks of the user inputs, shared by the forms and the server functions
+//! so that the client shows the same errors the server would return
+
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    model::{Ingredient, Tool, LEVEL_RANGE, SERVINGS_RANGE},
+    quantity::Quantity,
+};
+
+/// Maximum number of characters of the names of recipes, ingredients, tools and categories
+pub const NAME_MAX_LENGTH: usize = 100;
+pub const URL_MAX_LENGTH: usize = 2048;
+pub const INSTRUCTION_MAX_LENGTH: usize = 2000;
+pub const QUANTITY_MAX_LENGTH: usize = 50;
+pub const COMMENT_MAX_LENGTH: usize = 2000;
+/// Maximum number of instructions, ingredients or tools of a recipe
+pub const MAX_ENTRIES: usize = 100;
+
+/// Field of a recipe or of a comment, entries of lists are identified by their index
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipeField {
+    Name,
+    IconUrl,
+    PriceLevel,
+    HealthyLevel,
+    Servings,
+    Instructions,
+    Instruction(usize),
+    Ingredients,
+    IngredientName(usize),
+    IngredientIconUrl(usize),
+    IngredientQuantity(usize),
+    Tools,
+    ToolName(usize),
+    ToolIconUrl(usize),
+    /// Content of a comment of the recipe
+    Comment,
+}
+
+/// Reason why the value of a field is rejected,
+/// the messages are shown next to the field so they don't name it
+#[derive(Error, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("Required")]
+    Required(RecipeField),
+    #[error("At most {max} characters")]
+    TooLong { field: RecipeField, max: usize },
+    #[error("At most {max} entries")]
+    TooMany { field: RecipeField, max: usize },
+    #[error("Must be between {min} and {max}")]
+    OutOfRange {
+        field: RecipeField,
+        min: u8,
+        max: u8,
+    },
+    #[error("Must be an http or https url")]
+    InvalidUrl(RecipeField),
+}
+
+impl ValidationError {
+    pub fn field(&self) -> RecipeField {
+        match self {
+            Self::Required(field)
+            | Self::TooLong { field, .. }
+            | Self::TooMany { field, .. }
+            | Self::OutOfRange { field, .. }
+            | Self::InvalidUrl(field) => *field,
+        }
+    }
+}
+
+/// Fields of a recipe to validate
+pub struct RecipeDraft<'a> {
+    pub name: &'a str,
+    pub icon_url: &'a str,
+    pub price_level: u8,
+    pub healthy_level: u8,
+    pub servings: u8,
+    pub instructions: &'a [String],
+    pub ingredients: &'a [Ingredient],
+    pub tools: &'a [Tool],
+}
+
+/// Every problem of the fields of `recipe`, empty if the recipe is valid
+pub fn validate_recipe(recipe: &RecipeDraft) -> Vec<ValidationError> {
+    let RecipeDraft {
+        name,
+        icon_url,
+        price_level,
+        healthy_level,
+        servings,
+        instructions,
+        ingredients,
+        tools,
+    } = *recipe;
+
+    let mut errors = Vec::new();
+
+    required_text(&mut errors, RecipeField::Name, name, NAME_MAX_LENGTH);
+    optional_url(&mut errors, RecipeField::IconUrl, icon_url);
+    in_range(
+        &mut errors,
+        RecipeField::PriceLevel,
+        price_level,
+        LEVEL_RANGE,
+    );
+    in_range(
+        &mut errors,
+        RecipeField::HealthyLevel,
+        healthy_level,
+        LEVEL_RANGE,
+    );
+    in_range(&mut errors, RecipeField::Servings, servings, SERVINGS_RANGE);
+
+    entries(&mut errors, RecipeField::Instructions, instructions.len());
+    for (i, instruction) in instructions.iter().enumerate() {
+        required_text(
+            &mut errors,
+            RecipeField::Instruction(i),
+            instruction,
+            INSTRUCTION_MAX_LENGTH,
+        );
+    }
+
+    entries(&mut errors, RecipeField::Ingredients, ingredients.len());
+    for (i, ingredient) in ingredients.iter().enumerate() {
+        required_text(
+            &mut errors,
+            RecipeField::IngredientName(i),
+            &ingredient.name,
+            NAME_MAX_LENGTH,
+        );
+        optional_url(
+            &mut errors,
+            RecipeField::IngredientIconUrl(i),
+            &ingredient.icon_url,
+        );
+        // Only the unparsed quantities can be arbitrarily long
+        if let Quantity::Raw { text } = &ingredient.quantity {
+            max_length(
+                &mut errors,
+                RecipeField::IngredientQuantity(i),
+                text,
+                QUANTITY_MAX_LENGTH,
+            );
+        }
+    }
+
+    entries(&mut errors, RecipeField::Tools, tools.len());
+    for (i, tool) in tools.iter().enumerate() {
+        required_text(
+            &mut errors,
+            RecipeField::ToolName(i),
+            &tool.name,
+            NAME_MAX_LENGTH,
+        );
+        optional_url(&mut errors, RecipeField::ToolIconUrl(i), &tool.icon_url);
+    }
+
+    errors
+}
+
+/// Characters a user name can't contain: `%` would make the profile path decoded differently
+/// after a client side navigation, which already decodes the path, and `/` splits the path
+pub const USER_NAME_FORBIDDEN_CHARS: [char; 2] = ['%', '/'];
+
+/// Whether `name` can be the name of a new user
+pub fn is_valid_user_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(USER_NAME_FORBIDDEN_CHARS)
+}
+
+/// Whether `name`, once trimmed, is a valid category name
+pub fn is_valid_category_name(name: &str) -> bool {
+    let name = name.trim();
+    !name.is_empty() && name.chars().count() <= NAME_MAX_LENGTH
+}
+
+/// Problem of the content of a comment, `None` if it is valid
+pub fn validate_comment(content: &str) -> Option<ValidationError> {
+    let mut errors = Vec::new();
+    required_text(
+        &mut errors,
+        RecipeField::Comment,
+        content.trim(),
+        COMMENT_MAX_LENGTH,
+    );
+    errors.pop()
+}
+
+fn required_text(errors: &mut Vec<ValidationError>, field: RecipeField, value: &str, max: usize) {
+    if value.trim().is_empty() {
+        errors.push(ValidationError::Required(field));
+    } else {
+        max_length(errors, field, value, max);
+    }
+}
+
+fn max_length(errors: &mut Vec<ValidationError>, field: RecipeField, value: &str, max: usize) {
+    if value.chars().count() > max {
+        errors.push(ValidationError::TooLong { field, max });
+    }
+}
+
+/// An empty url is accepted, the icon is then simply not shown
+fn optional_url(errors: &mut Vec<ValidationError>, field: RecipeField, value: &str) {
+    if value.is_empty() {
+        return;
+    }
+
+    if value.chars().count() > URL_MAX_LENGTH {
+        errors.push(ValidationError::TooLong {
+            field,
+            max: URL_MAX_LENGTH,
+        });
+    } else if !is_url(value) {
+        errors.push(ValidationError::InvalidUrl(field));
+    }
+}
+
+fn in_range(
+    errors: &mut Vec<ValidationError>,
+    field: RecipeField,
+    value: u8,
+    range: RangeInclusive<u8>,
+) {
+    if !range.contains(&value) {
+        errors.push(ValidationError::OutOfRange {
+            field,
+            min: *range.start(),
+            max: *range.end(),
+        });
+    }
+}
+
+fn entries(errors: &mut Vec<ValidationError>, field: RecipeField, count: usize) {
+    if count > MAX_ENTRIES {
+        errors.push(ValidationError::TooMany {
+            field,
+            max: MAX_ENTRIES,
+        });
+    }
+}
+
+/// Whether `value` is an absolute http or https url with a host
+fn is_url(value: &str) -> bool {
+    let Some(rest) = value
+        .strip_prefix("https://")
+        .or_else(|| value.strip_prefix("http://"))
+    else {
+        return false;
+    };
+
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+
+    !host.is_empty() && !value.chars().any(|c| c.is_whitespace() || c.is_control())
+}
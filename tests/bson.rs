@@ -0,0 +1,185 @@
+#![cfg(feature = "server")]
+
+use bson::{bson, doc, from_document, oid::ObjectId, to_document, DateTime, Document};
+use serde::{de::DeserializeOwned, Serialize};
+
+use recipe_final_exercise::{
+    date::Date,
+    model::{
+        Comment, Ingredient, Meal, MealPlan, Note, PlannedMeal, Recipe, ShoppingItem, ShoppingList,
+        Token, Tool, User,
+    },
+    quantity::{Amount, Quantity, Unit, UnitSystem},
+};
+
+/// Store `value` like mongodb does and read it back, checking that nothing changed on the way
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> (Document, T) {
+    let document = to_document(value).unwrap();
+    let read: T = from_document(document.clone()).unwrap();
+    assert_eq!(to_document(&read).unwrap(), document);
+    (document, read)
+}
+
+fn ingredient(name: &str, quantity: Quantity) -> Ingredient {
+    Ingredient {
+        name: name.to_string(),
+        icon_url: String::new(),
+        quantity,
+    }
+}
+
+#[test]
+fn quantities_are_stored_with_amounts_as_pairs() {
+    let measure = Quantity::parse("1 1/2 cups");
+    let (document, read) = round_trip(&ingredient("Milk", measure.clone()));
+    assert_eq!(read.quantity, measure);
+    assert_eq!(
+        document.get_document("quantity").unwrap(),
+        &doc! { "kind": "Measure", "amount": [3_i64, 2_i64], "unit": "Cup" }
+    );
+
+    for quantity in [Quantity::ToTaste, Quantity::parse("a pinch")] {
+        assert_eq!(
+            round_trip(&ingredient("Salt", quantity.clone())).1.quantity,
+            quantity
+        );
+    }
+}
+
+#[test]
+fn amounts_are_read_whatever_the_integer_type() {
+    // Documents written by other tools may store small integers as 32 bits
+    let document = doc! { "name": "Flour", "icon_url": "", "quantity": {
+        "kind": "Measure", "amount": [250_i32, 1_i32], "unit": "Gram",
+    }};
+    let ingredient: Ingredient = from_document(document).unwrap();
+    assert_eq!(ingredient.quantity, Quantity::parse("250 g"));
+
+    // Amounts are kept irreducible
+    let document = doc! { "kind": "Measure", "amount": [2_i64, 4_i64], "unit": "Cup" };
+    let quantity: Quantity = from_document(document).unwrap();
+    assert_eq!(quantity, Quantity::parse("1/2 cup"));
+    let document = doc! { "kind": "Measure", "amount": [1_i64, 0_i64], "unit": "Cup" };
+    assert!(from_document::<Quantity>(document).is_err());
+}
+
+#[test]
+fn recipes_round_trip() {
+    let recipe = Recipe {
+        id: ObjectId::new(),
+        author: ObjectId::new(),
+        name: "Pancakes".to_string(),
+        instructions: vec!["Mix".to_string(), "Cook".to_string()],
+        icon_url: "https://example.com/pancakes.png".to_string(),
+        price_level: 1,
+        healthy_level: 2,
+        servings: 4,
+        comment: vec![Comment {
+            id: ObjectId::new(),
+            content: "Great".to_string(),
+            date: DateTime::now(),
+            user: ObjectId::new(),
+        }],
+        notes: vec![Note {
+            note: 5,
+            user: ObjectId::new(),
+        }],
+        ingredients: vec![
+            ingredient("Flour", Quantity::parse("250 g")),
+            ingredient("Sugar", Quantity::ToTaste),
+        ],
+        tools: vec![Tool {
+            name: "Pan".to_string(),
+            icon_url: String::new(),
+        }],
+        categories: vec![ObjectId::new()],
+    };
+
+    let (document, read) = round_trip(&recipe);
+    assert_eq!(document.get_object_id("_id").unwrap(), recipe.id);
+    assert_eq!(read.ingredients[0].quantity, recipe.ingredients[0].quantity);
+    assert_eq!(read.comment[0].date, recipe.comment[0].date);
+
+    // Recipes stored before they had servings
+    let mut document = document;
+    document.remove("servings");
+    assert_eq!(from_document::<Recipe>(document).unwrap().servings, 4);
+}
+
+#[test]
+fn users_round_trip() {
+    let user = User {
+        id: ObjectId::new(),
+        name: "alice".to_string(),
+        password: "hash".to_string(),
+        admin: false,
+        banned: true,
+        tokens: vec![Token {
+            token: "token hash".to_string(),
+            expiration: DateTime::now(),
+        }],
+        units: Some(UnitSystem::Imperial),
+    };
+
+    let (document, read) = round_trip(&user);
+    assert_eq!(document.get_str("name").unwrap(), "alice");
+    assert_eq!(read.tokens, user.tokens);
+    assert_eq!(read.units, Some(UnitSystem::Imperial));
+
+    // Users stored before they could be banned or choose their units
+    let mut document = document;
+    document.remove("banned");
+    document.remove("units");
+    let read: User = from_document(document).unwrap();
+    assert!(!read.banned);
+    assert_eq!(read.units, None);
+}
+
+#[test]
+fn shopping_lists_and_meal_plans_round_trip() {
+    let list = ShoppingList {
+        user: ObjectId::new(),
+        items: vec![ShoppingItem {
+            name: "Milk".to_string(),
+            icon_url: String::new(),
+            quantities: vec![Quantity::parse("1 1/4 cups"), Quantity::parse("1 l")],
+            checked: true,
+        }],
+        version: 3,
+    };
+    let (document, read) = round_trip(&list);
+    assert_eq!(document.get_object_id("_id").unwrap(), list.user);
+    assert_eq!(document.get("version"), Some(&bson!(3_i64)));
+    assert_eq!(read.items, list.items);
+
+    let plan = MealPlan {
+        user: ObjectId::new(),
+        date: Date::parse("2024-02-29").unwrap(),
+        meals: vec![PlannedMeal {
+            meal: Meal::Dinner,
+            recipe: ObjectId::new(),
+            servings: 6,
+        }],
+        version: 1,
+    };
+    let (document, read) = round_trip(&plan);
+    // Dates are stored as text so that they sort and compare like dates
+    assert_eq!(document.get_str("date").unwrap(), "2024-02-29");
+    assert_eq!(read.date, plan.date);
+    assert_eq!(read.meals[0].meal, Meal::Dinner);
+
+    let mut document = document;
+    document.insert("date", "2024-02-30");
+    assert!(from_document::<MealPlan>(document).is_err());
+}
+
+#[test]
+fn units_are_stored_by_name() {
+    let quantity = Quantity::Measure {
+        amount: Amount::integer(2),
+        unit: Unit::Tablespoon,
+    };
+    let document = to_document(&quantity).unwrap();
+    assert_eq!(document.get_str("unit").unwrap(), "Tablespoon");
+    assert_eq!(from_document::<Quantity>(document).unwrap(), quantity);
+}
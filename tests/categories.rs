@@ -0,0 +1,320 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use leptos::ServerFnError;
+use recipe_final_exercise::{
+    app::{
+        categories::{
+            CategoryChange, CategoryInfo, DeleteCategory, GetCategories, GetCategory, NewCategory,
+            RenameCategory,
+        },
+        recipe::{GetRecipe, RecipeChange},
+        recipes::RecipeFilter,
+    },
+    database::store::RecipeStore,
+    validation::NAME_MAX_LENGTH,
+};
+
+use common::{
+    client, get_recipes, insert_admin, new_recipe, signin, signup, test_store, FailingStore,
+};
+
+fn new_category(name: &str) -> NewCategory {
+    NewCategory {
+        name: name.to_string(),
+    }
+}
+
+fn names(categories: &[CategoryInfo]) -> Vec<&str> {
+    categories
+        .iter()
+        .map(|category| category.name.as_str())
+        .collect()
+}
+
+/// Store with an admin, returns the store and the id of the categories created by the admin
+async fn categories(names: &[&str]) -> (Arc<dyn RecipeStore>, Vec<String>) {
+    let store = test_store().await;
+    insert_admin(&store, "admin", "password1").await;
+    let mut admin = client(store.clone()).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+
+    for name in names {
+        assert_eq!(
+            admin.call(new_category(name)).await,
+            Ok(CategoryChange::Done)
+        );
+    }
+
+    let categories = admin.call(GetCategories {}).await.unwrap();
+    let ids = names
+        .iter()
+        .map(|name| {
+            let category = categories.iter().find(|category| category.name == *name);
+            category.unwrap().id.clone()
+        })
+        .collect();
+
+    (store, ids)
+}
+
+#[actix_web::test]
+async fn admins_manage_categories() {
+    let (store, ids) = categories(&["Dessert", "Breakfast"]).await;
+    let mut admin = client(store).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+
+    let categories = admin.call(GetCategories {}).await.unwrap();
+    assert_eq!(names(&categories), ["Breakfast", "Dessert"]);
+
+    assert_eq!(
+        admin
+            .call(RenameCategory {
+                id: ids[0].clone(),
+                name: "Desserts".to_string(),
+            })
+            .await,
+        Ok(CategoryChange::Done)
+    );
+    assert_eq!(
+        admin.call(DeleteCategory { id: ids[1].clone() }).await,
+        Ok(CategoryChange::Done)
+    );
+
+    let categories = admin.call(GetCategories {}).await.unwrap();
+    assert_eq!(names(&categories), ["Desserts"]);
+}
+
+#[actix_web::test]
+async fn category_names_are_unique() {
+    let (store, ids) = categories(&["Dessert", "Breakfast"]).await;
+    let mut admin = client(store).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+
+    assert_eq!(
+        admin.call(new_category("Dessert")).await,
+        Ok(CategoryChange::NameAlreadyTaken)
+    );
+    assert_eq!(
+        admin
+            .call(RenameCategory {
+                id: ids[1].clone(),
+                name: "Dessert".to_string(),
+            })
+            .await,
+        Ok(CategoryChange::NameAlreadyTaken)
+    );
+    // Renaming a category to its own name changes nothing
+    assert_eq!(
+        admin
+            .call(RenameCategory {
+                id: ids[0].clone(),
+                name: "Dessert".to_string(),
+            })
+            .await,
+        Ok(CategoryChange::Done)
+    );
+
+    // Names are trimmed before being compared
+    assert_eq!(
+        admin.call(new_category(" Dessert\t")).await,
+        Ok(CategoryChange::NameAlreadyTaken)
+    );
+    assert_eq!(
+        admin.call(new_category("  Lunch ")).await,
+        Ok(CategoryChange::Done)
+    );
+    let categories = admin.call(GetCategories {}).await.unwrap();
+    assert_eq!(names(&categories), ["Breakfast", "Dessert", "Lunch"]);
+}
+
+#[actix_web::test]
+async fn invalid_category_names_are_rejected() {
+    let (store, ids) = categories(&["Dessert"]).await;
+    let mut admin = client(store).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+
+    let too_long = "a".repeat(NAME_MAX_LENGTH + 1);
+    for name in ["", "   ", &too_long] {
+        assert_eq!(
+            admin.call(new_category(name)).await,
+            Ok(CategoryChange::InvalidName)
+        );
+        assert_eq!(
+            admin
+                .call(RenameCategory {
+                    id: ids[0].clone(),
+                    name: name.to_string(),
+                })
+                .await,
+            Ok(CategoryChange::InvalidName)
+        );
+    }
+
+    let categories = admin.call(GetCategories {}).await.unwrap();
+    assert_eq!(names(&categories), ["Dessert"]);
+}
+
+#[actix_web::test]
+async fn only_admins_manage_categories() {
+    let (store, ids) = categories(&["Dessert"]).await;
+    let mut alice = client(store).await;
+
+    assert_eq!(
+        alice.call(new_category("Lunch")).await,
+        Ok(CategoryChange::InvalidCredentials)
+    );
+
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        alice.call(new_category("Lunch")).await,
+        Ok(CategoryChange::Forbidden)
+    );
+    assert_eq!(
+        alice
+            .call(RenameCategory {
+                id: ids[0].clone(),
+                name: "Lunch".to_string(),
+            })
+            .await,
+        Ok(CategoryChange::Forbidden)
+    );
+    assert_eq!(
+        alice.call(DeleteCategory { id: ids[0].clone() }).await,
+        Ok(CategoryChange::Forbidden)
+    );
+
+    let categories = alice.call(GetCategories {}).await.unwrap();
+    assert_eq!(names(&categories), ["Dessert"]);
+}
+
+#[actix_web::test]
+async fn unknown_categories_are_not_found() {
+    let (store, _) = categories(&[]).await;
+    let mut admin = client(store).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+    let unknown = "0123456789abcdef01234567".to_string();
+
+    assert_eq!(
+        admin
+            .call(DeleteCategory {
+                id: unknown.clone()
+            })
+            .await,
+        Ok(CategoryChange::NotFound)
+    );
+    assert_eq!(
+        admin
+            .call(RenameCategory {
+                id: "not an id".to_string(),
+                name: "Lunch".to_string(),
+            })
+            .await,
+        Ok(CategoryChange::NotFound)
+    );
+    assert!(admin
+        .call(GetCategory { id: unknown })
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[actix_web::test]
+async fn categories_list_their_recipes() {
+    let (store, ids) = categories(&["Dessert", "Breakfast"]).await;
+    let mut alice = client(store.clone()).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+
+    let mut pancakes = new_recipe("Pancakes");
+    pancakes.categories = vec![
+        ids[0].clone(),
+        ids[1].clone(),
+        ids[0].clone(),
+        "0123456789abcdef01234567".to_string(),
+    ];
+    assert_eq!(alice.call(pancakes).await, Ok(RecipeChange::Done));
+    let mut crepes = new_recipe("Crepes");
+    crepes.categories = vec![ids[0].clone()];
+    assert_eq!(alice.call(crepes).await, Ok(RecipeChange::Done));
+    assert_eq!(alice.call(new_recipe("Soup")).await, Ok(RecipeChange::Done));
+
+    let dessert = alice
+        .call(GetCategory { id: ids[0].clone() })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(dessert.name, "Dessert");
+
+    // The recipes of a category are listed a page at a time
+    let mut list = get_recipes();
+    list.filter = RecipeFilter {
+        categories: vec![dessert.id],
+        ..RecipeFilter::default()
+    };
+    list.page_size = 1;
+    let first = alice.call(list.clone()).await.unwrap();
+    list.cursor = first.next;
+    let second = alice.call(list.clone()).await.unwrap();
+    assert!(second.next.is_none());
+    let recipes: Vec<_> = first
+        .recipes
+        .iter()
+        .chain(&second.recipes)
+        .map(|recipe| recipe.name.as_str())
+        .collect();
+    assert_eq!(recipes, ["Pancakes", "Crepes"]);
+    assert!(first
+        .recipes
+        .iter()
+        .chain(&second.recipes)
+        .all(|recipe| recipe.author.as_deref() == Some("alice")));
+
+    // Unknown and duplicated categories are dropped
+    let pancakes = alice
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+    let pancakes = alice
+        .call(GetRecipe { id: pancakes })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(names(&pancakes.categories), ["Breakfast", "Dessert"]);
+
+    // Deleting a category removes it from its recipes
+    let mut admin = client(store).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+    assert_eq!(
+        admin.call(DeleteCategory { id: ids[1].clone() }).await,
+        Ok(CategoryChange::Done)
+    );
+    let pancakes = alice
+        .call(GetRecipe { id: pancakes.id })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(names(&pancakes.categories), ["Dessert"]);
+}
+
+#[actix_web::test]
+async fn categories_with_database_failure_are_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+
+    assert_eq!(
+        client.call(GetCategories {}).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+
+    client.set_cookie("name", "admin");
+    client.set_cookie("token", "token");
+    assert_eq!(
+        client.call(new_category("Dessert")).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+}
@@ -0,0 +1,388 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use leptos::ServerFnError;
+use recipe_final_exercise::{
+    app::{
+        comments::{CommentPageInfo, DeleteComment, GetComments, NewComment, UpdateComment},
+        recipe::RecipeChange,
+    },
+    database::store::RecipeStore,
+    validation::{RecipeField, ValidationError, COMMENT_MAX_LENGTH},
+};
+
+use common::{
+    client, get_recipes, insert_admin, new_recipe, signin, signup, test_store, FailingStore,
+};
+
+fn new_comment(recipe: &str, content: &str) -> NewComment {
+    NewComment {
+        recipe: recipe.to_string(),
+        content: content.to_string(),
+    }
+}
+
+fn get_comments(recipe: &str, page: usize) -> GetComments {
+    GetComments {
+        recipe: recipe.to_string(),
+        page,
+        last: false,
+    }
+}
+
+fn contents(page: &CommentPageInfo) -> Vec<&str> {
+    page.comments
+        .iter()
+        .map(|comment| comment.content.as_str())
+        .collect()
+}
+
+/// Store with a recipe of alice, returns the store and the id of the recipe
+async fn recipe_of_alice() -> (Arc<dyn RecipeStore>, String) {
+    let store = test_store().await;
+    let mut alice = client(store.clone()).await;
+
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        alice.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let recipe = alice
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    (store, recipe)
+}
+
+#[actix_web::test]
+async fn comments_are_listed_with_their_author() {
+    let (store, recipe) = recipe_of_alice().await;
+    let mut bob = client(store).await;
+    assert_eq!(bob.call(signup("bob", "password1")).await, Ok(true));
+
+    assert_eq!(
+        bob.call(new_comment(&recipe, "Delicious")).await,
+        Ok(RecipeChange::Done)
+    );
+
+    let page = bob.call(get_comments(&recipe, 0)).await.unwrap().unwrap();
+    assert_eq!(contents(&page), ["Delicious"]);
+    assert_eq!(page.comments[0].author.as_deref(), Some("bob"));
+    assert!(page.comments[0].age < 60);
+    assert_eq!((page.page, page.page_count), (0, 1));
+}
+
+#[actix_web::test]
+async fn comments_are_paginated() {
+    let (store, recipe) = recipe_of_alice().await;
+    let mut alice = client(store).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+
+    for i in 0..25 {
+        assert_eq!(
+            alice.call(new_comment(&recipe, &i.to_string())).await,
+            Ok(RecipeChange::Done)
+        );
+    }
+
+    let first = alice.call(get_comments(&recipe, 0)).await.unwrap().unwrap();
+    assert_eq!(first.comments.len(), 20);
+    assert_eq!(first.comments[0].content, "0");
+    assert_eq!((first.page, first.page_count), (0, 2));
+
+    let last = alice.call(get_comments(&recipe, 1)).await.unwrap().unwrap();
+    assert_eq!(contents(&last), ["20", "21", "22", "23", "24"]);
+
+    // Pages after the last one give the last one
+    for page in [2, 1 << 40, usize::MAX] {
+        let after = alice
+            .call(get_comments(&recipe, page))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(after.page, 1);
+        assert_eq!(contents(&after), contents(&last));
+    }
+
+    let requested_last = alice
+        .call(GetComments {
+            recipe: recipe.clone(),
+            page: 0,
+            last: true,
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(requested_last.page, 1);
+    assert_eq!(contents(&requested_last), contents(&last));
+}
+
+#[actix_web::test]
+async fn comments_of_unknown_recipe_are_not_found() {
+    let (store, _) = recipe_of_alice().await;
+    let mut client = client(store).await;
+
+    assert!(client
+        .call(get_comments("0123456789abcdef01234567", 0))
+        .await
+        .unwrap()
+        .is_none());
+    assert!(client
+        .call(get_comments("not an id", 0))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[actix_web::test]
+async fn new_comment_requires_valid_credentials() {
+    let (store, recipe) = recipe_of_alice().await;
+    let mut client = client(store).await;
+
+    assert_eq!(
+        client.call(new_comment(&recipe, "Spam")).await,
+        Ok(RecipeChange::InvalidCredentials)
+    );
+    client.set_cookie("name", "alice");
+    client.set_cookie("token", "forged token");
+    assert_eq!(
+        client.call(new_comment(&recipe, "Spam")).await,
+        Ok(RecipeChange::InvalidCredentials)
+    );
+
+    let page = client
+        .call(get_comments(&recipe, 0))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(page.comments.is_empty());
+}
+
+#[actix_web::test]
+async fn new_comment_on_unknown_recipe_is_not_found() {
+    let (store, _) = recipe_of_alice().await;
+    let mut alice = client(store).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+
+    assert_eq!(
+        alice
+            .call(new_comment("0123456789abcdef01234567", "Hello"))
+            .await,
+        Ok(RecipeChange::NotFound)
+    );
+}
+
+#[actix_web::test]
+async fn invalid_comments_are_rejected() {
+    let (store, recipe) = recipe_of_alice().await;
+    let mut alice = client(store).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+
+    let required = RecipeChange::Invalid(vec![ValidationError::Required(RecipeField::Comment)]);
+    let too_long = RecipeChange::Invalid(vec![ValidationError::TooLong {
+        field: RecipeField::Comment,
+        max: COMMENT_MAX_LENGTH,
+    }]);
+
+    assert_eq!(
+        alice.call(new_comment(&recipe, "")).await,
+        Ok(required.clone())
+    );
+    assert_eq!(
+        alice.call(new_comment(&recipe, " \n\t ")).await,
+        Ok(required.clone())
+    );
+    assert_eq!(
+        alice
+            .call(new_comment(&recipe, &"a".repeat(COMMENT_MAX_LENGTH + 1)))
+            .await,
+        Ok(too_long.clone())
+    );
+    assert_eq!(
+        alice.call(new_comment(&recipe, "  Tasty \n")).await,
+        Ok(RecipeChange::Done)
+    );
+
+    let page = alice.call(get_comments(&recipe, 0)).await.unwrap().unwrap();
+    assert_eq!(contents(&page), ["Tasty"]);
+
+    let update = |content: &str| UpdateComment {
+        recipe: recipe.clone(),
+        comment: page.comments[0].id.clone(),
+        content: content.to_string(),
+    };
+    assert_eq!(alice.call(update("   ")).await, Ok(required));
+    assert_eq!(
+        alice
+            .call(update(&"a".repeat(COMMENT_MAX_LENGTH + 1)))
+            .await,
+        Ok(too_long)
+    );
+
+    let page = alice.call(get_comments(&recipe, 0)).await.unwrap().unwrap();
+    assert_eq!(contents(&page), ["Tasty"]);
+}
+
+#[actix_web::test]
+async fn only_the_author_and_admins_see_comments_as_editable() {
+    let (store, recipe) = recipe_of_alice().await;
+    let mut alice = client(store.clone()).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+    let mut bob = client(store.clone()).await;
+    assert_eq!(bob.call(signup("bob", "password1")).await, Ok(true));
+    insert_admin(&store, "admin", "password1").await;
+    let mut admin = client(store.clone()).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+    let mut anonymous = client(store.clone()).await;
+
+    assert_eq!(
+        alice.call(new_comment(&recipe, "Tasty")).await,
+        Ok(RecipeChange::Done)
+    );
+    assert_eq!(
+        bob.call(new_comment(&recipe, "Delicious")).await,
+        Ok(RecipeChange::Done)
+    );
+
+    for (client, editable) in [
+        (&mut alice, [true, false]),
+        (&mut bob, [false, true]),
+        (&mut admin, [true, true]),
+        (&mut anonymous, [false, false]),
+    ] {
+        let page = client
+            .call(get_comments(&recipe, 0))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(contents(&page), ["Tasty", "Delicious"]);
+        let shown: Vec<_> = page
+            .comments
+            .iter()
+            .map(|comment| comment.editable)
+            .collect();
+        assert_eq!(shown, editable);
+    }
+
+    // A stale token is treated as an anonymous viewer
+    alice.set_cookie("token", "expired");
+    let page = alice.call(get_comments(&recipe, 0)).await.unwrap().unwrap();
+    assert!(page.comments.iter().all(|comment| !comment.editable));
+}
+
+#[actix_web::test]
+async fn author_and_admins_can_edit_a_comment() {
+    let (store, recipe) = recipe_of_alice().await;
+    let mut alice = client(store.clone()).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+    let mut bob = client(store.clone()).await;
+    assert_eq!(bob.call(signup("bob", "password1")).await, Ok(true));
+    insert_admin(&store, "admin", "password1").await;
+    let mut admin = client(store.clone()).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+
+    assert_eq!(
+        alice.call(new_comment(&recipe, "Tasty")).await,
+        Ok(RecipeChange::Done)
+    );
+    let comment = alice
+        .call(get_comments(&recipe, 0))
+        .await
+        .unwrap()
+        .unwrap()
+        .comments[0]
+        .id
+        .clone();
+
+    let update = |content: &str| UpdateComment {
+        recipe: recipe.clone(),
+        comment: comment.clone(),
+        content: content.to_string(),
+    };
+
+    assert_eq!(
+        bob.call(update("Disgusting")).await,
+        Ok(RecipeChange::Forbidden)
+    );
+    assert_eq!(
+        alice.call(update("Very tasty")).await,
+        Ok(RecipeChange::Done)
+    );
+    let page = alice.call(get_comments(&recipe, 0)).await.unwrap().unwrap();
+    assert_eq!(contents(&page), ["Very tasty"]);
+
+    assert_eq!(
+        admin.call(update("[removed]")).await,
+        Ok(RecipeChange::Done)
+    );
+    let page = alice.call(get_comments(&recipe, 0)).await.unwrap().unwrap();
+    assert_eq!(contents(&page), ["[removed]"]);
+}
+
+#[actix_web::test]
+async fn author_and_admins_can_delete_a_comment() {
+    let (store, recipe) = recipe_of_alice().await;
+    let mut alice = client(store.clone()).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+    let mut bob = client(store.clone()).await;
+    assert_eq!(bob.call(signup("bob", "password1")).await, Ok(true));
+    insert_admin(&store, "admin", "password1").await;
+    let mut admin = client(store.clone()).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+
+    for content in ["First", "Second"] {
+        assert_eq!(
+            alice.call(new_comment(&recipe, content)).await,
+            Ok(RecipeChange::Done)
+        );
+    }
+    let ids: Vec<_> = alice
+        .call(get_comments(&recipe, 0))
+        .await
+        .unwrap()
+        .unwrap()
+        .comments
+        .into_iter()
+        .map(|comment| comment.id)
+        .collect();
+
+    let delete = |comment: &str| DeleteComment {
+        recipe: recipe.clone(),
+        comment: comment.to_string(),
+    };
+
+    assert_eq!(bob.call(delete(&ids[0])).await, Ok(RecipeChange::Forbidden));
+    assert_eq!(alice.call(delete(&ids[0])).await, Ok(RecipeChange::Done));
+    assert_eq!(admin.call(delete(&ids[1])).await, Ok(RecipeChange::Done));
+    assert_eq!(
+        alice.call(delete(&ids[1])).await,
+        Ok(RecipeChange::NotFound)
+    );
+
+    let page = alice.call(get_comments(&recipe, 0)).await.unwrap().unwrap();
+    assert!(page.comments.is_empty());
+}
+
+#[actix_web::test]
+async fn comments_with_database_failure_are_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+    client.set_cookie("name", "alice");
+    client.set_cookie("token", "token");
+    let recipe = "0123456789abcdef01234567";
+
+    assert_eq!(
+        client.call(get_comments(recipe, 0)).await.map(|_| ()),
+        Err(ServerFnError::ServerError("".into()))
+    );
+    assert_eq!(
+        client.call(new_comment(recipe, "Hello")).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+}
@@ -0,0 +1,401 @@
+//! Harness running the app against an in-memory storage backend
+//! and calling its server functions like the client does
+//!
+//! The tests run against a mongodb server instead when `RECIPE_TEST_MONGODB_URI` is set,
+//! each test on a new database named `recipe_test_*`
+
+#![allow(dead_code)]
+
+use std::{env, sync::Arc};
+
+use actix_http::Request;
+use actix_web::{
+    cookie::{time::OffsetDateTime, Cookie},
+    dev::{Service, ServiceResponse},
+    http::header::{ACCEPT, CONTENT_TYPE, SET_COOKIE},
+    test, App,
+};
+use async_trait::async_trait;
+use bson::{oid::ObjectId, DateTime};
+use leptos::{server_fn::Encoding, LeptosOptions, ServerFn, ServerFnError};
+use serde::de::DeserializeOwned;
+
+use recipe_final_exercise::{
+    app::{
+        login::{Signin, Signup},
+        new_recipe::NewRecipe,
+        recipes::{GetRecipes, RecipeFilter, RecipeSort},
+    },
+    config::Config,
+    database::{
+        memory::MemoryStore,
+        mongo::MongoStore,
+        recipes::{RecipeCriteria, RecipeCursor, RecipeOrder, RecipeUpdate},
+        store::{RecipeStore, StoreError},
+        Database,
+    },
+    date::Date,
+    model::{
+        Category, Comment, Ingredient, MealPlan, Note, Recipe, RecipeSummary, ShoppingList, Token,
+        Tool, User,
+    },
+    quantity::{Quantity, UnitSystem},
+    server,
+};
+
+/// Client of an app, keeping the cookies set by the server like a browser
+pub struct Client<S> {
+    app: S,
+    cookies: Vec<Cookie<'static>>,
+}
+
+/// Variable giving the uri of the mongodb server to run the tests against
+const MONGODB_URI_VARIABLE: &str = "RECIPE_TEST_MONGODB_URI";
+
+fn init_config() {
+    let default = Config::default();
+    // Hashing with the default cost makes the tests really slow
+    let _ = Config {
+        bcrypt_cost: 4,
+        mongodb_uri: env::var(MONGODB_URI_VARIABLE).unwrap_or(default.mongodb_uri),
+        ..default
+    }
+    .init();
+}
+
+/// Empty storage backend, a [`MemoryStore`] or a new mongodb database
+/// if `RECIPE_TEST_MONGODB_URI` is set
+pub async fn test_store() -> Arc<dyn RecipeStore> {
+    init_config();
+
+    if env::var_os(MONGODB_URI_VARIABLE).is_none() {
+        return Arc::new(MemoryStore::new());
+    }
+
+    let name = format!("recipe_test_{}", ObjectId::new().to_hex());
+    let store = MongoStore::with_database(&name).await.unwrap();
+    store.setup().await.unwrap();
+    Arc::new(store)
+}
+
+/// Start the app on top of `store`
+pub async fn client(
+    store: Arc<dyn RecipeStore>,
+) -> Client<impl Service<Request, Response = ServiceResponse, Error = actix_web::Error>> {
+    init_config();
+
+    let options = LeptosOptions::builder().output_name("app").build();
+    let database = Database::with_store(store);
+
+    let app = test::init_service(
+        App::new().configure(|service| server::configure(service, options, Some(database))),
+    )
+    .await;
+
+    Client {
+        app,
+        cookies: Vec::new(),
+    }
+}
+
+/// Start the app on top of an empty [`test_store`]
+pub async fn empty_client(
+) -> Client<impl Service<Request, Response = ServiceResponse, Error = actix_web::Error>> {
+    client(test_store().await).await
+}
+
+impl<S> Client<S>
+where
+    S: Service<Request, Response = ServiceResponse, Error = actix_web::Error>,
+{
+    /// Call the server function `F` with the arguments `args`
+    pub async fn call<F>(&mut self, args: F) -> Result<F::Output, ServerFnError>
+    where
+        F: ServerFn,
+        F::Output: DeserializeOwned,
+    {
+        let path = format!("{}/{}", F::prefix(), F::url());
+
+        let mut request = match F::encoding() {
+            Encoding::Cbor => {
+                let mut body = Vec::new();
+                ciborium::into_writer(&args, &mut body).unwrap();
+                test::TestRequest::post()
+                    .uri(&path)
+                    .insert_header((CONTENT_TYPE, "application/cbor"))
+                    .set_payload(body)
+            }
+            Encoding::GetCBOR => {
+                let query = serde_qs::to_string(&args).unwrap();
+                test::TestRequest::get().uri(&format!("{}?{}", path, query))
+            }
+            encoding => panic!("Unsupported encoding {:?}", encoding),
+        }
+        .insert_header((ACCEPT, "application/cbor"));
+
+        for cookie in &self.cookies {
+            request = request.cookie(cookie.clone());
+        }
+
+        let response = test::call_service(&self.app, request.to_request()).await;
+        let status = response.status();
+
+        for header in response.headers().get_all(SET_COOKIE) {
+            let cookie = Cookie::parse_encoded(header.to_str().unwrap().to_string()).unwrap();
+            self.cookies.retain(|other| other.name() != cookie.name());
+            // Expired cookies are removed by the browser
+            let expired = cookie
+                .expires_datetime()
+                .is_some_and(|expiration| expiration <= OffsetDateTime::now_utc());
+            if !expired {
+                self.cookies.push(cookie);
+            }
+        }
+
+        let body = test::read_body(response).await;
+        if status.is_success() {
+            Ok(ciborium::from_reader(body.as_ref()).unwrap())
+        } else {
+            Err(serde_json::from_slice(&body).unwrap())
+        }
+    }
+
+    /// Value of the cookie `name` set by the server
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.cookies
+            .iter()
+            .find(|cookie| cookie.name() == name)
+            .map(|cookie| cookie.value())
+    }
+
+    /// Set a cookie like if it was set by the server
+    pub fn set_cookie(&mut self, name: &str, value: &str) {
+        self.cookies.retain(|cookie| cookie.name() != name);
+        self.cookies
+            .push(Cookie::new(name.to_string(), value.to_string()));
+    }
+
+    pub fn clear_cookies(&mut self) {
+        self.cookies.clear();
+    }
+}
+
+pub fn signup(name: &str, password: &str) -> Signup {
+    Signup {
+        name: name.to_string(),
+        password: password.to_string(),
+    }
+}
+
+pub fn signin(name: &str, password: &str) -> Signin {
+    Signin {
+        name: name.to_string(),
+        password: password.to_string(),
+    }
+}
+
+pub fn new_recipe(name: &str) -> NewRecipe {
+    NewRecipe {
+        name: name.to_string(),
+        icon_url: "https://example.com/pancakes.png".to_string(),
+        price_level: 1,
+        healthy_level: 2,
+        servings: 4,
+        instructions: vec!["Mix".to_string(), "Cook".to_string()],
+        ingredients: vec![Ingredient {
+            name: "Flour".to_string(),
+            icon_url: String::new(),
+            quantity: Quantity::parse("250g"),
+        }],
+        tools: vec![Tool {
+            name: "Pan".to_string(),
+            icon_url: String::new(),
+        }],
+        categories: Vec::new(),
+    }
+}
+
+pub fn get_recipes() -> GetRecipes {
+    GetRecipes {
+        sort: RecipeSort::Oldest,
+        filter: RecipeFilter::default(),
+        cursor: None,
+        page_size: 50,
+    }
+}
+
+/// Insert a user directly in `store`, bypassing the signup
+pub async fn insert_user(store: &Arc<dyn RecipeStore>, name: &str, password: &str) {
+    assert!(store
+        .insert_user(User {
+            id: ObjectId::new(),
+            name: name.to_string(),
+            password: password.to_string(),
+            admin: false,
+            banned: false,
+            units: None,
+            tokens: Vec::new(),
+        })
+        .await
+        .unwrap());
+}
+
+/// Insert an admin directly in `store`
+pub async fn insert_admin(store: &Arc<dyn RecipeStore>, name: &str, password: &str) {
+    assert!(store
+        .insert_user(User {
+            id: ObjectId::new(),
+            name: name.to_string(),
+            password: bcrypt::hash(password, 4).unwrap(),
+            admin: true,
+            banned: false,
+            units: None,
+            tokens: Vec::new(),
+        })
+        .await
+        .unwrap());
+}
+
+/// Storage backend failing every operation, like an unreachable mongodb
+pub struct FailingStore;
+
+fn failure() -> StoreError {
+    StoreError::new("Storage unavailable")
+}
+
+#[async_trait]
+impl RecipeStore for FailingStore {
+    async fn setup(&self) -> Result<(), ()> {
+        Err(())
+    }
+
+    async fn find_user(&self, _: &str) -> Result<Option<User>, StoreError> {
+        Err(failure())
+    }
+    async fn find_users(&self, _: &[ObjectId]) -> Result<Vec<User>, StoreError> {
+        Err(failure())
+    }
+    async fn insert_user(&self, _: User) -> Result<bool, StoreError> {
+        Err(failure())
+    }
+    async fn set_admin(&self, _: ObjectId, _: bool) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn set_banned(&self, _: ObjectId, _: bool) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn set_units(&self, _: ObjectId, _: Option<UnitSystem>) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn push_token(&self, _: ObjectId, _: Token) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn pull_token(&self, _: ObjectId, _: &str) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn clear_tokens(&self, _: ObjectId) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn pull_expired_tokens(
+        &self,
+        _: Option<ObjectId>,
+        _: DateTime,
+    ) -> Result<(), StoreError> {
+        Err(failure())
+    }
+
+    async fn find_recipes(
+        &self,
+        _: usize,
+        _: RecipeOrder,
+        _: &RecipeCriteria,
+        _: Option<&RecipeCursor>,
+    ) -> Result<Vec<RecipeSummary>, StoreError> {
+        Err(failure())
+    }
+    async fn search_recipes(
+        &self,
+        _: &str,
+        _: &RecipeCriteria,
+        _: usize,
+        _: usize,
+    ) -> Result<(Vec<RecipeSummary>, usize), StoreError> {
+        Err(failure())
+    }
+    async fn find_author_recipes(&self, _: ObjectId) -> Result<Vec<RecipeSummary>, StoreError> {
+        Err(failure())
+    }
+    async fn find_recipe(&self, _: ObjectId) -> Result<Option<Recipe>, StoreError> {
+        Err(failure())
+    }
+    async fn insert_recipe(&self, _: Recipe) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn update_recipe(&self, _: ObjectId, _: RecipeUpdate) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn delete_recipe(&self, _: ObjectId) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn set_note(&self, _: ObjectId, _: Note) -> Result<(), StoreError> {
+        Err(failure())
+    }
+
+    async fn find_comments(
+        &self,
+        _: ObjectId,
+        _: usize,
+        _: usize,
+    ) -> Result<Option<(Vec<Comment>, usize)>, StoreError> {
+        Err(failure())
+    }
+    async fn push_comment(&self, _: ObjectId, _: Comment) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn set_comment_content(
+        &self,
+        _: ObjectId,
+        _: ObjectId,
+        _: String,
+    ) -> Result<(), StoreError> {
+        Err(failure())
+    }
+    async fn pull_comment(&self, _: ObjectId, _: ObjectId) -> Result<(), StoreError> {
+        Err(failure())
+    }
+
+    async fn find_categories(&self) -> Result<Vec<Category>, StoreError> {
+        Err(failure())
+    }
+    async fn find_category(&self, _: ObjectId) -> Result<Option<Category>, StoreError> {
+        Err(failure())
+    }
+    async fn insert_category(&self, _: Category) -> Result<bool, StoreError> {
+        Err(failure())
+    }
+    async fn rename_category(&self, _: ObjectId, _: String) -> Result<bool, StoreError> {
+        Err(failure())
+    }
+    async fn delete_category(&self, _: ObjectId) -> Result<(), StoreError> {
+        Err(failure())
+    }
+
+    async fn find_shopping_list(&self, _: ObjectId) -> Result<Option<ShoppingList>, StoreError> {
+        Err(failure())
+    }
+    async fn set_shopping_list(&self, _: ShoppingList) -> Result<bool, StoreError> {
+        Err(failure())
+    }
+
+    async fn find_meal_plans(
+        &self,
+        _: ObjectId,
+        _: Date,
+        _: Date,
+    ) -> Result<Vec<MealPlan>, StoreError> {
+        Err(failure())
+    }
+    async fn set_meal_plan(&self, _: MealPlan) -> Result<bool, StoreError> {
+        Err(failure())
+    }
+}
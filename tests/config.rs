@@ -0,0 +1,145 @@
+#![cfg(feature = "server")]
+
+use std::{env::VarError, ffi::OsString, time::Duration};
+
+use recipe_final_exercise::config::{Config, ConfigError, Storage};
+
+/// Read the configuration from the variables `vars` only
+fn config(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+    Config::from_vars(|variable| {
+        vars.iter()
+            .find(|(name, _)| *name == variable)
+            .map(|(_, value)| value.to_string())
+            .ok_or(VarError::NotPresent)
+    })
+}
+
+fn error(vars: &[(&str, &str)]) -> String {
+    config(vars).unwrap_err().to_string()
+}
+
+#[test]
+fn missing_variables_fall_back_to_the_defaults() {
+    let config = config(&[]).unwrap();
+    let default = Config::default();
+
+    assert_eq!(config.mongodb_uri, default.mongodb_uri);
+    assert_eq!(config.database_name, default.database_name);
+    assert_eq!(config.min_pool_size, None);
+    assert_eq!(config.max_pool_size, None);
+    assert_eq!(config.token_lifetime, default.token_lifetime);
+    assert_eq!(config.bcrypt_cost, default.bcrypt_cost);
+    assert_eq!(config.storage, Storage::MongoDb);
+    assert_eq!(config.admin, None);
+}
+
+#[test]
+fn valid_variables_are_read() {
+    let config = config(&[
+        ("RECIPE_MONGODB_URI", "mongodb+srv://example.com"),
+        ("RECIPE_DATABASE_NAME", "recipes"),
+        ("RECIPE_MIN_POOL_SIZE", "2"),
+        ("RECIPE_MAX_POOL_SIZE", " 8 "),
+        ("RECIPE_TOKEN_LIFETIME_DAYS", "7"),
+        ("RECIPE_BCRYPT_COST", "10"),
+        ("RECIPE_STORAGE", "memory"),
+        ("RECIPE_ADMIN", "alice"),
+    ])
+    .unwrap();
+
+    assert_eq!(config.mongodb_uri, "mongodb+srv://example.com");
+    assert_eq!(config.database_name, "recipes");
+    assert_eq!(config.min_pool_size, Some(2));
+    assert_eq!(config.max_pool_size, Some(8));
+    assert_eq!(config.token_lifetime, Duration::from_secs(7 * 24 * 60 * 60));
+    assert_eq!(config.bcrypt_cost, 10);
+    assert_eq!(config.storage, Storage::Memory);
+    assert_eq!(config.admin.as_deref(), Some("alice"));
+}
+
+#[test]
+fn empty_admin_name_is_no_admin() {
+    assert_eq!(config(&[("RECIPE_ADMIN", "")]).unwrap().admin, None);
+}
+
+#[test]
+fn invalid_mongodb_settings_are_rejected() {
+    assert_eq!(
+        error(&[("RECIPE_MONGODB_URI", "http://localhost")]),
+        "RECIPE_MONGODB_URI must be a mongodb:// or mongodb+srv:// uri, got \"http://localhost\""
+    );
+    assert_eq!(
+        error(&[("RECIPE_DATABASE_NAME", "my.recipes")]),
+        "RECIPE_DATABASE_NAME must be a mongodb database name, got \"my.recipes\""
+    );
+    assert_eq!(
+        error(&[("RECIPE_DATABASE_NAME", "")]),
+        "RECIPE_DATABASE_NAME must be a mongodb database name, got \"\""
+    );
+}
+
+#[test]
+fn invalid_pool_sizes_are_rejected() {
+    assert_eq!(
+        error(&[("RECIPE_MIN_POOL_SIZE", "two")]),
+        "RECIPE_MIN_POOL_SIZE must be an integer, got \"two\""
+    );
+    assert_eq!(
+        error(&[("RECIPE_MAX_POOL_SIZE", "0")]),
+        "RECIPE_MAX_POOL_SIZE must be a positive integer, got \"0\""
+    );
+    assert_eq!(
+        error(&[("RECIPE_MAX_POOL_SIZE", "-1")]),
+        "RECIPE_MAX_POOL_SIZE must be a positive integer, got \"-1\""
+    );
+    assert_eq!(
+        error(&[
+            ("RECIPE_MIN_POOL_SIZE", "10"),
+            ("RECIPE_MAX_POOL_SIZE", "5")
+        ]),
+        "RECIPE_MIN_POOL_SIZE (10) is greater than RECIPE_MAX_POOL_SIZE (5)"
+    );
+}
+
+#[test]
+fn out_of_range_numbers_are_rejected() {
+    for cost in ["3", "32", "ten"] {
+        assert_eq!(
+            error(&[("RECIPE_BCRYPT_COST", cost)]),
+            format!(
+                "RECIPE_BCRYPT_COST must be an integer between 4 and 31, got {:?}",
+                cost
+            )
+        );
+    }
+    for days in ["0", "3651"] {
+        assert_eq!(
+            error(&[("RECIPE_TOKEN_LIFETIME_DAYS", days)]),
+            format!(
+                "RECIPE_TOKEN_LIFETIME_DAYS must be a positive number of days, got {:?}",
+                days
+            )
+        );
+    }
+}
+
+#[test]
+fn unknown_storage_is_rejected() {
+    assert_eq!(
+        error(&[("RECIPE_STORAGE", "postgres")]),
+        "RECIPE_STORAGE must be mongodb or memory, got \"postgres\""
+    );
+}
+
+#[test]
+fn non_unicode_variables_are_rejected() {
+    let result = Config::from_vars(|variable| match variable {
+        "RECIPE_ADMIN" => Err(VarError::NotUnicode(OsString::new())),
+        _ => Err(VarError::NotPresent),
+    });
+
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "RECIPE_ADMIN is not valid unicode"
+    );
+}
@@ -0,0 +1,195 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use leptos::ServerFnError;
+use recipe_final_exercise::{
+    app::{
+        categories::{CategoryChange, GetCategories, NewCategory},
+        recipe::RecipeChange,
+        recipes::{GetRecipes, RecipeFilter, RecipeInfo, RecipeSort, SearchRecipes},
+    },
+    database::store::RecipeStore,
+    model::{Ingredient, Tool},
+    quantity::Quantity,
+};
+
+use common::{client, insert_admin, new_recipe, signin, signup, test_store};
+
+fn names(recipes: &[RecipeInfo]) -> Vec<&str> {
+    recipes.iter().map(|recipe| recipe.name.as_str()).collect()
+}
+
+fn ingredient(name: &str) -> Ingredient {
+    Ingredient {
+        name: name.to_string(),
+        icon_url: String::new(),
+        quantity: Quantity::default(),
+    }
+}
+
+fn tool(name: &str) -> Tool {
+    Tool {
+        name: name.to_string(),
+        icon_url: String::new(),
+    }
+}
+
+fn filter_recipes(filter: RecipeFilter) -> GetRecipes {
+    GetRecipes {
+        sort: RecipeSort::Oldest,
+        filter,
+        cursor: None,
+        page_size: 50,
+    }
+}
+
+/// Store with the recipes:
+/// - Pancakes: price 1, healthy 1, flour eggs milk, pan
+/// - Salad: price 2, healthy 4, lettuce tomato, bowl
+/// - Omelette: price 1, healthy 3, eggs, pan
+/// - Roast: price 4, healthy 2, beef, oven
+async fn recipes() -> Arc<dyn RecipeStore> {
+    let store = test_store().await;
+    let mut alice = client(store.clone()).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+
+    let recipes = [
+        ("Pancakes", 1, 1, &["Flour", "Eggs", "Milk"][..], "Pan"),
+        ("Salad", 2, 4, &["Lettuce", "Tomato"][..], "Bowl"),
+        ("Omelette", 1, 3, &["Eggs"][..], "Pan"),
+        ("Roast", 4, 2, &["Beef"][..], "Oven"),
+    ];
+    for (name, price_level, healthy_level, ingredients, tool_name) in recipes {
+        let mut recipe = new_recipe(name);
+        recipe.price_level = price_level;
+        recipe.healthy_level = healthy_level;
+        recipe.ingredients = ingredients.iter().map(|name| ingredient(name)).collect();
+        recipe.tools = vec![tool(tool_name)];
+        assert_eq!(alice.call(recipe).await, Ok(RecipeChange::Done));
+    }
+
+    store
+}
+
+#[actix_web::test]
+async fn recipes_are_filtered_by_levels() {
+    let mut client = client(recipes().await).await;
+
+    let filter = RecipeFilter {
+        max_price_level: Some(2),
+        min_healthy_level: Some(2),
+        ..RecipeFilter::default()
+    };
+    let recipes = client.call(filter_recipes(filter)).await.unwrap().recipes;
+    assert_eq!(names(&recipes), ["Salad", "Omelette"]);
+}
+
+#[actix_web::test]
+async fn recipes_are_filtered_by_ingredients_ignoring_case() {
+    let mut client = client(recipes().await).await;
+
+    let filter = RecipeFilter {
+        ingredients: vec!["eggs".to_string()],
+        ..RecipeFilter::default()
+    };
+    let recipes = client.call(filter_recipes(filter)).await.unwrap().recipes;
+    assert_eq!(names(&recipes), ["Pancakes", "Omelette"]);
+
+    let filter = RecipeFilter {
+        ingredients: vec!["EGGS".to_string()],
+        excluded_ingredients: vec!["milk".to_string()],
+        ..RecipeFilter::default()
+    };
+    let recipes = client.call(filter_recipes(filter)).await.unwrap().recipes;
+    assert_eq!(names(&recipes), ["Omelette"]);
+}
+
+#[actix_web::test]
+async fn recipes_are_filtered_by_available_tools() {
+    let mut client = client(recipes().await).await;
+
+    let filter = RecipeFilter {
+        tools: Some(vec!["pan".to_string(), "bowl".to_string()]),
+        ..RecipeFilter::default()
+    };
+    let recipes = client.call(filter_recipes(filter)).await.unwrap().recipes;
+    assert_eq!(names(&recipes), ["Pancakes", "Salad", "Omelette"]);
+
+    let filter = RecipeFilter {
+        tools: Some(Vec::new()),
+        ..RecipeFilter::default()
+    };
+    assert!(client
+        .call(filter_recipes(filter))
+        .await
+        .unwrap()
+        .recipes
+        .is_empty());
+}
+
+#[actix_web::test]
+async fn recipes_are_filtered_by_categories() {
+    let store = recipes().await;
+    insert_admin(&store, "admin", "password1").await;
+    let mut admin = client(store.clone()).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+    let new_category = NewCategory {
+        name: "Breakfast".to_string(),
+    };
+    assert_eq!(admin.call(new_category).await, Ok(CategoryChange::Done));
+    let breakfast = admin.call(GetCategories {}).await.unwrap().remove(0).id;
+
+    let mut alice = client(store).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+    let mut crepes = new_recipe("Crepes");
+    crepes.categories = vec![breakfast.clone()];
+    assert_eq!(alice.call(crepes).await, Ok(RecipeChange::Done));
+
+    let filter = RecipeFilter {
+        categories: vec![breakfast],
+        ..RecipeFilter::default()
+    };
+    let recipes = alice.call(filter_recipes(filter)).await.unwrap().recipes;
+    assert_eq!(names(&recipes), ["Crepes"]);
+
+    // A malformed id is rejected rather than ignored, which would list every recipe
+    let filter = RecipeFilter {
+        categories: vec!["not an id".to_string()],
+        ..RecipeFilter::default()
+    };
+    assert_eq!(
+        alice
+            .call(filter_recipes(filter.clone()))
+            .await
+            .map(|list| list.recipes.len()),
+        Err(ServerFnError::Args("Invalid category id".into()))
+    );
+    let search = SearchRecipes {
+        query: "crepes".to_string(),
+        filter,
+        page: 0,
+    };
+    assert_eq!(
+        alice.call(search).await.map(|page| page.recipes.len()),
+        Err(ServerFnError::Args("Invalid category id".into()))
+    );
+}
+
+#[actix_web::test]
+async fn search_results_are_filtered() {
+    let mut client = client(recipes().await).await;
+
+    let search = SearchRecipes {
+        query: "eggs".to_string(),
+        filter: RecipeFilter {
+            min_healthy_level: Some(2),
+            ..RecipeFilter::default()
+        },
+        page: 0,
+    };
+    let page = client.call(search).await.unwrap();
+    assert_eq!(names(&page.recipes), ["Omelette"]);
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "server")]
+
+use std::{env, sync::Arc};
+
+use bson::{doc, oid::ObjectId, DateTime, Document};
+use mongodb::Client;
+
+use recipe_final_exercise::{
+    config::Config,
+    database::{mongo::MongoStore, store::RecipeStore, Database},
+    date::Date,
+    quantity::Quantity,
+};
+
+/// Documents as stored by the first versions of the app,
+/// in a new database of the server at `RECIPE_TEST_MONGODB_URI`
+async fn legacy_database(user: ObjectId) -> String {
+    let uri = env::var("RECIPE_TEST_MONGODB_URI").unwrap();
+    let _ = Config {
+        mongodb_uri: uri.clone(),
+        ..Config::default()
+    }
+    .init();
+
+    let name = format!("recipe_test_{}", ObjectId::new().to_hex());
+    let database = Client::with_uri_str(uri).await.unwrap().database(&name);
+    let expiration = DateTime::from_millis(DateTime::now().timestamp_millis() + 60 * 60 * 1000);
+
+    database
+        .collection::<Document>("users")
+        .insert_one(
+            doc! {
+                "_id": user,
+                "name": "alice",
+                "password": "hash",
+                "admin": false,
+                "tokens": [{ "token": "plain token", "expiration": expiration }],
+                "recipes": [{
+                    "name": "Pancakes",
+                    "instructions": ["Mix", "Cook"],
+                    "icon_url": "",
+                    "price_level": 1,
+                    "healthy_level": 2,
+                    "comment": [],
+                    "notes": [],
+                    "ingredients": [
+                        { "name": "Flour", "icon_url": "", "quantity": "250g" },
+                        { "name": "Salt", "icon_url": "", "quantity": "some" },
+                    ],
+                    "tools": [],
+                    "categories": [],
+                }],
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    database
+        .collection::<Document>("shopping_lists")
+        .insert_one(doc! { "_id": user, "items": [] }, None)
+        .await
+        .unwrap();
+    database
+        .collection::<Document>("meal_plans")
+        .insert_one(
+            doc! {
+                "user": user,
+                "date": "2024-01-01",
+                "meals": [{ "meal": "Lunch", "recipe": ObjectId::new(), "servings": 4 }],
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    name
+}
+
+#[actix_web::test]
+#[ignore = "needs a mongodb server at RECIPE_TEST_MONGODB_URI"]
+async fn legacy_documents_are_migrated_once() {
+    let user = ObjectId::new();
+    let name = legacy_database(user).await;
+
+    // Setting up again must not migrate the documents twice
+    for _ in 0..2 {
+        let store = Arc::new(MongoStore::with_database(&name).await.unwrap());
+        store.setup().await.unwrap();
+        let database = Database::with_store(store.clone());
+
+        // The plain token held by the client is still valid once hashed
+        assert!(database
+            .get_units("alice".to_string(), "plain token".to_string())
+            .await
+            .is_ok());
+        let stored = store.find_user("alice").await.unwrap().unwrap();
+        assert_ne!(stored.tokens[0].token, "plain token");
+
+        let recipes = store.find_author_recipes(user).await.unwrap();
+        assert_eq!(recipes.len(), 1);
+        let recipe = store.find_recipe(recipes[0].id).await.unwrap().unwrap();
+        assert_eq!(recipe.servings, 4);
+        assert_eq!(recipe.ingredients[0].quantity, Quantity::parse("250 g"));
+        assert_eq!(
+            recipe.ingredients[1].quantity,
+            Quantity::Raw {
+                text: "some".to_string()
+            }
+        );
+
+        let list = store.find_shopping_list(user).await.unwrap().unwrap();
+        assert_eq!(list.version, 1);
+        let date = Date::parse("2024-01-01").unwrap();
+        let plans = store
+            .find_meal_plans(user, date, date.add_days(1))
+            .await
+            .unwrap();
+        assert_eq!(plans[0].version, 1);
+    }
+}
@@ -0,0 +1,153 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use leptos::ServerFnError;
+use recipe_final_exercise::app::{
+    recipe::{RateRecipe, RecipeChange},
+    recipes::{GetRecipes, RecipeFilter, RecipeListInfo, RecipeSort},
+};
+
+use common::{empty_client, get_recipes, new_recipe, signup};
+
+fn page(sort: RecipeSort, cursor: Option<String>, page_size: usize) -> GetRecipes {
+    GetRecipes {
+        sort,
+        filter: RecipeFilter::default(),
+        cursor,
+        page_size,
+    }
+}
+
+fn names(list: &RecipeListInfo) -> Vec<&str> {
+    list.recipes
+        .iter()
+        .map(|recipe| recipe.name.as_str())
+        .collect()
+}
+
+#[actix_web::test]
+async fn pages_follow_the_cursor() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    for name in ["A", "B", "C", "D", "E"] {
+        assert_eq!(client.call(new_recipe(name)).await, Ok(RecipeChange::Done));
+    }
+
+    let first = client
+        .call(page(RecipeSort::Oldest, None, 2))
+        .await
+        .unwrap();
+    assert_eq!(names(&first), ["A", "B"]);
+
+    // Recipes added meanwhile don't shift the next pages
+    assert_eq!(client.call(new_recipe("F")).await, Ok(RecipeChange::Done));
+
+    let second = client
+        .call(page(RecipeSort::Oldest, first.next, 2))
+        .await
+        .unwrap();
+    assert_eq!(names(&second), ["C", "D"]);
+
+    let third = client
+        .call(page(RecipeSort::Oldest, second.next, 2))
+        .await
+        .unwrap();
+    assert_eq!(names(&third), ["E", "F"]);
+    assert_eq!(third.next, None);
+}
+
+#[actix_web::test]
+async fn rating_pages_keep_ties_and_unrated_recipes_in_order() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    let notes = [
+        ("Unrated 1", None),
+        ("Good 1", Some(4)),
+        ("Unrated 2", None),
+        ("Good 2", Some(4)),
+        ("Best", Some(5)),
+    ];
+    for (name, note) in notes {
+        assert_eq!(client.call(new_recipe(name)).await, Ok(RecipeChange::Done));
+        if let Some(note) = note {
+            let id = client
+                .call(get_recipes())
+                .await
+                .unwrap()
+                .recipes
+                .pop()
+                .unwrap()
+                .id;
+            let rate = RateRecipe { id, note };
+            assert_eq!(client.call(rate).await, Ok(RecipeChange::Done));
+        }
+    }
+
+    let mut cursor = None;
+    let mut pages = Vec::new();
+    loop {
+        let list = client
+            .call(page(RecipeSort::Rating, cursor, 2))
+            .await
+            .unwrap();
+        pages.push(names(&list).join(", "));
+        cursor = list.next;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    assert_eq!(pages, ["Best, Good 1", "Good 2, Unrated 1", "Unrated 2"]);
+}
+
+#[actix_web::test]
+async fn page_size_is_capped() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    for i in 0..101 {
+        assert_eq!(
+            client.call(new_recipe(&i.to_string())).await,
+            Ok(RecipeChange::Done)
+        );
+    }
+
+    let list = client
+        .call(page(RecipeSort::Oldest, None, 1000))
+        .await
+        .unwrap();
+    assert_eq!(list.recipes.len(), 100);
+    assert!(list.next.is_some());
+
+    let list = client
+        .call(page(RecipeSort::Oldest, None, 0))
+        .await
+        .unwrap();
+    assert_eq!(list.recipes.len(), 1);
+}
+
+#[actix_web::test]
+async fn invalid_cursors_are_rejected() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    for name in ["A", "B"] {
+        assert_eq!(client.call(new_recipe(name)).await, Ok(RecipeChange::Done));
+    }
+
+    let forged = page(RecipeSort::Oldest, Some("forged".to_string()), 1);
+    assert!(matches!(
+        client.call(forged).await,
+        Err(ServerFnError::Args(_))
+    ));
+
+    // Cursors only work with the order they were made for
+    let next = client
+        .call(page(RecipeSort::Oldest, None, 1))
+        .await
+        .unwrap()
+        .next;
+    assert!(matches!(
+        client.call(page(RecipeSort::Rating, next, 1)).await,
+        Err(ServerFnError::Args(_))
+    ));
+}
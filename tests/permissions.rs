@@ -0,0 +1,196 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use recipe_final_exercise::{
+    app::{
+        categories::{CategoryChange, NewCategory},
+        comments::NewComment,
+        edit_recipe::UpdateRecipe,
+        recipe::{DeleteRecipe, RateRecipe, RecipeChange},
+        users::{SetBanned, UserChange},
+    },
+    database::{store::RecipeStore, Database},
+};
+
+use common::{
+    client, empty_client, get_recipes, insert_admin, new_recipe, signin, signup, test_store,
+};
+
+fn set_banned(name: &str, banned: bool) -> SetBanned {
+    SetBanned {
+        name: name.to_string(),
+        banned,
+    }
+}
+
+/// Store with the users alice and bob, and an admin, all with the password `password1`
+async fn users() -> Arc<dyn RecipeStore> {
+    let store = test_store().await;
+    insert_admin(&store, "admin", "password1").await;
+
+    for name in ["alice", "bob"] {
+        let mut user = client(store.clone()).await;
+        assert_eq!(user.call(signup(name, "password1")).await, Ok(true));
+    }
+
+    store
+}
+
+#[actix_web::test]
+async fn admins_can_edit_and_delete_any_recipe() {
+    let store = users().await;
+    let mut alice = client(store.clone()).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+    let mut bob = client(store.clone()).await;
+    assert_eq!(bob.call(signin("bob", "password1")).await, Ok(true));
+    let mut admin = client(store.clone()).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+    assert_eq!(
+        alice.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let id = alice
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    let update = |name: &str| UpdateRecipe {
+        id: id.clone(),
+        name: name.to_string(),
+        icon_url: String::new(),
+        price_level: 1,
+        healthy_level: 1,
+        servings: 2,
+        instructions: Vec::new(),
+        ingredients: Vec::new(),
+        tools: Vec::new(),
+        categories: Vec::new(),
+    };
+
+    assert_eq!(
+        bob.call(update("Bad pancakes")).await,
+        Ok(RecipeChange::Forbidden)
+    );
+    assert_eq!(
+        admin.call(update("Fluffy pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    assert_eq!(
+        alice.call(get_recipes()).await.unwrap().recipes[0].name,
+        "Fluffy pancakes"
+    );
+
+    let delete = DeleteRecipe { id: id.clone() };
+    assert_eq!(bob.call(delete.clone()).await, Ok(RecipeChange::Forbidden));
+    assert_eq!(admin.call(delete).await, Ok(RecipeChange::Done));
+    assert!(alice.call(get_recipes()).await.unwrap().recipes.is_empty());
+}
+
+#[actix_web::test]
+async fn banned_users_can_sign_in_but_not_contribute() {
+    let store = users().await;
+    let mut alice = client(store.clone()).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+    let mut admin = client(store.clone()).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+    assert_eq!(
+        alice.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let id = alice
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    assert_eq!(
+        admin.call(set_banned("bob", true)).await,
+        Ok(UserChange::Done)
+    );
+
+    let mut bob = client(store).await;
+    assert_eq!(bob.call(signin("bob", "password1")).await, Ok(true));
+    assert_eq!(
+        bob.call(new_recipe("Crepes")).await,
+        Ok(RecipeChange::Forbidden)
+    );
+    let comment = NewComment {
+        recipe: id.clone(),
+        content: "Spam".to_string(),
+    };
+    assert_eq!(bob.call(comment.clone()).await, Ok(RecipeChange::Forbidden));
+    let rate = RateRecipe {
+        id: id.clone(),
+        note: 1,
+    };
+    assert_eq!(bob.call(rate.clone()).await, Ok(RecipeChange::Forbidden));
+
+    assert_eq!(
+        admin.call(set_banned("bob", false)).await,
+        Ok(UserChange::Done)
+    );
+    assert_eq!(bob.call(comment).await, Ok(RecipeChange::Done));
+    assert_eq!(bob.call(rate).await, Ok(RecipeChange::Done));
+}
+
+#[actix_web::test]
+async fn only_admins_can_ban_and_admins_cant_be_banned() {
+    let store = users().await;
+    let mut alice = client(store.clone()).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+    let mut admin = client(store.clone()).await;
+    assert_eq!(admin.call(signin("admin", "password1")).await, Ok(true));
+    insert_admin(&store, "admin2", "password1").await;
+
+    assert_eq!(
+        alice.call(set_banned("bob", true)).await,
+        Ok(UserChange::Forbidden)
+    );
+    assert_eq!(
+        admin.call(set_banned("admin2", true)).await,
+        Ok(UserChange::Forbidden)
+    );
+    assert_eq!(
+        admin.call(set_banned("admin", true)).await,
+        Ok(UserChange::Forbidden)
+    );
+    assert_eq!(
+        admin.call(set_banned("carol", true)).await,
+        Ok(UserChange::NotFound)
+    );
+
+    let mut anonymous = empty_client().await;
+    assert_eq!(
+        anonymous.call(set_banned("bob", true)).await,
+        Ok(UserChange::InvalidCredentials)
+    );
+}
+
+#[actix_web::test]
+async fn promoted_users_can_manage_categories() {
+    let store = users().await;
+    let mut alice = client(store.clone()).await;
+    assert_eq!(alice.call(signin("alice", "password1")).await, Ok(true));
+    let category = NewCategory {
+        name: "Desserts".to_string(),
+    };
+
+    assert_eq!(
+        alice.call(category.clone()).await,
+        Ok(CategoryChange::Forbidden)
+    );
+
+    let database = Database::with_store(store);
+    assert!(database.promote_admin("alice").await.unwrap());
+    assert!(!database.promote_admin("carol").await.unwrap());
+
+    assert_eq!(alice.call(category).await, Ok(CategoryChange::Done));
+}
@@ -0,0 +1,427 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use bson::oid::ObjectId;
+use leptos::ServerFnError;
+use recipe_final_exercise::{
+    app::{
+        planner::{
+            BuildWeekShoppingList, GetWeekPlan, MoveMeal, PlanMeal, PlannedMealInfo, PlannerChange,
+            UnplanMeal, WeekPlanInfo,
+        },
+        recipe::RecipeChange,
+        recipes::RecipeListInfo,
+        shopping::GetShoppingList,
+    },
+    date::Date,
+    model::{Meal, MealPlan, PlannedMeal},
+};
+
+use common::{client, empty_client, get_recipes, new_recipe, signup, test_store, FailingStore};
+
+fn get_week(week: &str) -> GetWeekPlan {
+    GetWeekPlan {
+        week: week.to_string(),
+    }
+}
+
+fn plan(date: &str, meal: Meal, recipe: &str, servings: Option<u8>) -> PlanMeal {
+    PlanMeal {
+        date: date.to_string(),
+        meal,
+        recipe: recipe.to_string(),
+        servings,
+    }
+}
+
+fn move_meal(from: &str, from_meal: Meal, to: &str, to_meal: Meal) -> MoveMeal {
+    MoveMeal {
+        from: from.to_string(),
+        from_meal,
+        to: to.to_string(),
+        to_meal,
+    }
+}
+
+/// Meals planned on `date` in `week`
+fn meals(week: &WeekPlanInfo, date: &str) -> Vec<(Meal, String, u8)> {
+    week.days
+        .iter()
+        .find(|day| day.date == date)
+        .unwrap()
+        .meals
+        .iter()
+        .map(|meal| (meal.meal, meal.name.clone().unwrap(), meal.servings))
+        .collect()
+}
+
+fn recipe_ids(list: RecipeListInfo) -> Vec<String> {
+    list.recipes.into_iter().map(|recipe| recipe.id).collect()
+}
+
+#[test]
+fn dates_are_parsed_and_formatted_as_iso() {
+    let date = Date::parse("2024-02-29").unwrap();
+    assert_eq!(date.to_string(), "2024-02-29");
+    assert_eq!(date.add_days(1).to_string(), "2024-03-01");
+    assert_eq!(date.add_days(-60).to_string(), "2023-12-31");
+    assert_eq!(
+        Date::parse("1970-01-01").unwrap().add_days(-1).to_string(),
+        "1969-12-31"
+    );
+
+    assert_eq!(Date::parse("2023-02-29"), None);
+    assert_eq!(Date::parse("2024-13-01"), None);
+    assert_eq!(Date::parse("2024-1-01"), None);
+    assert_eq!(Date::parse("2024-01-01T00:00"), None);
+    assert_eq!(Date::parse(""), None);
+}
+
+#[test]
+fn weeks_start_on_monday() {
+    // 2024-01-04 was a thursday
+    let thursday = Date::parse("2024-01-04").unwrap();
+    assert_eq!(thursday.weekday(), 3);
+    assert_eq!(thursday.monday().to_string(), "2024-01-01");
+
+    let sunday = Date::parse("2024-01-07").unwrap();
+    assert_eq!(sunday.weekday(), 6);
+    assert_eq!(sunday.monday().to_string(), "2024-01-01");
+    assert_eq!(sunday.monday().monday(), sunday.monday());
+}
+
+#[actix_web::test]
+async fn meals_are_planned_moved_and_removed() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    assert_eq!(
+        client.call(new_recipe("Soup")).await,
+        Ok(RecipeChange::Done)
+    );
+    let ids = recipe_ids(client.call(get_recipes()).await.unwrap());
+
+    assert_eq!(
+        client
+            .call(plan("2024-01-03", Meal::Lunch, &ids[0], None))
+            .await,
+        Ok(PlannerChange::Done)
+    );
+    assert_eq!(
+        client
+            .call(plan("2024-01-03", Meal::Dinner, &ids[1], Some(2)))
+            .await,
+        Ok(PlannerChange::Done)
+    );
+
+    let week = client.call(get_week("2024-01-06")).await.unwrap().unwrap();
+    assert_eq!(week.start, "2024-01-01");
+    assert_eq!(week.previous, "2023-12-25");
+    assert_eq!(week.next, "2024-01-08");
+    assert_eq!(week.days.len(), 7);
+    assert_eq!(
+        week.days[2].meals,
+        [
+            PlannedMealInfo {
+                meal: Meal::Lunch,
+                recipe: ids[0].clone(),
+                name: Some("Pancakes".to_string()),
+                servings: 4,
+            },
+            PlannedMealInfo {
+                meal: Meal::Dinner,
+                recipe: ids[1].clone(),
+                name: Some("Soup".to_string()),
+                servings: 2,
+            },
+        ]
+    );
+
+    // Moving to an occupied slot swaps the meals
+    assert_eq!(
+        client
+            .call(move_meal(
+                "2024-01-03",
+                Meal::Lunch,
+                "2024-01-03",
+                Meal::Dinner
+            ))
+            .await,
+        Ok(PlannerChange::Done)
+    );
+    let week = client.call(get_week("2024-01-01")).await.unwrap().unwrap();
+    let mut wednesday = meals(&week, "2024-01-03");
+    wednesday.sort_by_key(|(meal, _, _)| *meal as u8);
+    assert_eq!(
+        wednesday,
+        [
+            (Meal::Lunch, "Soup".to_string(), 2),
+            (Meal::Dinner, "Pancakes".to_string(), 4),
+        ]
+    );
+
+    // Moving to an empty slot of another week leaves the previous slot empty
+    assert_eq!(
+        client
+            .call(move_meal(
+                "2024-01-03",
+                Meal::Dinner,
+                "2024-01-08",
+                Meal::Breakfast
+            ))
+            .await,
+        Ok(PlannerChange::Done)
+    );
+    let week = client.call(get_week("2024-01-01")).await.unwrap().unwrap();
+    assert_eq!(
+        meals(&week, "2024-01-03"),
+        [(Meal::Lunch, "Soup".to_string(), 2)]
+    );
+    let week = client.call(get_week(&week.next)).await.unwrap().unwrap();
+    assert_eq!(
+        meals(&week, "2024-01-08"),
+        [(Meal::Breakfast, "Pancakes".to_string(), 4)]
+    );
+
+    // Planning again in a slot changes its servings
+    assert_eq!(
+        client
+            .call(plan("2024-01-08", Meal::Breakfast, &ids[0], Some(6)))
+            .await,
+        Ok(PlannerChange::Done)
+    );
+    assert_eq!(
+        client
+            .call(UnplanMeal {
+                date: "2024-01-03".to_string(),
+                meal: Meal::Lunch,
+            })
+            .await,
+        Ok(PlannerChange::Done)
+    );
+
+    let week = client.call(get_week("2024-01-01")).await.unwrap().unwrap();
+    assert!(week.days.iter().all(|day| day.meals.is_empty()));
+    let week = client.call(get_week("2024-01-08")).await.unwrap().unwrap();
+    assert_eq!(
+        meals(&week, "2024-01-08"),
+        [(Meal::Breakfast, "Pancakes".to_string(), 6)]
+    );
+}
+
+#[actix_web::test]
+async fn plans_changed_concurrently_are_not_overwritten() {
+    let store = test_store().await;
+    let user = ObjectId::new();
+    let date = Date::parse("2024-01-01").unwrap();
+    let plan = |meals: &[Meal], version| MealPlan {
+        user,
+        date,
+        meals: meals
+            .iter()
+            .map(|&meal| PlannedMeal {
+                meal,
+                recipe: ObjectId::new(),
+                servings: 4,
+            })
+            .collect(),
+        version,
+    };
+    let stored = || async {
+        store
+            .find_meal_plans(user, date, date.add_days(1))
+            .await
+            .unwrap()
+    };
+
+    assert!(store.set_meal_plan(plan(&[Meal::Lunch], 0)).await.unwrap());
+    assert!(!store.set_meal_plan(plan(&[Meal::Dinner], 0)).await.unwrap());
+    assert_eq!(stored().await[0].version, 1);
+
+    // A plan emptied concurrently isn't planned again from its previous version
+    assert!(store.set_meal_plan(plan(&[], 1)).await.unwrap());
+    assert!(stored().await.is_empty());
+    assert!(!store.set_meal_plan(plan(&[Meal::Dinner], 1)).await.unwrap());
+    assert!(stored().await.is_empty());
+
+    assert!(store.set_meal_plan(plan(&[Meal::Dinner], 0)).await.unwrap());
+    let plans = stored().await;
+    assert_eq!(plans.len(), 1);
+    assert_eq!(plans[0].meals[0].meal, Meal::Dinner);
+}
+
+#[actix_web::test]
+async fn planned_week_builds_the_shopping_list() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    assert_eq!(
+        client.call(new_recipe("Crepes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let ids = recipe_ids(client.call(get_recipes()).await.unwrap());
+
+    // Both recipes need 250 g of flour for 4
+    for (date, meal, recipe, servings) in [
+        ("2024-01-01", Meal::Breakfast, &ids[0], Some(8)),
+        ("2024-01-07", Meal::Dinner, &ids[1], None),
+        ("2024-01-08", Meal::Dinner, &ids[1], None),
+    ] {
+        assert_eq!(
+            client.call(plan(date, meal, recipe, servings)).await,
+            Ok(PlannerChange::Done)
+        );
+    }
+
+    assert_eq!(
+        client
+            .call(BuildWeekShoppingList {
+                week: "2024-01-03".to_string(),
+            })
+            .await,
+        Ok(PlannerChange::Done)
+    );
+
+    let items = client.call(GetShoppingList {}).await.unwrap().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name, "Flour");
+    assert_eq!(items[0].quantities_text(), "750 g");
+}
+
+#[actix_web::test]
+async fn meal_plans_are_per_user() {
+    let store = test_store().await;
+    let mut alice = client(store.clone()).await;
+    let mut bob = client(store).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        alice.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let ids = recipe_ids(alice.call(get_recipes()).await.unwrap());
+    assert_eq!(bob.call(signup("bob", "password2")).await, Ok(true));
+
+    assert_eq!(
+        alice
+            .call(plan("2024-01-01", Meal::Lunch, &ids[0], None))
+            .await,
+        Ok(PlannerChange::Done)
+    );
+
+    let week = bob.call(get_week("2024-01-01")).await.unwrap().unwrap();
+    assert!(week.days.iter().all(|day| day.meals.is_empty()));
+    assert_eq!(
+        bob.call(move_meal(
+            "2024-01-01",
+            Meal::Lunch,
+            "2024-01-02",
+            Meal::Lunch
+        ))
+        .await,
+        Ok(PlannerChange::NotFound)
+    );
+}
+
+#[actix_web::test]
+async fn invalid_changes_are_rejected() {
+    let mut client = empty_client().await;
+
+    assert!(matches!(client.call(get_week("")).await, Ok(None)));
+    assert_eq!(
+        client
+            .call(plan(
+                "2024-01-01",
+                Meal::Lunch,
+                &ObjectId::new().to_hex(),
+                None
+            ))
+            .await,
+        Ok(PlannerChange::InvalidCredentials)
+    );
+
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let ids = recipe_ids(client.call(get_recipes()).await.unwrap());
+
+    // The current week when no valid day is given
+    let week = client.call(get_week("")).await.unwrap().unwrap();
+    assert_eq!(week.start, Date::today().monday().to_string());
+    let week = client.call(get_week("yesterday")).await.unwrap().unwrap();
+    assert_eq!(week.start, Date::today().monday().to_string());
+
+    assert_eq!(
+        client
+            .call(plan("2024-02-30", Meal::Lunch, &ids[0], None))
+            .await,
+        Ok(PlannerChange::InvalidDate)
+    );
+    assert_eq!(
+        client
+            .call(plan(
+                "2024-01-01",
+                Meal::Lunch,
+                &ObjectId::new().to_hex(),
+                None
+            ))
+            .await,
+        Ok(PlannerChange::NotFound)
+    );
+    assert_eq!(
+        client
+            .call(plan("2024-01-01", Meal::Lunch, &ids[0], Some(0)))
+            .await,
+        Ok(PlannerChange::InvalidServings)
+    );
+    assert_eq!(
+        client
+            .call(move_meal(
+                "2024-01-01",
+                Meal::Lunch,
+                "2024-01-02",
+                Meal::Lunch
+            ))
+            .await,
+        Ok(PlannerChange::NotFound)
+    );
+    assert_eq!(
+        client
+            .call(BuildWeekShoppingList {
+                week: "next week".to_string(),
+            })
+            .await,
+        Ok(PlannerChange::InvalidDate)
+    );
+}
+
+#[actix_web::test]
+async fn planner_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+    client.set_cookie("name", "alice");
+    client.set_cookie("token", "token");
+
+    assert_eq!(
+        client.call(get_week("2024-01-01")).await.map(|_| ()),
+        Err(ServerFnError::ServerError("".into()))
+    );
+    assert_eq!(
+        client
+            .call(UnplanMeal {
+                date: "2024-01-01".to_string(),
+                meal: Meal::Lunch,
+            })
+            .await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+}
@@ -0,0 +1,197 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use leptos::ServerFnError;
+use recipe_final_exercise::app::{
+    recipe::{GetRecipe, RateRecipe, RecipeChange},
+    users::{profile_name, profile_path, GetUserProfile},
+};
+
+use common::{client, empty_client, get_recipes, new_recipe, signup, test_store, FailingStore};
+
+fn get_profile(name: &str) -> GetUserProfile {
+    GetUserProfile {
+        name: name.to_string(),
+    }
+}
+
+#[actix_web::test]
+async fn recipes_carry_the_name_of_their_author() {
+    let store = test_store().await;
+    let mut alice = client(store.clone()).await;
+    let mut bob = client(store).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(bob.call(signup("bob", "password1")).await, Ok(true));
+    assert_eq!(
+        alice.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    assert_eq!(bob.call(new_recipe("Crepes")).await, Ok(RecipeChange::Done));
+
+    let recipes = alice.call(get_recipes()).await.unwrap().recipes;
+    let authors: Vec<_> = recipes
+        .iter()
+        .map(|recipe| (recipe.name.as_str(), recipe.author.as_deref()))
+        .collect();
+    assert_eq!(
+        authors,
+        [("Pancakes", Some("alice")), ("Crepes", Some("bob"))]
+    );
+
+    let details = bob
+        .call(GetRecipe {
+            id: recipes[0].id.clone(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(details.author.as_deref(), Some("alice"));
+}
+
+#[actix_web::test]
+async fn profile_lists_the_recipes_and_ratings_received() {
+    let store = test_store().await;
+    let mut alice = client(store.clone()).await;
+    let mut bob = client(store).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(bob.call(signup("bob", "password1")).await, Ok(true));
+    for name in ["Pancakes", "Waffles"] {
+        assert_eq!(alice.call(new_recipe(name)).await, Ok(RecipeChange::Done));
+    }
+    assert_eq!(bob.call(new_recipe("Crepes")).await, Ok(RecipeChange::Done));
+
+    let recipes = bob.call(get_recipes()).await.unwrap().recipes;
+    for (recipe, note) in [(&recipes[0], 5), (&recipes[1], 2), (&recipes[2], 1)] {
+        assert_eq!(
+            bob.call(RateRecipe {
+                id: recipe.id.clone(),
+                note,
+            })
+            .await,
+            Ok(RecipeChange::Done)
+        );
+    }
+    assert_eq!(
+        alice
+            .call(RateRecipe {
+                id: recipes[0].id.clone(),
+                note: 4,
+            })
+            .await,
+        Ok(RecipeChange::Done)
+    );
+
+    let profile = bob.call(get_profile("alice")).await.unwrap().unwrap();
+    assert_eq!(profile.name, "alice");
+    assert_eq!(profile.joined.len(), "YYYY-MM-DD".len());
+    let names: Vec<_> = profile
+        .recipes
+        .iter()
+        .map(|recipe| recipe.name.as_str())
+        .collect();
+    assert_eq!(names, ["Pancakes", "Waffles"]);
+    assert_eq!(
+        (profile.rating, profile.rating_count),
+        (Some(11.0 / 3.0), 3)
+    );
+
+    let profile = alice.call(get_profile("bob")).await.unwrap().unwrap();
+    assert_eq!((profile.rating, profile.rating_count), (Some(1.0), 1));
+}
+
+#[actix_web::test]
+async fn profile_of_a_user_without_recipes_is_empty() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+
+    let profile = client.call(get_profile("alice")).await.unwrap().unwrap();
+    assert!(profile.recipes.is_empty());
+    assert_eq!((profile.rating, profile.rating_count), (None, 0));
+}
+
+/// Decode `path` like `decodeURI`, which the router runs on client side navigations:
+/// escapes of the reserved characters are kept
+fn decode_uri(path: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = path.as_bytes();
+    while let [first, tail @ ..] = rest {
+        match (first, tail) {
+            (b'%', [high, low, tail @ ..]) => {
+                let hex = [*high, *low];
+                let byte = u8::from_str_radix(std::str::from_utf8(&hex).unwrap(), 16).unwrap();
+                if ";/?:@&=+$,#".as_bytes().contains(&byte) {
+                    bytes.extend_from_slice(&[b'%', *high, *low]);
+                } else {
+                    bytes.push(byte);
+                }
+                rest = tail;
+            }
+            _ => {
+                bytes.push(*first);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).unwrap()
+}
+
+#[actix_web::test]
+async fn profile_paths_round_trip_user_names() {
+    let mut client = empty_client().await;
+    let name = "a?b#c d&é";
+    assert_eq!(client.call(signup(name, "password1")).await, Ok(true));
+
+    let path = profile_path(name);
+    assert_eq!(path, "/users/a%3Fb%23c%20d%26%C3%A9");
+
+    // The server gets the path as sent, the client gets it partially decoded by the router
+    let server_segment = path.strip_prefix("/users/").unwrap().to_string();
+    let client_segment = decode_uri(&path)
+        .strip_prefix("/users/")
+        .unwrap()
+        .to_string();
+    assert_eq!(client_segment, "a%3Fb%23c d%26é");
+    for segment in [server_segment, client_segment] {
+        assert_eq!(profile_name(&segment), name);
+        let profile = client
+            .call(get_profile(&profile_name(&segment)))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(profile.name, name);
+    }
+}
+
+#[actix_web::test]
+async fn names_decoded_differently_by_the_router_are_rejected() {
+    let mut client = empty_client().await;
+
+    // `%41` would be decoded once by the server but twice by the client
+    for name in ["a%41", "a/b", "a%2Fb", ""] {
+        assert_eq!(
+            client.call(signup(name, "password1")).await,
+            Err(ServerFnError::Args("Invalid name".into()))
+        );
+    }
+    assert_eq!(client.cookie("token"), None);
+}
+
+#[actix_web::test]
+async fn profile_of_an_unknown_user_is_not_found() {
+    let mut client = empty_client().await;
+
+    assert!(client.call(get_profile("alice")).await.unwrap().is_none());
+}
+
+#[actix_web::test]
+async fn profile_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+
+    assert!(matches!(
+        client.call(get_profile("alice")).await,
+        Err(ServerFnError::ServerError(_))
+    ));
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use recipe_final_exercise::{
+    app::recipe::{GetRecipe, RecipeChange},
+    model::Ingredient,
+    quantity::{Amount, Quantity, Unit},
+};
+
+use common::{empty_client, get_recipes, new_recipe, signup};
+
+fn measure(numerator: u64, denominator: u64, unit: Unit) -> Quantity {
+    Quantity::Measure {
+        amount: Amount::new(numerator, denominator).unwrap(),
+        unit,
+    }
+}
+
+#[test]
+fn common_free_text_quantities_are_parsed() {
+    for (text, quantity) in [
+        ("200g", measure(200, 1, Unit::Gram)),
+        ("200 g", measure(200, 1, Unit::Gram)),
+        ("1 1/2 cups", measure(3, 2, Unit::Cup)),
+        ("1/2 cup", measure(1, 2, Unit::Cup)),
+        ("½ tsp", measure(1, 2, Unit::Teaspoon)),
+        ("1½ Tbsp.", measure(3, 2, Unit::Tablespoon)),
+        ("1.5 kg", measure(3, 2, Unit::Kilogram)),
+        ("0,25 l", measure(1, 4, Unit::Liter)),
+        ("2 fl oz", measure(2, 1, Unit::FluidOunce)),
+        ("3", measure(3, 1, Unit::Count)),
+        (" To taste ", Quantity::ToTaste),
+    ] {
+        assert_eq!(Quantity::parse(text), quantity, "{text}");
+    }
+}
+
+#[test]
+fn unparseable_quantities_are_kept_as_raw_text() {
+    for text in [
+        "",
+        "a pinch",
+        "2 eggs",
+        "1/0 cup",
+        "2 3 cups",
+        "1.",
+        "99999999999 g",
+    ] {
+        assert_eq!(
+            Quantity::parse(text),
+            Quantity::Raw {
+                text: text.to_string()
+            },
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn quantities_are_shown_in_the_style_of_their_unit() {
+    for (text, shown) in [
+        ("1 1/2 cups", "1 1/2 cups"),
+        ("0.5 cup", "1/2 cup"),
+        ("1.5 kg", "1.5 kg"),
+        ("1/3 l", "0.33 l"),
+        ("3", "3"),
+        ("to taste", "to taste"),
+        ("a pinch", "a pinch"),
+    ] {
+        assert_eq!(Quantity::parse(text).to_string(), shown, "{text}");
+    }
+}
+
+#[test]
+fn amounts_are_kept_irreducible() {
+    let half = Amount::new(2, 4).unwrap();
+    assert_eq!((half.numerator(), half.denominator()), (1, 2));
+    assert_eq!(
+        half + Amount::new(1, 3).unwrap(),
+        Amount::new(5, 6).unwrap()
+    );
+    assert_eq!(half * Amount::integer(4), Amount::integer(2));
+    assert_eq!(Amount::new(1, 0), None);
+    assert_eq!(Amount::approximate(0.125), Amount::new(1, 8).unwrap());
+}
+
+#[test]
+fn quantities_are_stored_as_documents() {
+    let quantity = Quantity::parse("1 1/2 cups");
+    let stored = bson::to_bson(&quantity).unwrap();
+
+    assert_eq!(
+        stored,
+        bson::bson!({"kind": "Measure", "amount": [3_i64, 2_i64], "unit": "Cup"})
+    );
+    assert_eq!(bson::from_bson::<Quantity>(stored).unwrap(), quantity);
+    assert!(bson::from_bson::<Quantity>(
+        bson::bson!({"kind": "Measure", "amount": [1, 0], "unit": "Cup"})
+    )
+    .is_err());
+}
+
+#[actix_web::test]
+async fn recipes_keep_their_structured_quantities() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+
+    let mut recipe = new_recipe("Pancakes");
+    recipe.ingredients.push(Ingredient {
+        name: "Salt".to_string(),
+        icon_url: String::new(),
+        quantity: Quantity::parse("a pinch"),
+    });
+    assert_eq!(client.call(recipe).await, Ok(RecipeChange::Done));
+    let id = client
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    let details = client.call(GetRecipe { id }).await.unwrap().unwrap();
+    let quantities: Vec<_> = details
+        .ingredients
+        .into_iter()
+        .map(|ingredient| ingredient.quantity)
+        .collect();
+    assert_eq!(
+        quantities,
+        [
+            measure(250, 1, Unit::Gram),
+            Quantity::Raw {
+                text: "a pinch".to_string()
+            }
+        ]
+    );
+}
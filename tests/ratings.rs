@@ -0,0 +1,189 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use leptos::ServerFnError;
+use recipe_final_exercise::app::{
+    recipe::{GetRecipe, RateRecipe, RecipeChange},
+    recipes::{GetRecipes, RecipeFilter, RecipeInfo, RecipeSort},
+};
+
+use common::{client, get_recipes, new_recipe, signup, test_store, FailingStore};
+
+fn rate(recipe: &str, note: u8) -> RateRecipe {
+    RateRecipe {
+        id: recipe.to_string(),
+        note,
+    }
+}
+
+fn ratings(recipes: &[RecipeInfo]) -> Vec<(&str, Option<f32>, u32)> {
+    recipes
+        .iter()
+        .map(|recipe| (recipe.name.as_str(), recipe.rating, recipe.rating_count))
+        .collect()
+}
+
+#[actix_web::test]
+async fn ratings_are_averaged() {
+    let store = test_store().await;
+    let mut alice = client(store.clone()).await;
+    let mut bob = client(store).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(bob.call(signup("bob", "password1")).await, Ok(true));
+    assert_eq!(
+        alice.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let recipe = alice
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    assert_eq!(alice.call(rate(&recipe, 5)).await, Ok(RecipeChange::Done));
+    assert_eq!(bob.call(rate(&recipe, 2)).await, Ok(RecipeChange::Done));
+
+    let recipes = alice.call(get_recipes()).await.unwrap().recipes;
+    assert_eq!(ratings(&recipes), [("Pancakes", Some(3.5), 2)]);
+
+    let details = alice.call(GetRecipe { id: recipe }).await.unwrap().unwrap();
+    assert_eq!((details.rating, details.rating_count), (Some(3.5), 2));
+}
+
+#[actix_web::test]
+async fn rating_again_replaces_the_previous_note() {
+    let mut alice = client(test_store().await).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        alice.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let recipe = alice
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    assert_eq!(alice.call(rate(&recipe, 1)).await, Ok(RecipeChange::Done));
+    assert_eq!(alice.call(rate(&recipe, 4)).await, Ok(RecipeChange::Done));
+
+    let recipes = alice.call(get_recipes()).await.unwrap().recipes;
+    assert_eq!(ratings(&recipes), [("Pancakes", Some(4.0), 1)]);
+}
+
+#[actix_web::test]
+async fn notes_outside_of_the_range_are_rejected() {
+    let mut alice = client(test_store().await).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        alice.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let recipe = alice
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    for note in [0, 6, u8::MAX] {
+        assert!(matches!(
+            alice.call(rate(&recipe, note)).await,
+            Err(ServerFnError::Args(_))
+        ));
+    }
+
+    let recipes = alice.call(get_recipes()).await.unwrap().recipes;
+    assert_eq!(ratings(&recipes), [("Pancakes", None, 0)]);
+}
+
+#[actix_web::test]
+async fn rating_requires_credentials_and_an_existing_recipe() {
+    let mut alice = client(test_store().await).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        alice.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let recipe = alice
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    assert_eq!(
+        alice.call(rate("0123456789abcdef01234567", 3)).await,
+        Ok(RecipeChange::NotFound)
+    );
+
+    alice.clear_cookies();
+    assert_eq!(
+        alice.call(rate(&recipe, 3)).await,
+        Ok(RecipeChange::InvalidCredentials)
+    );
+}
+
+#[actix_web::test]
+async fn recipes_can_be_sorted_by_rating() {
+    let mut alice = client(test_store().await).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+
+    for (name, note) in [("Unrated", None), ("Bad", Some(1)), ("Good", Some(5))] {
+        assert_eq!(alice.call(new_recipe(name)).await, Ok(RecipeChange::Done));
+        if let Some(note) = note {
+            let recipe = alice
+                .call(get_recipes())
+                .await
+                .unwrap()
+                .recipes
+                .pop()
+                .unwrap()
+                .id;
+            assert_eq!(
+                alice.call(rate(&recipe, note)).await,
+                Ok(RecipeChange::Done)
+            );
+        }
+    }
+
+    let recipes = alice
+        .call(GetRecipes {
+            sort: RecipeSort::Rating,
+            filter: RecipeFilter::default(),
+            cursor: None,
+            page_size: 50,
+        })
+        .await
+        .unwrap()
+        .recipes;
+    assert_eq!(
+        ratings(&recipes),
+        [
+            ("Good", Some(5.0), 1),
+            ("Bad", Some(1.0), 1),
+            ("Unrated", None, 0)
+        ]
+    );
+}
+
+#[actix_web::test]
+async fn rating_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+    client.set_cookie("name", "alice");
+    client.set_cookie("token", "token");
+
+    assert_eq!(
+        client.call(rate("0123456789abcdef01234567", 3)).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use leptos::ServerFnError;
+use recipe_final_exercise::app::recipe::RecipeChange;
+
+use common::{client, empty_client, get_recipes, new_recipe, signup, FailingStore};
+
+#[actix_web::test]
+async fn get_recipes_is_empty_at_first() {
+    let mut client = empty_client().await;
+
+    let recipes = client.call(get_recipes()).await.unwrap().recipes;
+    assert!(recipes.is_empty());
+}
+
+#[actix_web::test]
+async fn new_recipe_is_listed_by_get_recipes() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    assert_eq!(
+        client.call(new_recipe("Crepes")).await,
+        Ok(RecipeChange::Done)
+    );
+
+    let recipes = client.call(get_recipes()).await.unwrap().recipes;
+    let names: Vec<_> = recipes.iter().map(|recipe| recipe.name.as_str()).collect();
+    assert_eq!(names, ["Pancakes", "Crepes"]);
+    assert_eq!(recipes[0].icon_url, "https://example.com/pancakes.png");
+    assert_ne!(recipes[0].id, recipes[1].id);
+}
+
+#[actix_web::test]
+async fn new_recipe_without_credentials_is_rejected() {
+    let mut client = empty_client().await;
+
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::InvalidCredentials)
+    );
+    assert!(client.call(get_recipes()).await.unwrap().recipes.is_empty());
+}
+
+#[actix_web::test]
+async fn new_recipe_with_invalid_token_is_rejected() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    client.set_cookie("token", "forged token");
+
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::InvalidCredentials)
+    );
+    assert!(client.call(get_recipes()).await.unwrap().recipes.is_empty());
+}
+
+#[actix_web::test]
+async fn new_recipe_with_unknown_user_is_rejected() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    client.set_cookie("name", "bob");
+
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::InvalidCredentials)
+    );
+    assert!(client.call(get_recipes()).await.unwrap().recipes.is_empty());
+}
+
+#[actix_web::test]
+async fn new_recipe_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+    client.set_cookie("name", "alice");
+    client.set_cookie("token", "token");
+
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+}
+
+#[actix_web::test]
+async fn get_recipes_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+
+    assert!(matches!(
+        client.call(get_recipes()).await,
+        Err(ServerFnError::ServerError(_))
+    ));
+}
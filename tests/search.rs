@@ -0,0 +1,113 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use leptos::ServerFnError;
+use recipe_final_exercise::{
+    app::{
+        recipe::RecipeChange,
+        recipes::{RecipeFilter, RecipePageInfo, SearchRecipes},
+    },
+    model::Ingredient,
+    quantity::Quantity,
+};
+
+use common::{client, empty_client, new_recipe, signup, FailingStore};
+
+fn search(query: &str, page: usize) -> SearchRecipes {
+    SearchRecipes {
+        query: query.to_string(),
+        filter: RecipeFilter::default(),
+        page,
+    }
+}
+
+fn names(page: &RecipePageInfo) -> Vec<&str> {
+    page.recipes
+        .iter()
+        .map(|recipe| recipe.name.as_str())
+        .collect()
+}
+
+#[actix_web::test]
+async fn name_matches_are_more_relevant_than_ingredients_and_instructions() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+
+    let mut soup = new_recipe("Soup");
+    soup.instructions = vec!["Serve with chocolate shavings".to_string()];
+    let mut cake = new_recipe("Cake");
+    cake.ingredients.push(Ingredient {
+        name: "Chocolate".to_string(),
+        icon_url: String::new(),
+        quantity: Quantity::parse("200g"),
+    });
+    for recipe in [
+        soup,
+        cake,
+        new_recipe("Chocolate mousse"),
+        new_recipe("Salad"),
+    ] {
+        assert_eq!(client.call(recipe).await, Ok(RecipeChange::Done));
+    }
+
+    let page = client.call(search("chocolate", 0)).await.unwrap();
+    assert_eq!(names(&page), ["Chocolate mousse", "Cake", "Soup"]);
+    assert_eq!((page.page, page.page_count), (0, 1));
+
+    let page = client.call(search("pizza", 0)).await.unwrap();
+    assert!(page.recipes.is_empty());
+}
+
+#[actix_web::test]
+async fn search_results_are_paginated() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    for i in 0..25 {
+        assert_eq!(
+            client.call(new_recipe(&format!("Pancakes {}", i))).await,
+            Ok(RecipeChange::Done)
+        );
+    }
+
+    let first = client.call(search("pancakes", 0)).await.unwrap();
+    assert_eq!(first.recipes.len(), 20);
+    assert_eq!((first.page, first.page_count), (0, 2));
+
+    let second = client.call(search("pancakes", 1)).await.unwrap();
+    assert_eq!(second.recipes.len(), 5);
+    assert_eq!((second.page, second.page_count), (1, 2));
+
+    // Pages after the last one, like a stale link, give the last page
+    for page in [2, 1 << 40, usize::MAX] {
+        let last = client.call(search("pancakes", page)).await.unwrap();
+        assert_eq!(names(&last), names(&second));
+        assert_eq!((last.page, last.page_count), (1, 2));
+    }
+}
+
+#[actix_web::test]
+async fn blank_search_matches_nothing() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+
+    let page = client.call(search("  ", 0)).await.unwrap();
+    assert!(page.recipes.is_empty());
+    assert_eq!(page.page_count, 1);
+}
+
+#[actix_web::test]
+async fn search_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+
+    assert!(matches!(
+        client.call(search("pancakes", 0)).await,
+        Err(ServerFnError::ServerError(_))
+    ));
+}
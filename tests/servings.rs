@@ -0,0 +1,94 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use recipe_final_exercise::{
+    app::{
+        edit_recipe::UpdateRecipe,
+        recipe::{GetRecipe, RecipeChange},
+    },
+    quantity::{Amount, Quantity},
+};
+
+use common::{empty_client, get_recipes, new_recipe, signup};
+
+fn scaled(text: &str, servings: u64, base: u64) -> String {
+    Quantity::parse(text)
+        .scaled(Amount::new(servings, base).unwrap())
+        .to_string()
+}
+
+#[test]
+fn scaled_quantities_are_promoted_to_larger_units() {
+    assert_eq!(scaled("8 tbsp", 4, 2), "1 cup");
+    assert_eq!(scaled("1 1/2 tsp", 4, 2), "1 tbsp");
+    assert_eq!(scaled("2 cups", 4, 2), "1 quart");
+    assert_eq!(scaled("600 g", 4, 2), "1.2 kg");
+    assert_eq!(scaled("500 ml", 4, 2), "1 l");
+    assert_eq!(scaled("12 oz", 4, 3), "1 lb");
+}
+
+#[test]
+fn scaled_quantities_are_demoted_to_smaller_units() {
+    assert_eq!(scaled("1/4 cup", 1, 2), "2 tbsp");
+    assert_eq!(scaled("1 kg", 1, 4), "250 g");
+    assert_eq!(scaled("1 tbsp", 1, 3), "1 tsp");
+}
+
+#[test]
+fn scaled_quantities_are_rounded_to_measurable_amounts() {
+    assert_eq!(scaled("1 cup", 4, 3), "1 1/3 cups");
+    assert_eq!(scaled("3", 1, 8), "1/2");
+    assert_eq!(scaled("3", 5, 4), "4");
+    assert_eq!(scaled("1 tsp", 1, 100), "1/8 tsp");
+    assert_eq!(scaled("333 g", 1, 3), "111 g");
+    assert_eq!(scaled("5 g", 1, 3), "1.7 g");
+}
+
+#[test]
+fn only_measures_are_scaled_and_only_when_the_servings_change() {
+    assert_eq!(scaled("16 tbsp", 4, 4), "16 tbsp");
+    assert_eq!(scaled("to taste", 8, 4), "to taste");
+    assert_eq!(scaled("a pinch", 8, 4), "a pinch");
+}
+
+#[actix_web::test]
+async fn recipes_keep_their_servings() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+
+    let mut recipe = new_recipe("Pancakes");
+    recipe.servings = 6;
+    assert_eq!(client.call(recipe).await, Ok(RecipeChange::Done));
+    let id = client
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    let details = client
+        .call(GetRecipe { id: id.clone() })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(details.servings, 6);
+
+    let update = UpdateRecipe {
+        id: id.clone(),
+        name: details.name,
+        icon_url: details.icon_url,
+        price_level: details.price_level,
+        healthy_level: details.healthy_level,
+        servings: 2,
+        instructions: details.instructions,
+        ingredients: details.ingredients,
+        tools: details.tools,
+        categories: Vec::new(),
+    };
+    assert_eq!(client.call(update).await, Ok(RecipeChange::Done));
+
+    let details = client.call(GetRecipe { id }).await.unwrap().unwrap();
+    assert_eq!(details.servings, 2);
+}
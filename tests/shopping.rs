@@ -0,0 +1,289 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use bson::oid::ObjectId;
+use leptos::ServerFnError;
+use recipe_final_exercise::{
+    app::{
+        recipe::RecipeChange,
+        shopping::{
+            AddToShoppingList, ClearShoppingList, ExportShoppingList, GetShoppingList,
+            SetShoppingItemChecked, ShoppingChange,
+        },
+    },
+    model::{Ingredient, ShoppingList},
+    quantity::{Amount, Quantity},
+};
+
+use common::{client, empty_client, get_recipes, new_recipe, signup, test_store, FailingStore};
+
+fn ingredient(name: &str, quantity: &str) -> Ingredient {
+    Ingredient {
+        name: name.to_string(),
+        icon_url: String::new(),
+        quantity: Quantity::parse(quantity),
+    }
+}
+
+fn quantities(list: &ShoppingList, name: &str) -> String {
+    list.items
+        .iter()
+        .find(|item| item.name == name)
+        .unwrap()
+        .quantities_text()
+}
+
+fn add_recipe(recipe: &str, servings: Option<u8>) -> AddToShoppingList {
+    AddToShoppingList {
+        recipe: recipe.to_string(),
+        servings,
+    }
+}
+
+fn check(item: &str, checked: bool) -> SetShoppingItemChecked {
+    SetShoppingItemChecked {
+        item: item.to_string(),
+        checked,
+    }
+}
+
+#[test]
+fn identical_ingredients_are_merged_and_compatible_quantities_summed() {
+    let mut list = ShoppingList::new(ObjectId::new());
+    list.add(&ingredient("Flour", "500 g"), Amount::ONE);
+    list.add(&ingredient(" flour ", "1 kg"), Amount::ONE);
+    list.add(&ingredient("Milk", "1 cup"), Amount::ONE);
+    list.add(&ingredient("milk", "4 tbsp"), Amount::ONE);
+    list.add(&ingredient("Eggs", "2"), Amount::integer(2));
+    list.add(&ingredient("Eggs", "1"), Amount::ONE);
+
+    assert_eq!(list.items.len(), 3);
+    assert_eq!(quantities(&list, "Flour"), "1.5 kg");
+    assert_eq!(quantities(&list, "Milk"), "1 1/4 cups");
+    assert_eq!(quantities(&list, "Eggs"), "5");
+}
+
+#[test]
+fn incompatible_quantities_are_kept_apart() {
+    let mut list = ShoppingList::new(ObjectId::new());
+    list.add(&ingredient("Flour", "200 g"), Amount::ONE);
+    list.add(&ingredient("Flour", "1 cup"), Amount::ONE);
+    list.add(&ingredient("Salt", "to taste"), Amount::ONE);
+    list.add(&ingredient("Salt", "a pinch"), Amount::ONE);
+    list.add(&ingredient("Salt", "to taste"), Amount::ONE);
+    list.add(&ingredient("Pepper", ""), Amount::ONE);
+
+    assert_eq!(quantities(&list, "Flour"), "200 g + 1 cup");
+    assert_eq!(quantities(&list, "Salt"), "to taste + a pinch");
+    assert_eq!(quantities(&list, "Pepper"), "");
+}
+
+#[test]
+fn export_has_one_line_per_item() {
+    let mut list = ShoppingList::new(ObjectId::new());
+    list.add(&ingredient("Flour", "500 g"), Amount::ONE);
+    list.add(&ingredient("Pepper", ""), Amount::ONE);
+    list.items[0].checked = true;
+
+    assert_eq!(list.export(), "[x] Flour: 500 g\n[ ] Pepper\n");
+}
+
+#[actix_web::test]
+async fn lists_changed_concurrently_are_not_overwritten() {
+    let store = test_store().await;
+    let user = ObjectId::new();
+
+    // Two devices start from the same empty list, only the first one can store it
+    let mut first = ShoppingList::new(user);
+    first.add(&ingredient("Flour", "500 g"), Amount::ONE);
+    let mut second = ShoppingList::new(user);
+    second.add(&ingredient("Milk", "1 cup"), Amount::ONE);
+    assert!(store.set_shopping_list(first).await.unwrap());
+    assert!(!store.set_shopping_list(second).await.unwrap());
+
+    // Starting again from the stored list keeps both changes
+    let mut stored = store.find_shopping_list(user).await.unwrap().unwrap();
+    assert_eq!(stored.version, 1);
+    stored.add(&ingredient("Milk", "1 cup"), Amount::ONE);
+    let stale = stored.clone();
+    assert!(store.set_shopping_list(stored).await.unwrap());
+    assert!(!store.set_shopping_list(stale).await.unwrap());
+
+    let stored = store.find_shopping_list(user).await.unwrap().unwrap();
+    assert_eq!(stored.version, 2);
+    assert_eq!(quantities(&stored, "Flour"), "500 g");
+    assert_eq!(quantities(&stored, "Milk"), "1 cup");
+}
+
+#[actix_web::test]
+async fn recipes_are_added_scaled_and_items_checked_off() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let id = client
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    assert_eq!(client.call(GetShoppingList {}).await, Ok(Some(Vec::new())));
+
+    // 250 g of flour for 4, twice as much for 8, plus 250 g for 4
+    assert_eq!(
+        client.call(add_recipe(&id, Some(8))).await,
+        Ok(ShoppingChange::Done)
+    );
+    assert_eq!(
+        client.call(add_recipe(&id, None)).await,
+        Ok(ShoppingChange::Done)
+    );
+
+    let items = client.call(GetShoppingList {}).await.unwrap().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name, "Flour");
+    assert_eq!(items[0].quantities_text(), "750 g");
+    assert!(!items[0].checked);
+
+    assert_eq!(
+        client.call(check("flour", true)).await,
+        Ok(ShoppingChange::Done)
+    );
+    assert_eq!(
+        client.call(check("Sugar", true)).await,
+        Ok(ShoppingChange::NotFound)
+    );
+    assert_eq!(
+        client.call(ExportShoppingList {}).await,
+        Ok(Some("[x] Flour: 750 g\n".to_string()))
+    );
+
+    // Adding more of a checked item means it must be bought again
+    assert_eq!(
+        client.call(add_recipe(&id, Some(2))).await,
+        Ok(ShoppingChange::Done)
+    );
+    let items = client.call(GetShoppingList {}).await.unwrap().unwrap();
+    assert_eq!(items[0].quantities_text(), "875 g");
+    assert!(!items[0].checked);
+
+    assert_eq!(
+        client.call(ClearShoppingList { only_checked: true }).await,
+        Ok(ShoppingChange::Done)
+    );
+    assert_eq!(
+        client
+            .call(GetShoppingList {})
+            .await
+            .unwrap()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    assert_eq!(
+        client.call(check("Flour", true)).await,
+        Ok(ShoppingChange::Done)
+    );
+    assert_eq!(
+        client.call(ClearShoppingList { only_checked: true }).await,
+        Ok(ShoppingChange::Done)
+    );
+    assert_eq!(client.call(GetShoppingList {}).await, Ok(Some(Vec::new())));
+}
+
+#[actix_web::test]
+async fn shopping_lists_are_per_user() {
+    let store = test_store().await;
+    let mut alice = client(store.clone()).await;
+    let mut bob = client(store).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(bob.call(signup("bob", "password2")).await, Ok(true));
+
+    assert_eq!(
+        alice.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let id = alice
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    assert_eq!(
+        bob.call(add_recipe(&id, None)).await,
+        Ok(ShoppingChange::Done)
+    );
+    assert_eq!(
+        bob.call(GetShoppingList {}).await.unwrap().unwrap().len(),
+        1
+    );
+    assert_eq!(alice.call(GetShoppingList {}).await, Ok(Some(Vec::new())));
+
+    assert_eq!(
+        bob.call(ClearShoppingList {
+            only_checked: false
+        })
+        .await,
+        Ok(ShoppingChange::Done)
+    );
+    assert_eq!(bob.call(GetShoppingList {}).await, Ok(Some(Vec::new())));
+}
+
+#[actix_web::test]
+async fn invalid_additions_are_rejected() {
+    let mut client = empty_client().await;
+
+    assert_eq!(client.call(GetShoppingList {}).await, Ok(None));
+    assert_eq!(client.call(ExportShoppingList {}).await, Ok(None));
+    assert_eq!(
+        client
+            .call(add_recipe(&ObjectId::new().to_hex(), None))
+            .await,
+        Ok(ShoppingChange::InvalidCredentials)
+    );
+
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        client
+            .call(add_recipe(&ObjectId::new().to_hex(), None))
+            .await,
+        Ok(ShoppingChange::NotFound)
+    );
+    assert_eq!(
+        client.call(add_recipe("not an id", None)).await,
+        Ok(ShoppingChange::NotFound)
+    );
+    assert_eq!(
+        client
+            .call(add_recipe(&ObjectId::new().to_hex(), Some(0)))
+            .await,
+        Ok(ShoppingChange::InvalidServings)
+    );
+}
+
+#[actix_web::test]
+async fn shopping_list_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+    client.set_cookie("name", "alice");
+    client.set_cookie("token", "token");
+
+    assert_eq!(
+        client.call(GetShoppingList {}).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+    assert_eq!(
+        client.call(check("Flour", true)).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+}
@@ -0,0 +1,226 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bson::DateTime;
+use leptos::ServerFnError;
+use recipe_final_exercise::{
+    app::{
+        nav_bar::{Signout, SignoutAll},
+        users::{SetUnitPreference, UserChange},
+    },
+    database::{store::RecipeStore, Database},
+    model::Token,
+    quantity::UnitSystem,
+};
+use sha2::{Digest, Sha256};
+
+use common::{client, empty_client, insert_user, signin, signup, test_store, FailingStore};
+
+/// Hash of `token` as stored by the database
+fn hash(token: &str) -> String {
+    STANDARD.encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Authenticated server function, to check whether the credentials are accepted
+fn set_metric() -> SetUnitPreference {
+    SetUnitPreference {
+        units: Some(UnitSystem::Metric),
+    }
+}
+
+/// Hashes of the tokens stored for `name`
+async fn stored_tokens(store: &Arc<dyn RecipeStore>, name: &str) -> Vec<String> {
+    let user = store.find_user(name).await.unwrap().unwrap();
+    user.tokens.into_iter().map(|token| token.token).collect()
+}
+
+/// Store an already expired token `token` for `name`
+async fn push_expired_token(store: &Arc<dyn RecipeStore>, name: &str, token: &str) {
+    let user = store.find_user(name).await.unwrap().unwrap();
+    store
+        .push_token(
+            user.id,
+            Token {
+                token: hash(token),
+                expiration: DateTime::from_millis(DateTime::now().timestamp_millis() - 1000),
+            },
+        )
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn signin_sets_the_credentials() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    client.clear_cookies();
+
+    assert_eq!(client.call(signin("alice", "password1")).await, Ok(true));
+    assert_eq!(client.cookie("name"), Some("alice"));
+    assert!(client
+        .cookie("token")
+        .is_some_and(|token| !token.is_empty()));
+}
+
+#[actix_web::test]
+async fn signin_gives_a_new_token_each_time() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    let first = client.cookie("token").map(str::to_string);
+
+    assert_eq!(client.call(signin("alice", "password1")).await, Ok(true));
+    assert_ne!(client.cookie("token").map(str::to_string), first);
+}
+
+#[actix_web::test]
+async fn expired_tokens_are_rejected_and_removed_on_signin() {
+    let store = test_store().await;
+    let mut client = client(store.clone()).await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    push_expired_token(&store, "alice", "expired").await;
+
+    client.set_cookie("token", "expired");
+    assert_eq!(
+        client.call(set_metric()).await,
+        Ok(UserChange::InvalidCredentials)
+    );
+
+    assert!(stored_tokens(&store, "alice")
+        .await
+        .contains(&hash("expired")));
+    assert_eq!(client.call(signin("alice", "password1")).await, Ok(true));
+    let tokens = stored_tokens(&store, "alice").await;
+    assert_eq!(tokens.len(), 2);
+    assert!(!tokens.contains(&hash("expired")));
+    assert_eq!(client.call(set_metric()).await, Ok(UserChange::Done));
+}
+
+#[actix_web::test]
+async fn expired_tokens_of_every_user_are_pruned() {
+    let store = test_store().await;
+    let mut client = client(store.clone()).await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(client.call(signup("bob", "password1")).await, Ok(true));
+    push_expired_token(&store, "alice", "expired alice").await;
+    push_expired_token(&store, "bob", "expired bob").await;
+
+    Database::with_store(store.clone())
+        .prune_expired_tokens()
+        .await
+        .unwrap();
+
+    for name in ["alice", "bob"] {
+        let tokens = stored_tokens(&store, name).await;
+        assert_eq!(tokens.len(), 1);
+        assert!(!tokens.contains(&hash(&format!("expired {}", name))));
+    }
+    // The unexpired session of bob is still valid
+    assert_eq!(client.call(set_metric()).await, Ok(UserChange::Done));
+}
+
+#[actix_web::test]
+async fn signout_revokes_only_the_current_session() {
+    let store = test_store().await;
+    let mut phone = client(store.clone()).await;
+    let mut laptop = client(store).await;
+    assert_eq!(phone.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(laptop.call(signin("alice", "password1")).await, Ok(true));
+    let token = phone.cookie("token").unwrap().to_string();
+
+    assert_eq!(phone.call(Signout {}).await, Ok(true));
+    assert_eq!(phone.cookie("token"), None);
+
+    // Reusing the revoked token is rejected
+    phone.set_cookie("name", "alice");
+    phone.set_cookie("token", &token);
+    assert_eq!(
+        phone.call(set_metric()).await,
+        Ok(UserChange::InvalidCredentials)
+    );
+    assert_eq!(phone.call(Signout {}).await, Ok(false));
+
+    assert_eq!(laptop.call(set_metric()).await, Ok(UserChange::Done));
+}
+
+#[actix_web::test]
+async fn signout_all_revokes_every_session() {
+    let store = test_store().await;
+    let mut phone = client(store.clone()).await;
+    let mut laptop = client(store).await;
+    assert_eq!(phone.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(laptop.call(signin("alice", "password1")).await, Ok(true));
+    let token = laptop.cookie("token").unwrap().to_string();
+
+    assert_eq!(laptop.call(SignoutAll {}).await, Ok(true));
+    assert_eq!(laptop.cookie("token"), None);
+
+    assert_eq!(
+        phone.call(set_metric()).await,
+        Ok(UserChange::InvalidCredentials)
+    );
+    laptop.set_cookie("name", "alice");
+    laptop.set_cookie("token", &token);
+    assert_eq!(
+        laptop.call(set_metric()).await,
+        Ok(UserChange::InvalidCredentials)
+    );
+}
+
+#[actix_web::test]
+async fn only_token_hashes_are_stored() {
+    let store = test_store().await;
+    let mut client = client(store.clone()).await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(client.call(signin("alice", "password1")).await, Ok(true));
+    let token = client.cookie("token").unwrap().to_string();
+
+    let tokens = stored_tokens(&store, "alice").await;
+    assert_eq!(tokens.len(), 2);
+    assert!(!tokens.contains(&token));
+    assert_eq!(tokens[1], hash(&token));
+}
+
+#[actix_web::test]
+async fn signin_with_wrong_password_is_rejected() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    client.clear_cookies();
+
+    assert_eq!(client.call(signin("alice", "password2")).await, Ok(false));
+    assert_eq!(client.cookie("token"), None);
+}
+
+#[actix_web::test]
+async fn signin_with_unknown_name_is_rejected() {
+    let mut client = empty_client().await;
+
+    assert_eq!(client.call(signin("bob", "password1")).await, Ok(false));
+    assert_eq!(client.cookie("token"), None);
+}
+
+#[actix_web::test]
+async fn signin_with_corrupted_password_hash_is_an_internal_error() {
+    let store = test_store().await;
+    insert_user(&store, "alice", "not a bcrypt hash").await;
+    let mut client = client(store).await;
+
+    assert_eq!(
+        client.call(signin("alice", "password1")).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+    assert_eq!(client.cookie("token"), None);
+}
+
+#[actix_web::test]
+async fn signin_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+
+    assert_eq!(
+        client.call(signin("alice", "password1")).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use bson::doc;
+use leptos::ServerFnError;
+use mongodb::error::{Error as MongoError, ErrorKind, WriteFailure};
+use recipe_final_exercise::{app::login::IsNameAvailable, database::mongo::is_duplicate_key};
+
+use common::{client, empty_client, signin, signup, FailingStore};
+
+fn is_name_available(name: &str) -> IsNameAvailable {
+    IsNameAvailable {
+        name: name.to_string(),
+    }
+}
+
+#[actix_web::test]
+async fn signup_sets_the_credentials() {
+    let mut client = empty_client().await;
+
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(client.cookie("name"), Some("alice"));
+    assert!(client
+        .cookie("token")
+        .is_some_and(|token| !token.is_empty()));
+}
+
+#[actix_web::test]
+async fn signup_with_taken_name_is_rejected() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    client.clear_cookies();
+
+    assert_eq!(client.call(signup("alice", "password2")).await, Ok(false));
+    assert_eq!(client.cookie("token"), None);
+
+    // The original account is untouched
+    assert_eq!(client.call(signin("alice", "password1")).await, Ok(true));
+    assert_eq!(client.call(signin("alice", "password2")).await, Ok(false));
+}
+
+#[actix_web::test]
+async fn name_is_available_until_signup() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(is_name_available("alice")).await, Ok(true));
+
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+
+    assert_eq!(client.call(is_name_available("alice")).await, Ok(false));
+    assert_eq!(client.call(is_name_available("bob")).await, Ok(true));
+}
+
+#[actix_web::test]
+async fn name_availability_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+
+    assert_eq!(
+        client.call(is_name_available("alice")).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+}
+
+#[test]
+fn mongodb_duplicate_key_errors_are_detected() {
+    let write_error = |code: i32| -> MongoError {
+        let error = bson::from_document(doc! { "code": code, "errmsg": "" }).unwrap();
+        ErrorKind::Write(WriteFailure::WriteError(error)).into()
+    };
+
+    assert!(is_duplicate_key(&write_error(11000)));
+    assert!(!is_duplicate_key(&write_error(121)));
+    assert!(!is_duplicate_key(&MongoError::custom("other")));
+}
+
+#[actix_web::test]
+async fn signup_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+
+    assert_eq!(
+        client.call(signup("alice", "password1")).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+    assert_eq!(client.cookie("token"), None);
+}
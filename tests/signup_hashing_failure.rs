@@ -0,0 +1,34 @@
+#![cfg(feature = "server")]
+
+//! In its own test binary because the configuration is global to the program
+
+mod common;
+
+use leptos::ServerFnError;
+use recipe_final_exercise::{app::login::Signup, config::Config};
+
+use common::empty_client;
+
+#[actix_web::test]
+async fn signup_with_hashing_failure_is_an_internal_error() {
+    // bcrypt refuses costs above 31
+    Config {
+        bcrypt_cost: 32,
+        ..Config::default()
+    }
+    .init()
+    .unwrap();
+
+    let mut client = empty_client().await;
+
+    assert_eq!(
+        client
+            .call(Signup {
+                name: "alice".to_string(),
+                password: "password1".to_string(),
+            })
+            .await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+    assert_eq!(client.cookie("token"), None);
+}
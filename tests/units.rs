@@ -0,0 +1,114 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use std::sync::Arc;
+
+use leptos::ServerFnError;
+use recipe_final_exercise::{
+    app::users::{GetUnitPreference, SetUnitPreference, UserChange},
+    quantity::{density, Quantity, UnitSystem},
+};
+
+use common::{client, empty_client, signup, test_store, FailingStore};
+
+fn converted(text: &str, ingredient: &str, system: UnitSystem) -> String {
+    Quantity::parse(text)
+        .converted(system, density(ingredient))
+        .to_string()
+}
+
+#[test]
+fn quantities_are_converted_within_their_dimension() {
+    assert_eq!(converted("1 cup", "water", UnitSystem::Metric), "237 ml");
+    assert_eq!(converted("2 tbsp", "milk", UnitSystem::Metric), "30 ml");
+    assert_eq!(converted("1 lb", "beef", UnitSystem::Metric), "454 g");
+    assert_eq!(converted("100 g", "beef", UnitSystem::Imperial), "3 1/2 oz");
+    assert_eq!(converted("1 l", "milk", UnitSystem::Imperial), "1 quart");
+}
+
+#[test]
+fn dry_ingredients_are_converted_with_their_density() {
+    assert_eq!(converted("1 cup", "flour", UnitSystem::Metric), "125 g");
+    assert_eq!(converted("500 g", "flour", UnitSystem::Imperial), "4 cups");
+    assert_eq!(converted("2 tbsp", "butter", UnitSystem::Metric), "28 g");
+}
+
+#[test]
+fn quantities_already_in_the_system_or_without_unit_are_unchanged() {
+    assert_eq!(converted("250 g", "flour", UnitSystem::Metric), "250 g");
+    assert_eq!(converted("1 cup", "flour", UnitSystem::Imperial), "1 cup");
+    assert_eq!(converted("3", "eggs", UnitSystem::Metric), "3");
+    assert_eq!(
+        converted("to taste", "salt", UnitSystem::Imperial),
+        "to taste"
+    );
+    assert_eq!(converted("a pinch", "salt", UnitSystem::Metric), "a pinch");
+}
+
+#[test]
+fn density_matches_the_end_of_the_ingredient_name() {
+    assert_eq!(density("All-purpose flour"), Some(0.53));
+    assert_eq!(density("light brown sugar"), Some(0.93));
+    assert_eq!(density("sugar"), Some(0.85));
+    assert_eq!(density("rice vinegar"), None);
+    assert_eq!(density("buttermilk"), None);
+}
+
+#[actix_web::test]
+async fn unit_preference_is_stored_per_user() {
+    let store = test_store().await;
+    let mut alice = client(store.clone()).await;
+    let mut bob = client(store).await;
+    assert_eq!(alice.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(bob.call(signup("bob", "password2")).await, Ok(true));
+
+    assert_eq!(alice.call(GetUnitPreference {}).await, Ok(None));
+
+    let metric = SetUnitPreference {
+        units: Some(UnitSystem::Metric),
+    };
+    assert_eq!(alice.call(metric).await, Ok(UserChange::Done));
+    assert_eq!(
+        alice.call(GetUnitPreference {}).await,
+        Ok(Some(UnitSystem::Metric))
+    );
+    assert_eq!(bob.call(GetUnitPreference {}).await, Ok(None));
+
+    assert_eq!(
+        alice.call(SetUnitPreference { units: None }).await,
+        Ok(UserChange::Done)
+    );
+    assert_eq!(alice.call(GetUnitPreference {}).await, Ok(None));
+}
+
+#[actix_web::test]
+async fn signed_out_users_see_quantities_as_written() {
+    let mut client = empty_client().await;
+
+    assert_eq!(client.call(GetUnitPreference {}).await, Ok(None));
+    assert_eq!(
+        client
+            .call(SetUnitPreference {
+                units: Some(UnitSystem::Imperial),
+            })
+            .await,
+        Ok(UserChange::InvalidCredentials)
+    );
+}
+
+#[actix_web::test]
+async fn unit_preference_with_database_failure_is_a_server_error() {
+    let mut client = client(Arc::new(FailingStore)).await;
+    client.set_cookie("name", "alice");
+    client.set_cookie("token", "token");
+
+    assert_eq!(
+        client.call(GetUnitPreference {}).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+    assert_eq!(
+        client.call(SetUnitPreference { units: None }).await,
+        Err(ServerFnError::ServerError("".into()))
+    );
+}
@@ -0,0 +1,193 @@
+#![cfg(feature = "server")]
+
+mod common;
+
+use recipe_final_exercise::{
+    app::{edit_recipe::UpdateRecipe, recipe::RecipeChange},
+    model::{Ingredient, Tool, SERVINGS_RANGE},
+    quantity::Quantity,
+    validation::{
+        validate_recipe, RecipeDraft, RecipeField, ValidationError, INSTRUCTION_MAX_LENGTH,
+        MAX_ENTRIES, NAME_MAX_LENGTH,
+    },
+};
+
+use common::{empty_client, get_recipes, new_recipe, signup};
+
+#[actix_web::test]
+async fn invalid_recipes_are_rejected_with_every_error() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+
+    let mut recipe = new_recipe("  ");
+    recipe.icon_url = "pancakes.png".to_string();
+    recipe.price_level = 5;
+    recipe.instructions = vec!["Mix".to_string(), String::new()];
+    recipe.ingredients.push(Ingredient {
+        name: "Milk".to_string(),
+        icon_url: "ftp://example.com/milk.png".to_string(),
+        quantity: Quantity::parse(&"a".repeat(51)),
+    });
+    recipe.tools.push(Tool {
+        name: String::new(),
+        icon_url: String::new(),
+    });
+
+    assert_eq!(
+        client.call(recipe).await,
+        Ok(RecipeChange::Invalid(vec![
+            ValidationError::Required(RecipeField::Name),
+            ValidationError::InvalidUrl(RecipeField::IconUrl),
+            ValidationError::OutOfRange {
+                field: RecipeField::PriceLevel,
+                min: 0,
+                max: 4
+            },
+            ValidationError::Required(RecipeField::Instruction(1)),
+            ValidationError::InvalidUrl(RecipeField::IngredientIconUrl(1)),
+            ValidationError::TooLong {
+                field: RecipeField::IngredientQuantity(1),
+                max: 50
+            },
+            ValidationError::Required(RecipeField::ToolName(1)),
+        ]))
+    );
+    assert!(client.call(get_recipes()).await.unwrap().recipes.is_empty());
+}
+
+#[actix_web::test]
+async fn invalid_updates_are_rejected() {
+    let mut client = empty_client().await;
+    assert_eq!(client.call(signup("alice", "password1")).await, Ok(true));
+    assert_eq!(
+        client.call(new_recipe("Pancakes")).await,
+        Ok(RecipeChange::Done)
+    );
+    let id = client
+        .call(get_recipes())
+        .await
+        .unwrap()
+        .recipes
+        .remove(0)
+        .id;
+
+    let update = UpdateRecipe {
+        id,
+        name: "P".repeat(NAME_MAX_LENGTH + 1),
+        icon_url: String::new(),
+        price_level: 1,
+        healthy_level: 9,
+        servings: 4,
+        instructions: Vec::new(),
+        ingredients: Vec::new(),
+        tools: Vec::new(),
+        categories: Vec::new(),
+    };
+
+    assert_eq!(
+        client.call(update).await,
+        Ok(RecipeChange::Invalid(vec![
+            ValidationError::TooLong {
+                field: RecipeField::Name,
+                max: NAME_MAX_LENGTH
+            },
+            ValidationError::OutOfRange {
+                field: RecipeField::HealthyLevel,
+                min: 0,
+                max: 4
+            },
+        ]))
+    );
+    let recipes = client.call(get_recipes()).await.unwrap().recipes;
+    assert_eq!(recipes[0].name, "Pancakes");
+}
+
+/// Smallest valid recipe
+fn soup() -> RecipeDraft<'static> {
+    RecipeDraft {
+        name: "Soup",
+        icon_url: "",
+        price_level: 0,
+        healthy_level: 0,
+        servings: 1,
+        instructions: &[],
+        ingredients: &[],
+        tools: &[],
+    }
+}
+
+#[test]
+fn optional_fields_can_be_empty() {
+    let ingredients = [Ingredient {
+        name: "Salt".to_string(),
+        icon_url: String::new(),
+        quantity: Quantity::default(),
+    }];
+
+    assert_eq!(
+        validate_recipe(&RecipeDraft {
+            ingredients: &ingredients,
+            ..soup()
+        }),
+        []
+    );
+}
+
+#[test]
+fn urls_need_an_http_scheme_and_a_host() {
+    let errors = |icon_url: &str| validate_recipe(&RecipeDraft { icon_url, ..soup() });
+
+    for url in ["https://example.com/soup.png", "http://localhost:3000"] {
+        assert_eq!(errors(url), [], "{url}");
+    }
+    for url in [
+        "example.com",
+        "https://",
+        "https:///soup.png",
+        "http://a b.com",
+    ] {
+        assert_eq!(
+            errors(url),
+            [ValidationError::InvalidUrl(RecipeField::IconUrl)],
+            "{url}"
+        );
+    }
+}
+
+#[test]
+fn servings_are_bounded() {
+    for servings in [0, SERVINGS_RANGE.end() + 1] {
+        assert_eq!(
+            validate_recipe(&RecipeDraft { servings, ..soup() }),
+            [ValidationError::OutOfRange {
+                field: RecipeField::Servings,
+                min: 1,
+                max: 100
+            }]
+        );
+    }
+}
+
+#[test]
+fn lengths_are_counted_in_characters_and_lists_are_bounded() {
+    let instructions = ["é".repeat(INSTRUCTION_MAX_LENGTH)];
+    assert_eq!(
+        validate_recipe(&RecipeDraft {
+            instructions: &instructions,
+            ..soup()
+        }),
+        []
+    );
+
+    let instructions = vec!["Stir".to_string(); MAX_ENTRIES + 1];
+    assert_eq!(
+        validate_recipe(&RecipeDraft {
+            instructions: &instructions,
+            ..soup()
+        }),
+        [ValidationError::TooMany {
+            field: RecipeField::Instructions,
+            max: MAX_ENTRIES
+        }]
+    );
+}